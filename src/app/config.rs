@@ -1,3 +1,4 @@
+use crate::core::demand::adapters::DeclarativeAdapterConfig;
 use crate::core::models::advertiser::Advertiser;
 use crate::core::models::bidder::{Bidder, Endpoint};
 use crate::core::models::buyer::Buyer;
@@ -7,6 +8,8 @@ use crate::core::models::deal::Deal;
 use crate::core::models::placement::Placement;
 use crate::core::models::property::Property;
 use crate::core::models::publisher::Publisher;
+use crate::core::models::secret::SecretRef;
+use crate::core::models::supply_rule::SupplyRule;
 use config::Config;
 use derive_builder::Builder;
 use rtb::server::TlsConfig;
@@ -35,6 +38,243 @@ impl Default for CacheConfig {
     }
 }
 
+/// Where exchange rates are loaded from. Rate documents hold a
+/// `base` code and `rates` of units per one unit of base
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FxRatesSource {
+    /// No rates, prices in any currency are passed through unconverted
+    #[default]
+    None,
+    /// Local json file
+    File {
+        path: PathBuf,
+        #[serde(with = "humantime_serde", default = "default_fx_refresh")]
+        refresh: Duration,
+    },
+    /// Single Firestore document
+    Firestore {
+        collection: String,
+        document: String,
+        #[serde(with = "humantime_serde", default = "default_fx_refresh")]
+        refresh: Duration,
+    },
+}
+
+fn default_fx_refresh() -> Duration {
+    Duration::from_secs(60 * 60)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrencyConfig {
+    /// Currency floors, bids, deal prices and budgets are normalized into
+    pub accounting: String,
+    pub rates: FxRatesSource,
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> Self {
+        Self {
+            accounting: "USD".to_string(),
+            rates: FxRatesSource::None,
+        }
+    }
+}
+
+/// Learned floor prices per publisher, placement, country,
+/// device type and format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FloorsConfig {
+    /// Apply learned floors. Static publisher floor rules apply regardless
+    pub dynamic: bool,
+    /// Bid price quantile used as the learned floor
+    pub quantile: f64,
+    /// Fraction of auctions sent without a learned floor, the only
+    /// auctions learned from so the floors never ratchet themselves up
+    pub explore_rate: f32,
+    /// Exploration bids observed before a learned floor is used
+    pub min_samples: u64,
+    /// Learned floors are never raised above this
+    pub max_floor: f64,
+    /// Cap on tracked floor keys, new keys beyond it are not learned
+    pub max_keys: usize,
+}
+
+impl Default for FloorsConfig {
+    fn default() -> Self {
+        Self {
+            dynamic: false,
+            quantile: 0.2,
+            explore_rate: 0.05,
+            min_samples: 200,
+            max_floor: 20.0,
+            max_keys: 100_000,
+        }
+    }
+}
+
+/// Traffic sampling for availability forecasts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForecastConfig {
+    pub enabled: bool,
+    /// Fraction of enriched auctions sampled into the reservoir
+    pub sample_rate: f32,
+    /// Reservoir size, the oldest samples are evicted beyond it
+    pub max_samples: usize,
+    /// Forecasts evaluated per minute on each node, beyond it
+    /// the forecast route answers 429
+    pub max_requests_per_minute: u32,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: 0.01,
+            max_samples: 20_000,
+            max_requests_per_minute: 30,
+        }
+    }
+}
+
+/// Sent to bidders as a user.data entry holding the user's segments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentUserDataConfig {
+    /// data.name, e.g. the exchange domain
+    pub name: String,
+    /// Segment taxonomy id, sent as data.id
+    pub taxonomy_id: String,
+}
+
+/// First party audience segments. Memberships are held in memory on
+/// the node whose segment pixel recorded them, so aren't shared across
+/// a cluster and are lost on restart. Behind a load balancer without
+/// rxid affinity, segment targeting only sees a node's share of users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SegmentsConfig {
+    /// Membership ttl when the segment pixel doesn't specify one
+    #[serde(with = "humantime_serde")]
+    pub default_ttl: Duration,
+    /// Longest membership ttl a segment pixel may set
+    #[serde(with = "humantime_serde")]
+    pub max_ttl: Duration,
+    /// Cap on segments per user
+    pub max_per_user: usize,
+    /// Cap on users held, the least recently used are evicted beyond it
+    pub max_users: u64,
+    /// Share segments with bidders which opt in, if set
+    pub user_data: Option<SegmentUserDataConfig>,
+}
+
+impl Default for SegmentsConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl: Duration::from_hours(24 * 30),
+            max_ttl: Duration::from_hours(24 * 90),
+            max_per_user: 100,
+            max_users: 5_000_000,
+            user_data: None,
+        }
+    }
+}
+
+/// Order bidders are synced in when a sync call can't reach all of them
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncOrder {
+    /// Highest bidder sync priority first, ties in config order
+    #[default]
+    Priority,
+    /// Each call starts with the next bidder along, so
+    /// capped calls still spread syncs across bidders
+    Rotate,
+    /// Randomly, weighted by each bidder's sync weight scaled by its
+    /// share of recent revenue, so high value matches are synced most
+    /// without starving the rest. See [`UserSyncConfig::revenue_explore`]
+    Weighted,
+}
+
+/// Outbound user syncing on /sync/out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSyncConfig {
+    /// How long a stored buyeruid is fresh for, unless the bidder sets
+    /// its own resync interval. Bidders with fresh buyeruids aren't synced
+    #[serde(with = "humantime_serde")]
+    pub fresh_for: Duration,
+    /// Most bidder pixels dropped in one iframe sync call
+    pub max_syncs_per_call: usize,
+    /// Most bidders redirected through in one sync chain
+    pub max_chain_syncs: usize,
+    pub order: SyncOrder,
+    /// Window recent bidder revenue is measured over for weighted
+    /// order, revenue from the previous window also counts
+    #[serde(with = "humantime_serde")]
+    pub revenue_window: Duration,
+    /// Revenue share added to every bidder's own in weighted order,
+    /// so bidders without recent revenue are still synced. A bidder
+    /// with all the revenue is weighted (1 + explore) / explore times
+    /// one with none
+    pub revenue_explore: f64,
+}
+
+impl Default for UserSyncConfig {
+    fn default() -> Self {
+        Self {
+            fresh_for: Duration::from_hours(24 * 7),
+            max_syncs_per_call: 5,
+            max_chain_syncs: 3,
+            order: SyncOrder::default(),
+            revenue_window: Duration::from_hours(1),
+            revenue_explore: 0.1,
+        }
+    }
+}
+
+/// Thresholds for the per endpoint callout circuit breaker.
+/// Disabled unless opted in, defaults shown per field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Off by default
+    pub enabled: bool,
+    /// Outcomes are evaluated over tumbling windows of this length, 10s
+    #[serde(with = "humantime_serde")]
+    pub window: Duration,
+    /// Minimum callouts within a window before it can trip, 100
+    pub min_samples: u32,
+    /// Fraction of errors and 5xx responses which trips the breaker, 0.5
+    pub error_rate: f32,
+    /// Fraction of timeouts which trips the breaker, 0.8
+    pub timeout_rate: f32,
+    /// How long the breaker stays open before probes may close it, 30s
+    #[serde(with = "humantime_serde")]
+    pub open_for: Duration,
+    /// Fraction of callouts still sent to an open endpoint as probes, 0.01
+    pub probe_rate: f32,
+    /// Healthy probes required before the breaker closes again, 10
+    pub recovery_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: Duration::from_secs(10),
+            min_samples: 100,
+            error_rate: 0.5,
+            timeout_rate: 0.8,
+            open_for: Duration::from_secs(30),
+            probe_rate: 0.01,
+            recovery_probes: 10,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Builder)]
 pub struct EventConfig {
     /// The public domain to be used when building event notificaion urls, e.g. burl
@@ -55,6 +295,10 @@ pub enum ClusterConfig {
     /// Auto discovery of cluster peers & updates
     /// via k8s api
     K8s,
+    /// Static list of peer hosts, excluding self,
+    /// for bare metal deployments which want
+    /// peer coordination such as QPS sharing
+    Peers(Vec<String>),
 }
 
 impl Default for ClusterConfig {
@@ -63,6 +307,35 @@ impl Default for ClusterConfig {
     }
 }
 
+/// How the node-local share of an endpoint's cluster-wide
+/// QPS limit is derived
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QpsLimitMode {
+    /// Every node takes an even qps / cluster size share
+    #[default]
+    Static,
+    /// Nodes exchange their recent per endpoint demand with
+    /// discovered peers and size local shares proportionally
+    /// to it. Falls back to static when any peer is unreachable
+    Coordinated {
+        /// How often demand is exchanged and quotas rebalanced
+        #[serde(with = "humantime_serde", default = "default_qps_sync_interval")]
+        interval: Duration,
+        /// Port peers serve their demand report on
+        #[serde(default = "default_qps_peer_port")]
+        peer_port: u16,
+    },
+}
+
+fn default_qps_sync_interval() -> Duration {
+    Duration::from_secs(2)
+}
+
+fn default_qps_peer_port() -> u16 {
+    80
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FirestoreConfig {
     pub project_id: String,
@@ -97,9 +370,42 @@ pub struct RexConfig {
     pub deals: Option<Vec<Deal>>,
     pub buyers: Option<Vec<Buyer>>,
     pub advertisers: Option<Vec<Advertiser>>,
+    /// Supply quality rules blocking or flagging requests. The built
+    /// in spoofed premium bundle rules always apply alongside, unless
+    /// replaced by a rule of the same id, e.g. to only flag them
+    pub supply_rules: Option<Vec<SupplyRule>>,
     pub notifications: EventConfig,
     #[serde(default)]
     pub cluster: ClusterConfig,
+    /// How endpoint QPS limits are shared between cluster nodes
+    #[serde(default)]
+    pub qps_limit_mode: QpsLimitMode,
+    /// Bearer token required on internal routes such as the cluster
+    /// QPS exchange, forecasts and deal funnel reports. Those routes are
+    /// refused while unset, and coordinated QPS limits won't start without it
+    #[serde(default)]
+    pub internal_token: Option<SecretRef>,
+    /// Accounting currency and exchange rates
+    #[serde(default)]
+    pub currency: CurrencyConfig,
+    /// Learned floor prices
+    #[serde(default)]
+    pub floors: FloorsConfig,
+    /// Sampled traffic for deal and campaign availability forecasts
+    #[serde(default)]
+    pub forecast: ForecastConfig,
+    /// First party audience segments and sharing them with bidders
+    #[serde(default)]
+    pub segments: SegmentsConfig,
+    /// Outbound user sync chains on /sync/out
+    #[serde(default)]
+    pub usersync: UserSyncConfig,
+    /// Config driven bidder adapters, selectable by bidders by name
+    #[serde(default)]
+    pub adapters: Vec<DeclarativeAdapterConfig>,
+    /// Suppresses callouts to endpoints which are erroring or timing out
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
     #[serde(default)]
     pub logging: LoggingConfig,
     pub schain: Option<SchainConfig>,
@@ -112,6 +418,11 @@ pub struct RexConfig {
     /// is host-only (suitable for local development)
     #[serde(default)]
     pub cookie_domain: Option<String>,
+    /// EID source our rxid is shared with bidders under, e.g. the
+    /// exchange domain. Defaults to the cookie domain without any
+    /// leading dot, when neither is set the rxid isn't added as an EID
+    #[serde(default)]
+    pub rxid_eid_source: Option<String>,
     /// Skip IP/bot datacenter blocking in the enrichment pipeline.
     /// Useful for local development or when traffic is pre-filtered.
     #[serde(default)]
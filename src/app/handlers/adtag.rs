@@ -224,5 +224,7 @@ fn block_reason_phrase(reason: &PublisherBlockReason) -> &'static str {
         PublisherBlockReason::MissingAppSite => "Missing App Or Site",
         PublisherBlockReason::MissingAppSiteDomain => "Missing Domain Or Bundle",
         PublisherBlockReason::TmaxTooLow => "Auction Tmax Too Low",
+        PublisherBlockReason::UnsupportedCurrency => "Unsupported Currency",
+        PublisherBlockReason::SupplyRule(_) => "Blocked By Supply Rule",
     }
 }
@@ -0,0 +1,18 @@
+use crate::app::http::is_internal_authorized;
+use crate::core::cluster::QpsCoordinator;
+use actix_web::{HttpRequest, HttpResponse};
+use std::sync::Arc;
+
+/// Serves this node's recent per endpoint demand to
+/// peers for coordinated QPS limit sharing
+pub async fn qps_usage_handler(
+    http_req: HttpRequest,
+    coordinator: Arc<QpsCoordinator>,
+    internal_token: Option<Arc<str>>,
+) -> HttpResponse {
+    if !is_internal_authorized(&http_req, internal_token.as_deref()) {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    HttpResponse::Ok().json(coordinator.usage_report())
+}
@@ -0,0 +1,56 @@
+use crate::app::handlers::sync::apply_debug_cors;
+use crate::app::http::is_internal_authorized;
+use crate::app::pipeline::ortb::forecast::Forecaster;
+use crate::core::managers::DealManager;
+use crate::core::models::targeting::CommonTargeting;
+use actix_web::rt::task::spawn_blocking;
+use actix_web::{HttpRequest, HttpResponse, web};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Forecasts availability for a proposed deal or campaign targeting
+/// from sampled recent traffic: projected daily impressions, the
+/// floor distribution and overlap with currently active deals.
+/// Requires the internal token and is rate limited per node
+pub async fn forecast_handler(
+    targeting: web::Json<CommonTargeting>,
+    http_req: HttpRequest,
+    forecaster: Option<Arc<Forecaster>>,
+    deal_manager: Arc<DealManager>,
+    internal_token: Option<Arc<str>>,
+) -> HttpResponse {
+    if !is_internal_authorized(&http_req, internal_token.as_deref()) {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let Some(forecaster) = forecaster else {
+        return HttpResponse::ServiceUnavailable().body("Forecasting is not enabled");
+    };
+
+    if !forecaster.try_acquire() {
+        return HttpResponse::TooManyRequests().body("Forecast rate limit exceeded");
+    }
+
+    let mut targeting = targeting.into_inner();
+    if let Err(e) = targeting.validate() {
+        return HttpResponse::BadRequest().body(format!("Invalid targeting: {}", e));
+    }
+    targeting.normalize();
+
+    let deals = deal_manager.active_deals();
+
+    // evaluating the whole reservoir is too slow for the event loop
+    let forecast = spawn_blocking(move || forecaster.evaluate(&targeting, &deals)).await;
+
+    match forecast {
+        Ok(forecast) => {
+            let mut response = HttpResponse::Ok();
+            apply_debug_cors(&http_req, &mut response);
+            response.json(forecast)
+        }
+        Err(e) => {
+            warn!("Failed to evaluate forecast: {}", e);
+            HttpResponse::InternalServerError().body("Failed to evaluate forecast")
+        }
+    }
+}
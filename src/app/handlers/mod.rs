@@ -1,6 +1,11 @@
 pub mod adtag;
 pub mod billing;
+pub mod cluster;
 pub mod creative_serving;
+pub mod forecast;
+pub mod optout;
 pub mod profile;
+pub mod report;
 pub mod rtb;
+pub mod segments;
 pub mod sync;
@@ -0,0 +1,89 @@
+use crate::app::http::{build_optout_cookie, build_rxid_removal_cookie, extract_cookies};
+use crate::core::segments::SegmentStore;
+use crate::core::usersync;
+use crate::core::usersync::SyncStore;
+use actix_web::{HttpRequest, HttpResponse};
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+use serde::Serialize;
+use std::sync::{Arc, LazyLock};
+use tracing::debug;
+
+static COUNTER_OPTOUTS: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:supply:syncing")
+        .u64_counter("syncing.optouts")
+        .with_description("Count of users opting out of tracking")
+        .with_unit("1")
+        .build()
+});
+
+const OPTOUT_CONFIRM_HTML: &str = "<!DOCTYPE html><html><body>\
+    <p>Opt out of interest based advertising from this exchange on this browser?</p>\
+    <form method=\"post\" action=\"/optout\"><button type=\"submit\">Opt out</button></form>\
+    </body></html>";
+
+const OPTOUT_HTML: &str = "<!DOCTYPE html><html><body>\
+    <p>You have opted out of interest based advertising from this exchange.</p>\
+    <p>The opt out is stored in a cookie on this browser, clearing your cookies undoes it.</p>\
+    </body></html>";
+
+#[derive(Serialize)]
+struct OptOutStatus {
+    opted_out: bool,
+}
+
+/// Confirmation page for opting out, which posts to [`optout_handler`]
+/// so that prefetches and stray GETs can't change the user's choice
+pub async fn optout_confirm_handler() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html")
+        .insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"))
+        .body(OPTOUT_CONFIRM_HTML)
+}
+
+/// Opts the user out of tracking: purges their stored syncs and
+/// segment memberships, drops the rxid cookie and sets the opt out
+/// cookie, which stops us from issuing an rxid, syncing, segmenting
+/// or passing their ids to bidders
+pub async fn optout_handler(
+    http_req: HttpRequest,
+    sync_store: Arc<dyn SyncStore>,
+    segment_store: Arc<dyn SegmentStore>,
+) -> HttpResponse {
+    let cookies = extract_cookies(&http_req);
+
+    let purged = match cookies.get(usersync::constants::CONST_REX_COOKIE_ID_PARAM) {
+        Some(local_uid) if usersync::utils::validate_local_id(local_uid) => {
+            let entries = sync_store.remove(local_uid).await;
+            let segments = segment_store.remove(local_uid).await;
+
+            debug!(
+                "Opted out {}, purged {} sync entries and {} segments",
+                local_uid,
+                entries.as_ref().map_or(0, |e| e.len()),
+                segments
+            );
+
+            entries.is_some() || segments > 0
+        }
+        _ => false,
+    };
+
+    COUNTER_OPTOUTS.add(1, &[KeyValue::new("purged_syncs", purged)]);
+
+    HttpResponse::Ok()
+        .content_type("text/html")
+        .insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"))
+        .cookie(build_rxid_removal_cookie())
+        .cookie(build_optout_cookie())
+        .body(OPTOUT_HTML)
+}
+
+/// Whether the user is opted out, e.g. for a privacy page
+pub async fn optout_status_handler(http_req: HttpRequest) -> HttpResponse {
+    let opted_out = usersync::utils::is_opted_out(&extract_cookies(&http_req));
+
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"))
+        .json(OptOutStatus { opted_out })
+}
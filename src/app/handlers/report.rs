@@ -0,0 +1,61 @@
+use crate::app::handlers::sync::apply_debug_cors;
+use crate::app::http::is_internal_authorized;
+use crate::core::firestore::counters::deal::DealCounterStore;
+use actix_web::{HttpRequest, HttpResponse, web};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Longest range in days a single funnel report may span
+const MAX_REPORT_DAYS: i64 = 31;
+
+#[derive(Deserialize)]
+pub struct ReportRange {
+    /// Range start, defaults to 24 hours before `to`
+    from: Option<DateTime<Utc>>,
+    /// Range end, defaults to now
+    to: Option<DateTime<Utc>>,
+}
+
+/// Serves the per bidder delivery funnel of a deal over an
+/// hour granular time range, for troubleshooting deals which
+/// aren't delivering. Requires the internal token and is rate
+/// limited per node
+pub async fn deal_funnel_handler(
+    deal_id: String,
+    range: web::Query<ReportRange>,
+    http_req: HttpRequest,
+    deal_store: Option<Arc<DealCounterStore>>,
+    internal_token: Option<Arc<str>>,
+) -> HttpResponse {
+    if !is_internal_authorized(&http_req, internal_token.as_deref()) {
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let Some(deal_store) = deal_store else {
+        return HttpResponse::ServiceUnavailable().body("Deal counters are not enabled");
+    };
+
+    if !deal_store.try_acquire_report() {
+        return HttpResponse::TooManyRequests().body("Report rate limit exceeded");
+    }
+
+    let to = range.to.unwrap_or_else(Utc::now);
+    let from = range.from.unwrap_or(to - Duration::hours(24));
+
+    if from >= to || to - from > Duration::days(MAX_REPORT_DAYS) {
+        return HttpResponse::BadRequest().body("Invalid report range");
+    }
+
+    let mut response = HttpResponse::Ok();
+    apply_debug_cors(&http_req, &mut response);
+
+    match deal_store.funnel_report(&deal_id, from, to).await {
+        Ok(report) => response.json(report),
+        Err(e) => {
+            warn!("Failed to query funnel for deal {}: {}", deal_id, e);
+            HttpResponse::InternalServerError().body("Failed to query deal funnel")
+        }
+    }
+}
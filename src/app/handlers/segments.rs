@@ -0,0 +1,73 @@
+use crate::app::config::SegmentsConfig;
+use crate::app::http::{PIXEL_GIF, build_rxid_cookie, extract_cookies};
+use crate::app::pipeline::syncing::utils::extract_or_assign_local_uid;
+use crate::core::segments::{self, SegmentStore};
+use crate::core::usersync;
+use actix_web::{HttpRequest, HttpResponse, web};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+#[derive(Deserialize)]
+pub struct SegmentPixelQuery {
+    /// Comma separated segment ids to add the user to
+    s: String,
+    /// Membership ttl in seconds, capped at the configured max
+    ttl: Option<u64>,
+}
+
+/// Segment pixel, adds the cookied user to first party segments,
+/// e.g. fired from an advertiser site or a campaign's creative.
+/// Users without an rxid cookie are assigned one, so later
+/// auctions can recognize them
+pub async fn segment_pixel_handler(
+    query: web::Query<SegmentPixelQuery>,
+    http_req: HttpRequest,
+    store: Arc<dyn SegmentStore>,
+    config: SegmentsConfig,
+) -> HttpResponse {
+    let segment_ids: Vec<&str> = query
+        .s
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if segment_ids.is_empty() || !segment_ids.iter().all(|s| segments::validate_segment_id(s)) {
+        return HttpResponse::BadRequest().body("Invalid segment ids");
+    }
+
+    let ttl = query
+        .ttl
+        .map(Duration::from_secs)
+        .unwrap_or(config.default_ttl)
+        .min(config.max_ttl);
+
+    let cookies = extract_cookies(&http_req);
+
+    // acknowledged, but opted out users aren't issued an rxid or segmented
+    if usersync::utils::is_opted_out(&cookies) {
+        return HttpResponse::Ok()
+            .content_type("image/gif")
+            .insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"))
+            .body(PIXEL_GIF);
+    }
+
+    let (local_uid, _) = extract_or_assign_local_uid(&cookies);
+
+    for segment_id in &segment_ids {
+        store.add(&local_uid, segment_id, ttl).await;
+    }
+
+    debug!(
+        "Added {} to segments {:?} for {:?}",
+        local_uid, segment_ids, ttl
+    );
+
+    HttpResponse::Ok()
+        .content_type("image/gif")
+        .insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"))
+        .cookie(build_rxid_cookie(&local_uid))
+        .body(PIXEL_GIF)
+}
@@ -1,10 +1,11 @@
-use crate::app::http::{build_rxid_cookie, extract_cookies};
+use crate::app::http::{PIXEL_GIF, build_rxid_cookie, build_sync_chain_cookie, extract_cookies};
 use crate::app::pipeline::syncing::r#in::context::SyncInContext;
-use crate::app::pipeline::syncing::out::context::{SyncOutContext, SyncResponse};
+use crate::app::pipeline::syncing::out::context::{SyncMode, SyncOutContext, SyncResponse};
 use crate::app::pipeline::syncing::utils;
 use crate::core::managers::{DemandManager, PublisherManager};
 use crate::core::usersync;
 use crate::core::usersync::SyncStore;
+use crate::core::usersync::chain::SyncChain;
 use actix_web::{HttpRequest, HttpResponse, Responder};
 use anyhow::Error;
 use pipeline::Pipeline;
@@ -18,7 +19,8 @@ pub async fn sync_out_handler(
     pipeline: Arc<Pipeline<SyncOutContext, Error>>,
 ) -> impl Responder {
     let cookies = extract_cookies(&http_req);
-    let context = SyncOutContext::new(pubid, cookies);
+    let mode = SyncMode::from_query(http_req.query_string());
+    let context = SyncOutContext::new(pubid, cookies, mode);
 
     if let Err(e) = pipeline.run(&context).await {
         debug!("Sync-out pipeline aborted early: {}", e);
@@ -65,9 +67,39 @@ pub async fn sync_out_handler(
             }
             response.finish()
         }
+        SyncResponse::Redirect(url) => {
+            debug!("Sync-out redirecting to: {}", url);
+            let mut response = HttpResponse::Found();
+            response.insert_header(("Location", url.as_str()));
+            response.insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"));
+            if let Some(uid) = local_uid {
+                response.cookie(build_rxid_cookie(uid));
+            }
+            response.cookie(build_sync_chain_cookie(
+                context.chain.get().map(SyncChain::encode),
+            ));
+            response.finish()
+        }
+        SyncResponse::Pixel => {
+            let mut response = HttpResponse::Ok();
+            response.content_type("image/gif");
+            response.insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"));
+            if let Some(uid) = local_uid {
+                response.cookie(build_rxid_cookie(uid));
+            }
+            response.cookie(build_sync_chain_cookie(None));
+            response.body(PIXEL_GIF)
+        }
     }
 }
 
+/// Sync-out url of the next hop in a redirect sync chain
+fn sync_chain_next_hop(chain: &SyncChain) -> String {
+    let pubid: String = url::form_urlencoded::byte_serialize(chain.pubid.as_bytes()).collect();
+
+    format!("/sync/out/{}?mode=redirect", pubid)
+}
+
 pub async fn sync_in_handler(
     http_req: HttpRequest,
     pipeline: Arc<Pipeline<SyncInContext, Error>>,
@@ -77,7 +109,23 @@ pub async fn sync_in_handler(
 
     let pipeline_ok = pipeline.run(&context).await.is_ok();
 
-    let mut response = if pipeline_ok {
+    // mid chain, hand back to sync-out for the next bidder even if
+    // this bidder's return was bad, as it's already been attempted.
+    // Only for the bidder the chain redirected to, other syncs
+    // landing while the chain cookie lives are answered as usual
+    let partner_id = context.event.get().map(|event| event.partner_id.as_str());
+    let chain = context
+        .cookies
+        .get(usersync::constants::CONST_REX_SYNC_CHAIN_COOKIE)
+        .and_then(|value| SyncChain::decode(value))
+        .filter(|chain| partner_id.is_some_and(|id| chain.awaits(id)));
+
+    let mut response = if let Some(chain) = chain {
+        let mut response = HttpResponse::Found();
+        response.insert_header(("Location", sync_chain_next_hop(&chain)));
+        response.insert_header(("Cache-Control", "no-cache, no-store, must-revalidate"));
+        response
+    } else if pipeline_ok {
         HttpResponse::Ok()
     } else {
         HttpResponse::BadRequest()
@@ -168,6 +216,9 @@ pub async fn sync_debug_handler(
             .map(|url| crate::core::models::sync::SyncConfig {
                 kind: crate::core::models::sync::SyncKind::Image,
                 url: url.clone(),
+                priority: 0,
+                weight: 1.0,
+                resync_interval: None,
             })
     });
 
@@ -3,12 +3,26 @@ use crate::core::usersync;
 use actix_web::HttpRequest;
 use actix_web::cookie::time::Duration as CookieDuration;
 use actix_web::cookie::{Cookie, SameSite};
+use actix_web::http::header::AUTHORIZATION;
 use std::collections::HashMap;
 use std::net::IpAddr;
 use std::sync::OnceLock;
 
 pub static COOKIE_DOMAIN: OnceLock<Option<String>> = OnceLock::new();
 
+/// Transparent 1x1 gif served by pixel endpoints
+pub const PIXEL_GIF: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+/// How long an opt out lasts, renewed by opting out again
+const OPTOUT_COOKIE_DAYS: i64 = 365 * 5;
+
+/// How long a redirect sync chain may take between hops
+const SYNC_CHAIN_COOKIE_MINUTES: i64 = 5;
+
 pub fn extract_client_ip(req: &HttpRequest) -> Option<IpAddr> {
     let headers = req.headers();
 
@@ -32,6 +46,25 @@ pub fn extract_client_ip(req: &HttpRequest) -> Option<IpAddr> {
     req.peer_addr().map(|a| a.ip())
 }
 
+/// Whether the request carries the internal bearer token. Internal
+/// routes are refused outright while no token is configured
+pub fn is_internal_authorized(http_req: &HttpRequest, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return false;
+    };
+
+    http_req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 pub fn extract_http_context(req: &HttpRequest) -> HttpRequestContext {
     let headers = req.headers();
 
@@ -89,3 +122,54 @@ pub fn build_rxid_cookie(uid: &str) -> Cookie<'_> {
 
     builder.finish()
 }
+
+/// Removal cookie for the rxid, e.g. when the user opts out
+pub fn build_rxid_removal_cookie() -> Cookie<'static> {
+    let mut cookie = build_rxid_cookie("").into_owned();
+    cookie.make_removal();
+    cookie
+}
+
+/// Cookie marking the user as opted out of tracking
+pub fn build_optout_cookie() -> Cookie<'static> {
+    let mut builder = Cookie::build(usersync::constants::CONST_REX_OPTOUT_COOKIE, "1")
+        .path("/")
+        .secure(true)
+        .http_only(true)
+        .same_site(SameSite::None)
+        .max_age(CookieDuration::days(OPTOUT_COOKIE_DAYS));
+
+    if let Some(Some(domain)) = COOKIE_DOMAIN.get() {
+        builder = builder.domain(domain.clone());
+    }
+
+    builder.finish()
+}
+
+/// Cookie carrying redirect sync chain state between hops. A
+/// None value builds the removal cookie, ending the chain
+pub fn build_sync_chain_cookie(value: Option<String>) -> Cookie<'static> {
+    let ended = value.is_none();
+
+    let mut builder = Cookie::build(
+        usersync::constants::CONST_REX_SYNC_CHAIN_COOKIE,
+        value.unwrap_or_default(),
+    )
+    .path("/")
+    .secure(true)
+    .http_only(true)
+    .same_site(SameSite::None)
+    .max_age(CookieDuration::minutes(SYNC_CHAIN_COOKIE_MINUTES));
+
+    if let Some(Some(domain)) = COOKIE_DOMAIN.get() {
+        builder = builder.domain(domain.clone());
+    }
+
+    let mut cookie = builder.finish();
+
+    if ended {
+        cookie.make_removal();
+    }
+
+    cookie
+}
@@ -6,9 +6,12 @@ use crate::app::pipeline::ortb::AuctionContext;
 use crate::app::pipeline::ortb::direct::pacing::{
     DealImpressionTracker, DealPacer, SpendPacer, SpendTracker,
 };
+use crate::app::pipeline::ortb::forecast::Forecaster;
 use crate::app::pipeline::syncing::r#in::context::SyncInContext;
 use crate::app::pipeline::syncing::out::context::SyncOutContext;
-use crate::core::cluster::ClusterDiscovery;
+use crate::core::cluster::{ClusterDiscovery, QpsCoordinator};
+use crate::core::currency::CurrencyService;
+use crate::core::demand::adapters::AdapterRegistry;
 use crate::core::demand::notifications::DemandNotificationsCache;
 use crate::core::enrichment::device::DeviceLookup;
 use crate::core::filters::bot::IpRiskFilter;
@@ -18,10 +21,11 @@ use crate::core::firestore::counters::demand::DemandCounterStore;
 use crate::core::firestore::counters::publisher::PublisherCounterStore;
 use crate::core::managers::{
     AdvertiserManager, BuyerManager, CampaignManager, CreativeManager, DealManager, DemandManager,
-    PlacementManager, PropertyManager, PublisherManager, ShaperManager,
+    PlacementManager, PropertyManager, PublisherManager, ShaperManager, SupplyRuleManager,
 };
 use crate::core::observability::ObservabilityProviders;
-use crate::core::usersync::SyncStore;
+use crate::core::segments::SegmentStore;
+use crate::core::usersync::{SyncRevenue, SyncStore};
 use anyhow::Error;
 use firestore::FirestoreDb;
 use pipeline::Pipeline;
@@ -54,16 +58,30 @@ pub struct StartupContext {
     pub campaign_manager: OnceLock<Arc<CampaignManager>>,
     /// Maintains list of creatives for direct campaigns
     pub creative_manager: OnceLock<Arc<CreativeManager>>,
+    /// Maintains supply quality rules, blocking or flagging requests
+    pub supply_rule_manager: OnceLock<Arc<SupplyRuleManager>>,
     /// Maintains list of deals (direct + RTB)
     pub deal_manager: OnceLock<Arc<DealManager>>,
+    /// Bidder request/response adapters, selected per bidder by name
+    pub adapter_registry: OnceLock<Arc<AdapterRegistry>>,
     /// Traffic shaping instances per endpoint
     pub shaping_manager: OnceLock<Arc<ShaperManager>>,
     /// Caches demand provided notification URLs like burl, lurl
     pub demand_url_cache: OnceLock<Arc<DemandNotificationsCache>>,
     /// The user sync store for partners which we host a match table
     pub sync_store: OnceLock<Arc<dyn SyncStore>>,
+    /// Recent revenue per bidder, weighting which bidders are synced
+    pub sync_revenue: OnceLock<Arc<SyncRevenue>>,
+    /// First party audience segment memberships by local uid
+    pub segment_store: OnceLock<Arc<dyn SegmentStore>>,
     /// Responsible for observing cluster sizing changes
     pub cluster_manager: OnceLock<Arc<dyn ClusterDiscovery>>,
+    /// Sizes each node's share of endpoint QPS limits, static or peer coordinated
+    pub qps_coordinator: OnceLock<Arc<QpsCoordinator>>,
+    /// Resolved bearer token for internal routes, None refuses them
+    pub internal_token: OnceLock<Option<String>>,
+    /// FX rates and conversion into the accounting currency
+    pub currency: OnceLock<Arc<CurrencyService>>,
     /// Optional Firestore client, if configured. oncelock should
     /// always be set to catch accidential pipeline configurations
     /// leading to inactive database tasks
@@ -84,6 +102,8 @@ pub struct StartupContext {
     pub deal_pacer: OnceLock<Arc<dyn DealPacer>>,
     /// Unified campaign spend pacer — reads campaign.pacing per call
     pub spend_pacer: OnceLock<Arc<dyn SpendPacer>>,
+    /// Sampled traffic reservoir for availability forecasts, if enabled
+    pub forecaster: OnceLock<Arc<Forecaster>>,
 
     /// Maintains updated list of publisher ad placements
     pub placement_manager: OnceLock<Arc<PlacementManager>>,
@@ -1,23 +1,28 @@
 use crate::app::lifecycle::context::StartupContext;
 use crate::app::lifecycle::startup::tasks::config_load::ConfigLoadTask;
 use crate::app::span::WrappedPipelineTask;
+use crate::app::startup::tasks::adapters_load::AdapterRegistryLoadTask;
 use crate::app::startup::tasks::bidders_load::BidderManagerLoadTask;
 use crate::app::startup::tasks::build_adtag_pipeline::BuildAdtagPipelineTask;
 use crate::app::startup::tasks::cluster::ClusterDiscoveryTask;
 use crate::app::startup::tasks::counter_stores::CounterStoresTask;
 use crate::app::startup::tasks::creative_pipeline::BuildCreativePipelineTask;
+use crate::app::startup::tasks::currency_load::CurrencyLoadTask;
 use crate::app::startup::tasks::demand_url_cache::DemandUrlCacheStartTask;
 use crate::app::startup::tasks::device_load::DeviceLookupLoadTask;
 use crate::app::startup::tasks::direct_managers_load::DirectManagersLoadTask;
 use crate::app::startup::tasks::event_pipeline::BuildEventPipelineTask;
 use crate::app::startup::tasks::firestore::FirestoreTask;
+use crate::app::startup::tasks::forecaster_init::ForecasterInitTask;
 use crate::app::startup::tasks::ip_risk_load::IpRiskLoadTask;
 use crate::app::startup::tasks::load_adtag_managers::LoadAdtagManagersTask;
 use crate::app::startup::tasks::observability::ConfigureObservabilityTask;
 use crate::app::startup::tasks::pubs_load::PubsManagerLoadTask;
 use crate::app::startup::tasks::rtb_pipeline::BuildRtbPipelineTask;
+use crate::app::startup::tasks::segment_store_init::SegmentStoreInitTask;
 use crate::app::startup::tasks::shapers_load::ShapersManagerLoadTask;
 use crate::app::startup::tasks::start_server::StartServerTask;
+use crate::app::startup::tasks::supply_rules_load::SupplyRulesLoadTask;
 use crate::app::startup::tasks::sync_pipelines::BuildSyncPipelinesTask;
 use crate::app::startup::tasks::sync_store_init::SyncStoreInitTask;
 use crate::app::startup::tasks::tracker_init::TrackerInitTask;
@@ -48,19 +53,24 @@ pub fn build_start_pipeline(cfg_path: PathBuf) -> Pipeline<StartupContext, anyho
         .with_blocking(Box::new(ValidateCdnDomainTask))
         .with_async(Box::new(ClusterDiscoveryTask))
         .with_async(Box::new(FirestoreTask))
+        .with_async(Box::new(CurrencyLoadTask))
         .with_blocking(Box::new(CounterStoresTask))
         .with_async(Box::new(DirectManagersLoadTask::new(cfg_manager.clone())))
         .with_async(Box::new(TrackerInitTask))
         .with_async(Box::new(BidderManagerLoadTask::new(cfg_manager.clone())))
+        .with_blocking(Box::new(AdapterRegistryLoadTask))
         .with_blocking(Box::new(ShapersManagerLoadTask))
         .with_async(Box::new(PubsManagerLoadTask::new(cfg_manager.clone())))
         .with_async(Box::new(LoadAdtagManagersTask::new(cfg_manager.clone())))
+        .with_async(Box::new(SupplyRulesLoadTask::new(cfg_manager.clone())))
         .with_async(Box::new(IpRiskLoadTask))
         .with_async(Box::new(DeviceLookupLoadTask))
         .with_blocking(Box::new(DemandUrlCacheStartTask::new(cfg_manager.clone())))
         .with_async(Box::new(SyncStoreInitTask::new(Duration::from_hours(
             24 * 7,
         ))))
+        .with_async(Box::new(SegmentStoreInitTask))
+        .with_blocking(Box::new(ForecasterInitTask))
         .with_blocking(Box::new(BuildRtbPipelineTask))
         .with_blocking(Box::new(BuildAdtagPipelineTask))
         .with_blocking(Box::new(BuildEventPipelineTask))
@@ -0,0 +1,70 @@
+use crate::app::context::StartupContext;
+use crate::core::demand::adapters::{AdapterRegistry, DeclarativeAdapter};
+use crate::core::managers::DemandChange;
+use anyhow::{Error, anyhow};
+use pipeline::BlockingTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Registers every bidder adapter, built in and declarative
+/// from config, which bidders can then select by name. Bidders
+/// selecting an unknown adapter are reported once here, when
+/// loaded or changed, and fall back to passthrough
+pub struct AdapterRegistryLoadTask;
+
+impl BlockingTask<StartupContext, Error> for AdapterRegistryLoadTask {
+    fn run(&self, context: &StartupContext) -> Result<(), Error> {
+        let _span = child_span_info!("adapter_registry_load_task").entered();
+
+        let config = context
+            .config
+            .get()
+            .ok_or_else(|| anyhow!("Config not loaded, cant register bidder adapters"))?;
+
+        let mut registry = AdapterRegistry::new();
+
+        for adapter_config in &config.adapters {
+            debug!(
+                "Registering declarative bidder adapter {}",
+                adapter_config.name
+            );
+
+            registry.register(Arc::new(DeclarativeAdapter::new(adapter_config.clone())))?;
+        }
+
+        let registry = Arc::new(registry);
+
+        let bidder_manager = context
+            .bidder_manager
+            .get()
+            .ok_or_else(|| anyhow!("Bidder manager must load before adapter registry"))?;
+
+        for bidder in bidder_manager.bidders() {
+            if let Err(e) = registry.validate(&bidder) {
+                warn!("{}", e);
+            }
+        }
+
+        let change_registry = registry.clone();
+        bidder_manager.on_change(Box::new(move |change| {
+            let bidder = match change {
+                DemandChange::Added { bidder, .. } | DemandChange::Modified { bidder, .. } => {
+                    bidder
+                }
+                DemandChange::Removed { .. } => return,
+            };
+
+            if let Err(e) = change_registry.validate(bidder) {
+                warn!("{}", e);
+            }
+        }));
+
+        context
+            .adapter_registry
+            .set(registry)
+            .map_err(|_| anyhow!("Can't set adapter registry on context, exist already?"))?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::app::config::ClusterConfig;
 use crate::app::context::StartupContext;
-use crate::core::cluster::ClusterDiscovery;
 use crate::core::cluster::impls::{FixedClusterDiscovery, KubeClusterDiscovery};
+use crate::core::cluster::{ClusterDiscovery, QpsCoordinator};
 use anyhow::{Error, anyhow};
 use async_trait::async_trait;
 use pipeline::AsyncTask;
@@ -10,7 +10,8 @@ use tracing::{info, instrument};
 
 /// Adds the cluster discovery agent
 /// to the startup context and ensures
-/// a successful connection if applicable
+/// a successful connection if applicable,
+/// along with the QPS share coordinator
 pub struct ClusterDiscoveryTask;
 
 #[async_trait]
@@ -41,13 +42,48 @@ impl AsyncTask<StartupContext, Error> for ClusterDiscoveryTask {
 
                 Arc::new(k8s)
             }
+            ClusterConfig::Peers(peers) => {
+                info!("Cluster fixed to self and {} static peers", peers.len());
+
+                Arc::new(FixedClusterDiscovery::with_peers(peers.clone()))
+            }
         };
 
+        let config = context
+            .config
+            .get()
+            .ok_or(anyhow!("Config not set on startup context!"))?;
+
+        let qps_mode = &config.qps_limit_mode;
+
+        // resolved here as the peer exchange is the first internal route user
+        let internal_token = config
+            .internal_token
+            .as_ref()
+            .map(|secret| secret.resolve())
+            .transpose()?
+            .filter(|token| !token.is_empty());
+
+        let qps_coordinator =
+            QpsCoordinator::start(qps_mode, cluster_discovery.clone(), internal_token.clone())?;
+
+        info!("QPS limit mode {:?}", qps_mode);
+
         context
             .cluster_manager
             .set(cluster_discovery)
             .map_err(|_| anyhow!("Failed to set cluster manager on startup context"))?;
 
+        context
+            .qps_coordinator
+            .set(qps_coordinator)
+            .map_err(|_| anyhow!("Failed to set qps coordinator on startup context"))?;
+
+        context
+            .internal_token
+            .set(internal_token)
+            .map_err(|_| anyhow!("Failed to set internal token on startup context"))?;
+
         Ok(())
     }
 }
@@ -0,0 +1,38 @@
+use crate::app::context::StartupContext;
+use crate::core::currency::CurrencyService;
+use anyhow::{Error, anyhow};
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use tracing::{info, instrument};
+
+/// Loads FX rates and starts their refresh. Must run
+/// after FirestoreTask when rates come from Firestore
+pub struct CurrencyLoadTask;
+
+#[async_trait]
+impl AsyncTask<StartupContext, Error> for CurrencyLoadTask {
+    #[instrument(skip_all, name = "currency_load_task")]
+    async fn run(&self, context: &StartupContext) -> Result<(), Error> {
+        let config = context
+            .config
+            .get()
+            .ok_or_else(|| anyhow!("Config not loaded, cant start currency service"))?;
+
+        let firestore = context
+            .firestore
+            .get()
+            .ok_or_else(|| anyhow!("Firestore task must run before currency service"))?
+            .clone();
+
+        let service = CurrencyService::start(&config.currency, firestore).await?;
+
+        info!("Accounting currency is {}", service.accounting());
+
+        context
+            .currency
+            .set(service)
+            .map_err(|_| anyhow!("Can't set currency service on context, exist already?"))?;
+
+        Ok(())
+    }
+}
@@ -40,6 +40,11 @@ impl AsyncTask<StartupContext, Error> for DirectManagersLoadTask {
             .get()
             .ok_or_else(|| anyhow!("Firestore state not set"))?;
 
+        let currency = context
+            .currency
+            .get()
+            .ok_or_else(|| anyhow!("Currency service not set"))?;
+
         let (camp_prov, crea_prov, deal_prov, buy_prov, adv_prov): (
             Arc<dyn Provider<Campaign>>,
             Arc<dyn Provider<Creative>>,
@@ -73,9 +78,9 @@ impl AsyncTask<StartupContext, Error> for DirectManagersLoadTask {
         let (buyers, advertisers, campaigns, creatives, deals) = tokio::try_join!(
             BuyerManager::start(buy_prov),
             AdvertiserManager::start(adv_prov),
-            CampaignManager::start(camp_prov, firestore_opt.clone()),
+            CampaignManager::start(camp_prov, firestore_opt.clone(), currency.clone()),
             CreativeManager::start(crea_prov),
-            DealManager::start(deal_prov, firestore_opt.clone()),
+            DealManager::start(deal_prov, firestore_opt.clone(), currency.clone()),
         )?;
 
         context
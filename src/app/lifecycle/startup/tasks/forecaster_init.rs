@@ -0,0 +1,49 @@
+use crate::app::context::StartupContext;
+use crate::app::pipeline::ortb::forecast::Forecaster;
+use anyhow::{Error, anyhow};
+use pipeline::BlockingTask;
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+/// Creates the forecasting traffic reservoir when enabled, which
+/// the enrichment pipeline samples into. Must run after
+/// ClusterDiscoveryTask and before the auction pipeline is built
+pub struct ForecasterInitTask;
+
+impl BlockingTask<StartupContext, Error> for ForecasterInitTask {
+    #[instrument(skip_all, name = "forecaster_init_task")]
+    fn run(&self, context: &StartupContext) -> Result<(), Error> {
+        let config = context
+            .config
+            .get()
+            .ok_or_else(|| anyhow!("Config not set yet on context!"))?;
+
+        if !config.forecast.enabled {
+            info!("Forecasting is not enabled, skipping traffic sampling");
+            return Ok(());
+        }
+
+        let cluster = context
+            .cluster_manager
+            .get()
+            .ok_or_else(|| anyhow!("Cluster manager not set yet on context!"))?
+            .clone();
+
+        let forecaster = Forecaster::new(
+            config.forecast.clone(),
+            Box::new(move || cluster.cluster_size()),
+        );
+
+        context
+            .forecaster
+            .set(Arc::new(forecaster))
+            .map_err(|_| anyhow!("Failed to set forecaster on context"))?;
+
+        info!(
+            "Forecasting enabled, sampling {} of auctions",
+            config.forecast.sample_rate
+        );
+
+        Ok(())
+    }
+}
@@ -1,21 +1,26 @@
+pub mod adapters_load;
 pub mod bidders_load;
 pub mod build_adtag_pipeline;
 pub mod cluster;
 pub mod config_load;
 pub mod counter_stores;
 pub mod creative_pipeline;
+pub mod currency_load;
 pub mod demand_url_cache;
 pub mod device_load;
 pub mod direct_managers_load;
 pub mod event_pipeline;
 pub mod firestore;
+pub mod forecaster_init;
 pub mod ip_risk_load;
 pub mod load_adtag_managers;
 pub mod observability;
 pub mod pubs_load;
 pub mod rtb_pipeline;
+pub mod segment_store_init;
 pub mod shapers_load;
 pub mod start_server;
+pub mod supply_rules_load;
 pub mod sync_pipelines;
 pub mod sync_store_init;
 pub mod tracker_init;
@@ -0,0 +1,42 @@
+use crate::app::context::StartupContext;
+use crate::core::segments;
+use anyhow::{Error, anyhow};
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+use tracing::info;
+
+/// Creates the first party segment store, currently only caches
+/// locally per node, see [`crate::app::config::SegmentsConfig`]
+pub struct SegmentStoreInitTask;
+
+#[async_trait]
+impl AsyncTask<StartupContext, Error> for SegmentStoreInitTask {
+    async fn run(&self, context: &StartupContext) -> Result<(), Error> {
+        let _span = child_span_info!("segment_store_init_task").entered();
+
+        let config = context
+            .config
+            .get()
+            .ok_or_else(|| anyhow!("Config not set yet on context!"))?;
+
+        let segment_store = segments::LocalSegmentStore::new(
+            config.segments.max_ttl,
+            config.segments.max_per_user,
+            config.segments.max_users,
+        );
+
+        context
+            .segment_store
+            .set(Arc::new(segment_store))
+            .map_err(|_err| anyhow!("Failed to attach segment store to start context!"))?;
+
+        info!(
+            "Attached segment store to start context with max TTL of {:?} and max users of {}",
+            config.segments.max_ttl, config.segments.max_users
+        );
+
+        Ok(())
+    }
+}
@@ -1,14 +1,21 @@
 use crate::app::handlers::adtag::{adtag_handler, adtag_preflight};
 use crate::app::handlers::billing::billing_event_handler;
+use crate::app::handlers::cluster::qps_usage_handler;
 use crate::app::handlers::creative_serving::raw_creative_handler;
+use crate::app::handlers::forecast::forecast_handler;
+use crate::app::handlers::optout::{optout_confirm_handler, optout_handler, optout_status_handler};
 use crate::app::handlers::profile::profile_handler;
+use crate::app::handlers::report::{ReportRange, deal_funnel_handler};
 use crate::app::handlers::rtb::json_bid_handler;
+use crate::app::handlers::segments::{SegmentPixelQuery, segment_pixel_handler};
 use crate::app::handlers::sync::{
     sync_debug_handler, sync_debug_preflight, sync_in_handler, sync_out_handler,
 };
 use crate::app::http::COOKIE_DOMAIN;
 use crate::app::lifecycle::context::StartupContext;
 use crate::app::pipeline::adtag::request::AdTagRequest;
+use crate::core::cluster::QPS_USAGE_PATH;
+use crate::core::models::targeting::CommonTargeting;
 use actix_web::HttpRequest;
 use actix_web::web;
 use anyhow::{Error, anyhow, bail};
@@ -99,6 +106,37 @@ impl AsyncTask<StartupContext, anyhow::Error> for StartServerTask {
 
         let raw_creative_pipeline = ctx.raw_creative_pipeline.get().cloned();
 
+        let deal_store = ctx.counters_deal_store.get().cloned().flatten();
+
+        let segment_store = ctx
+            .segment_store
+            .get()
+            .ok_or(anyhow!("Segment store not built"))?
+            .clone();
+
+        let segments_config = config.segments.clone();
+
+        let forecaster = ctx.forecaster.get().cloned();
+
+        let deal_manager = ctx
+            .deal_manager
+            .get()
+            .ok_or(anyhow!("Deal manager not built"))?
+            .clone();
+
+        let qps_coordinator = ctx
+            .qps_coordinator
+            .get()
+            .ok_or(anyhow!("QPS coordinator not built"))?
+            .clone();
+
+        let internal_token: Option<Arc<str>> = ctx
+            .internal_token
+            .get()
+            .ok_or(anyhow!("Internal token not resolved"))?
+            .as_deref()
+            .map(Arc::from);
+
         let server = Server::listen(server_cfg, move |app| {
             app.route("/hi", web::get().to(|| async { "hi!" }))
                     .route(
@@ -116,6 +154,18 @@ impl AsyncTask<StartupContext, anyhow::Error> for StartServerTask {
                         web::method(actix_web::http::Method::OPTIONS).to(adtag_preflight),
                     )
                     .route("/profile", web::get().to(profile_handler))
+                    .route(
+                        QPS_USAGE_PATH,
+                        web::get().to({
+                            let coordinator = qps_coordinator.clone();
+                            let token = internal_token.clone();
+                            move |http_req: HttpRequest| {
+                                let c = coordinator.clone();
+                                let t = token.clone();
+                                async move { qps_usage_handler(http_req, c, t).await }
+                            }
+                        }),
+                    )
                     .route(
                         billing_event_path.as_str(),
                         web::get().to({
@@ -174,6 +224,20 @@ impl AsyncTask<StartupContext, anyhow::Error> for StartServerTask {
                             }
                         }),
                     )
+                    .route("/optout", web::get().to(optout_confirm_handler))
+                    .route(
+                        "/optout",
+                        web::post().to({
+                            let ss = sync_store.clone();
+                            let segs = segment_store.clone();
+                            move |http_req: HttpRequest| {
+                                let ss = ss.clone();
+                                let segs = segs.clone();
+                                async move { optout_handler(http_req, ss, segs).await }
+                            }
+                        }),
+                    )
+                    .route("/optout/status", web::get().to(optout_status_handler))
                     .route(
                         "/sync/debug/{pubid}",
                         web::get().to({
@@ -197,6 +261,49 @@ impl AsyncTask<StartupContext, anyhow::Error> for StartServerTask {
                             }
                         }),
                     )
+                    .route(
+                        "/report/deals/{deal_id}/funnel",
+                        web::get().to({
+                            let ds = deal_store.clone();
+                            let token = internal_token.clone();
+                            move |deal_id: web::Path<String>,
+                                  range: web::Query<ReportRange>,
+                                  http_req: HttpRequest| {
+                                let ds = ds.clone();
+                                let t = token.clone();
+                                let deal_id = deal_id.into_inner();
+                                async move {
+                                    deal_funnel_handler(deal_id, range, http_req, ds, t).await
+                                }
+                            }
+                        }),
+                    )
+                    .route(
+                        "/seg",
+                        web::get().to({
+                            let store = segment_store.clone();
+                            let cfg = segments_config.clone();
+                            move |query: web::Query<SegmentPixelQuery>, http_req: HttpRequest| {
+                                let store = store.clone();
+                                let cfg = cfg.clone();
+                                async move { segment_pixel_handler(query, http_req, store, cfg).await }
+                            }
+                        }),
+                    )
+                    .route(
+                        "/forecast",
+                        web::post().to({
+                            let fc = forecaster.clone();
+                            let dm = deal_manager.clone();
+                            let token = internal_token.clone();
+                            move |targeting: web::Json<CommonTargeting>, http_req: HttpRequest| {
+                                let fc = fc.clone();
+                                let dm = dm.clone();
+                                let t = token.clone();
+                                async move { forecast_handler(targeting, http_req, fc, dm, t).await }
+                            }
+                        }),
+                    )
                     .route(
                         "/adserving/raw/{crid}",
                         web::get().to({
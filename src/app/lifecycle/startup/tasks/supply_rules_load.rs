@@ -0,0 +1,47 @@
+use crate::app::context::StartupContext;
+use crate::core::config_manager::ConfigManager;
+use crate::core::managers::SupplyRuleManager;
+use crate::core::models::supply_rule::SupplyRule;
+use crate::core::providers::{ConfigSupplyRuleProvider, FirestoreProvider, Provider};
+use anyhow::{Error, anyhow};
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+pub struct SupplyRulesLoadTask {
+    config_manager: Arc<ConfigManager>,
+}
+
+impl SupplyRulesLoadTask {
+    pub fn new(config_manager: Arc<ConfigManager>) -> Self {
+        Self { config_manager }
+    }
+}
+
+#[async_trait]
+impl AsyncTask<StartupContext, Error> for SupplyRulesLoadTask {
+    #[instrument(skip_all, name = "supply_rules_load_task")]
+    async fn run(&self, context: &StartupContext) -> Result<(), Error> {
+        let provider: Arc<dyn Provider<SupplyRule>> = match context.firestore.get() {
+            None => return Err(anyhow!("Firestore task must run before supply rules")),
+            Some(None) => {
+                info!("Loading supply rules from config");
+                Arc::new(ConfigSupplyRuleProvider::new(self.config_manager.clone()))
+            }
+            Some(Some(db)) => {
+                info!("Loading supply rules from Firestore");
+                Arc::new(FirestoreProvider::new(db.clone(), "supply_rules"))
+            }
+        };
+
+        let manager = SupplyRuleManager::start(provider).await?;
+
+        context
+            .supply_rule_manager
+            .set(manager)
+            .map_err(|_| anyhow!("supply_rule_manager already set on context"))?;
+
+        Ok(())
+    }
+}
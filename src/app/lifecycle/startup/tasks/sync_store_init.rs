@@ -8,7 +8,8 @@ use std::sync::Arc;
 use std::time::Duration;
 use tracing::info;
 
-/// Creates the user match store, currently only caches locally
+/// Creates the user match store, currently only caches locally,
+/// and the recent bidder revenue weighting which bidders are synced
 pub struct SyncStoreInitTask {
     duration_ttl: Duration,
 }
@@ -36,6 +37,18 @@ impl AsyncTask<StartupContext, Error> for SyncStoreInitTask {
             self.duration_ttl
         );
 
+        let config = context
+            .config
+            .get()
+            .ok_or_else(|| anyhow!("Config not set yet on context!"))?;
+
+        context
+            .sync_revenue
+            .set(Arc::new(usersync::SyncRevenue::new(
+                config.usersync.revenue_window,
+            )))
+            .map_err(|_err| anyhow!("Failed to attach sync revenue to start context!"))?;
+
         Ok(())
     }
 }
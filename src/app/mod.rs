@@ -3,6 +3,8 @@ mod handlers;
 mod http;
 mod lifecycle;
 mod pipeline;
+#[cfg(feature = "simulator")]
+pub mod simulate;
 mod span;
 
 pub use lifecycle::*;
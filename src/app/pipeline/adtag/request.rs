@@ -24,6 +24,24 @@ pub struct AdTagRequest {
     /// Set by the JS tag via the `?neuronic_force_bid=1` URL parameter.
     #[serde(default)]
     pub force_bid: bool,
+
+    /// First party ids read by the JS tag, which don't depend on
+    /// our own (often partitioned) rxid cookie
+    #[serde(default)]
+    pub ids: FirstPartyIds,
+}
+
+/// First party ids the JS tag could read on the page. Whether each
+/// may be sent to bidders is controlled by the publisher id_sharing
+#[derive(Debug, Deserialize, Default)]
+pub struct FirstPartyIds {
+    /// Publisher provided id (PPID), set by the publisher on the tag
+    pub ppid: Option<String>,
+    /// UID2 advertising token, generated from a hashed email
+    /// by the publisher's UID2 integration
+    pub uid2: Option<String>,
+    /// SharedID (pubcid) first party cookie value
+    pub sharedid: Option<String>,
 }
 
 /// Rendering environment capabilities assessed by the JS tag.
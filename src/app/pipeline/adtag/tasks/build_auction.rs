@@ -1,15 +1,19 @@
 use crate::app::pipeline::adtag::context::AdtagContext;
+use crate::app::pipeline::adtag::request::FirstPartyIds;
 use crate::app::pipeline::ortb::AuctionContext;
 use crate::app::pipeline::ortb::HttpRequestContext;
 use crate::core::models::creative::{BannerSize, CreativeFormat};
 use crate::core::models::property::PropertyKind;
+use crate::core::models::publisher::IdSharing;
 use crate::core::spec::nobidreasons;
 use anyhow::{Error, anyhow, bail};
 use pipeline::BlockingTask;
 use rtb::BidRequestBuilder;
+use rtb::bid_request::user::Eid;
+use rtb::bid_request::user::eid::Uid;
 use rtb::bid_request::{
     App, Audio, Banner, DeviceBuilder, DistributionchannelOneof, Format as BannerFormat, Imp, Regs,
-    SiteBuilder, UserBuilder, Video,
+    SiteBuilder, User, UserBuilder, Video,
 };
 use rtb::child_span_info;
 use rtb::common::bidresponsestate::BidResponseState;
@@ -22,6 +26,17 @@ pub struct BuildAuctionTask;
 
 const NO_VIEWPORT_IMPRESSION_DESC: &str = "No impressions match available viewport";
 
+/// Longest first party id accepted from the tag, UID2 tokens
+/// being the longest at a few hundred characters
+const MAX_FIRST_PARTY_ID_LEN: usize = 512;
+
+const EID_SOURCE_UID2: &str = "uidapi.com";
+const EID_SOURCE_SHAREDID: &str = "pubcid.org";
+
+/// AdCOM agent types, browser/device and person based ids
+const EID_ATYPE_DEVICE: i32 = 1;
+const EID_ATYPE_PERSON: i32 = 3;
+
 impl BlockingTask<AdtagContext, Error> for BuildAuctionTask {
     fn run(&self, ctx: &AdtagContext) -> Result<(), Error> {
         let placement = ctx
@@ -238,7 +253,7 @@ impl BlockingTask<AdtagContext, Error> for BuildAuctionTask {
             ..Default::default()
         });
 
-        let user = consent
+        let mut user = consent
             .tcf
             .as_ref()
             .map(|tcf| {
@@ -248,6 +263,22 @@ impl BlockingTask<AdtagContext, Error> for BuildAuctionTask {
             })
             .transpose()?;
 
+        let ids = &ctx.request.ids;
+        let sharing = &publisher.id_sharing;
+        let ppid = clean_first_party_id(ids.ppid.as_deref());
+        let eids = shared_eids(ids, sharing, &publisher.domain);
+
+        if (sharing.ppid && ppid.is_some()) || !eids.is_empty() {
+            let user = user.get_or_insert_with(User::default);
+
+            // replaced by our local uid later on for recognized users
+            if let Some(ppid) = ppid.filter(|_| sharing.ppid) {
+                user.id = ppid.to_string();
+            }
+
+            user.eids = eids;
+        }
+
         let mut req_builder = BidRequestBuilder::default();
         req_builder
             .id(auction_id.clone())
@@ -308,6 +339,62 @@ fn fits_render_caps(
     true
 }
 
+/// Trimmed id, None if empty, overly long or not printable ascii
+fn clean_first_party_id(id: Option<&str>) -> Option<&str> {
+    id.map(str::trim).filter(|id| {
+        !id.is_empty()
+            && id.len() <= MAX_FIRST_PARTY_ID_LEN
+            && id.bytes().all(|b| b.is_ascii_graphic())
+    })
+}
+
+fn first_party_eid(source: &str, id: &str, atype: i32) -> Eid {
+    Eid {
+        source: source.to_string(),
+        uids: vec![Uid {
+            id: id.to_string(),
+            atype,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+/// EIDs for the tag's first party ids which the publisher shares.
+/// The PPID is keyed by the publisher domain, as there's no
+/// common source for publisher provided ids
+fn shared_eids(ids: &FirstPartyIds, sharing: &IdSharing, pub_domain: &str) -> Vec<Eid> {
+    let mut eids = Vec::new();
+
+    if let Some(ppid) = clean_first_party_id(ids.ppid.as_deref()) {
+        if sharing.ppid && !pub_domain.is_empty() {
+            eids.push(first_party_eid(
+                &pub_domain.to_lowercase(),
+                ppid,
+                EID_ATYPE_PERSON,
+            ));
+        }
+    }
+
+    if let Some(uid2) = clean_first_party_id(ids.uid2.as_deref()) {
+        if sharing.uid2 {
+            eids.push(first_party_eid(EID_SOURCE_UID2, uid2, EID_ATYPE_PERSON));
+        }
+    }
+
+    if let Some(sharedid) = clean_first_party_id(ids.sharedid.as_deref()) {
+        if sharing.sharedid {
+            eids.push(first_party_eid(
+                EID_SOURCE_SHAREDID,
+                sharedid,
+                EID_ATYPE_DEVICE,
+            ));
+        }
+    }
+
+    eids
+}
+
 fn clone_http_context(http: &HttpRequestContext) -> HttpRequestContext {
     HttpRequestContext {
         ip: http.ip,
@@ -323,11 +410,11 @@ fn clone_http_context(http: &HttpRequestContext) -> HttpRequestContext {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::app::pipeline::adtag::request::{AdTagRequest, Consent, RenderCaps};
+    use crate::app::pipeline::adtag::request::{AdTagRequest, Consent, FirstPartyIds, RenderCaps};
     use crate::core::models::common::Status;
     use crate::core::models::placement::{ContainerType, FillPolicy, Placement};
     use crate::core::models::property::{Property, PropertyKind};
-    use crate::core::models::publisher::{Publisher, PublisherType};
+    use crate::core::models::publisher::Publisher;
     use std::sync::Arc;
 
     fn banner_placement() -> Placement {
@@ -365,15 +452,22 @@ mod tests {
         Publisher {
             id: "pub_1".into(),
             domain: "example.com".into(),
-            seller_type: PublisherType::Publisher,
             enabled: true,
             name: "Publisher".into(),
-            margin: 0,
-            sync_url: None,
+            ..Default::default()
         }
     }
 
     fn ctx_with_caps(max_w: Option<u32>, max_h: Option<u32>) -> AdtagContext {
+        ctx_with(max_w, max_h, FirstPartyIds::default(), publisher())
+    }
+
+    fn ctx_with(
+        max_w: Option<u32>,
+        max_h: Option<u32>,
+        ids: FirstPartyIds,
+        publisher: Publisher,
+    ) -> AdtagContext {
         let request = AdTagRequest {
             placement_id: "plc_1".into(),
             consent: Consent::default(),
@@ -385,6 +479,7 @@ mod tests {
                 max_h,
             },
             force_bid: false,
+            ids,
         };
 
         let ctx = AdtagContext::new(request, HttpRequestContext::default());
@@ -396,7 +491,7 @@ mod tests {
             .set(Arc::new(site_property()))
             .expect("property should set");
         ctx.publisher
-            .set(Arc::new(publisher()))
+            .set(Arc::new(publisher))
             .expect("publisher should set");
 
         ctx
@@ -453,4 +548,57 @@ mod tests {
             other => panic!("expected no-bid reason, got {other:?}"),
         }
     }
+
+    #[test]
+    fn shares_only_first_party_ids_the_publisher_allows() {
+        let ids = FirstPartyIds {
+            ppid: Some(" user-123 ".into()),
+            uid2: Some("A4AAAABlh75XmviGJi".into()),
+            sharedid: Some("has spaces".into()),
+        };
+
+        let publisher = Publisher {
+            id_sharing: IdSharing {
+                ppid: false,
+                uid2: true,
+                sharedid: true,
+            },
+            ..publisher()
+        };
+
+        let ctx = ctx_with(None, None, ids, publisher);
+
+        BuildAuctionTask.run(&ctx).expect("task should succeed");
+
+        let auction_ctx = ctx.auction_ctx.get().expect("auction ctx should exist");
+        let req = auction_ctx.req.read();
+        let user = req.user.as_ref().expect("user expected");
+
+        // ppid not shared, invalid sharedid dropped
+        assert!(user.id.is_empty());
+        assert_eq!(user.eids.len(), 1);
+        assert_eq!(user.eids[0].source, EID_SOURCE_UID2);
+        assert_eq!(user.eids[0].uids[0].atype, EID_ATYPE_PERSON);
+
+        assert!(auction_ctx.identity.get().is_none());
+    }
+
+    #[test]
+    fn shared_ppid_sent_as_user_id_and_publisher_eid() {
+        let ids = FirstPartyIds {
+            ppid: Some("user-123".into()),
+            ..Default::default()
+        };
+
+        let sharing = IdSharing {
+            ppid: true,
+            ..Default::default()
+        };
+
+        let eids = shared_eids(&ids, &sharing, "Example.com");
+
+        assert_eq!(eids.len(), 1);
+        assert_eq!(eids[0].source, "example.com");
+        assert_eq!(eids[0].uids[0].id, "user-123");
+    }
 }
@@ -1,6 +1,7 @@
 use crate::app::pipeline::adtag::context::AdtagContext;
 use crate::app::pipeline::adtag::response::{AdTagResponse, TagBid, TagBidContent};
 use crate::core::models::placement::Placement;
+use crate::core::usersync;
 use crate::core::usersync::utils::build_sync_out_url;
 use anyhow::{Error, anyhow, bail};
 use async_trait::async_trait;
@@ -58,7 +59,12 @@ impl BuildResponseTask {
             .map(|g| !g.gpp_applicable_sections.is_empty())
             .unwrap_or(false);
 
-        let sync_frame_url = if gdpr_blocks_sync || gpp_blocks_sync {
+        let opted_out = usersync::utils::is_opted_out(&ctx.http.cookies);
+
+        let sync_frame_url = if opted_out {
+            debug!("suppressing sync frame — user opted out");
+            None
+        } else if gdpr_blocks_sync || gpp_blocks_sync {
             debug!(
                 gdpr_blocks_sync,
                 gpp_blocks_sync, "suppressing sync frame — consent signal blocks sync"
@@ -5,7 +5,7 @@ use crate::app::pipeline::events::billing::tasks::{
     MarkIfExpiredTask, ParseDataUrlTask, RecordBillingMetricsTask,
     RecordCampaignBillingCountersTask, RecordDealBillingCountersTask,
     RecordDemandBillingCountersTask, RecordPacingTask, RecordPubBillingCountersTask,
-    RecordShapingEventsTask,
+    RecordShapingEventsTask, RecordSyncRevenueTask,
 };
 use anyhow::{Error, anyhow, bail};
 use pipeline::{Pipeline, PipelineBuilder};
@@ -89,6 +89,14 @@ pub fn build_event_pipeline(
         deal_pacer.clone(),
     )));
 
+    // Sync revenue — always run, user sync weighting is node local
+    let sync_revenue = context
+        .sync_revenue
+        .get()
+        .ok_or_else(|| anyhow!("No sync revenue on context!"))?;
+
+    builder.add_blocking(Box::new(RecordSyncRevenueTask::new(sync_revenue.clone())));
+
     // Deal counters — platform-wide, runs for both direct and RTB bids
     if let Some(deal_store) = context
         .counters_deal_store
@@ -11,6 +11,7 @@ mod record_metrics;
 mod record_pacing;
 mod record_pub_counters;
 mod record_shaping;
+mod record_sync_revenue;
 
 pub use bail_if_expired::BailIfExpiredTask;
 pub use cache_urls_validation::CacheNoticeUrlsValidationTask;
@@ -25,3 +26,4 @@ pub use record_metrics::RecordBillingMetricsTask;
 pub use record_pacing::RecordPacingTask;
 pub use record_pub_counters::RecordPubBillingCountersTask;
 pub use record_shaping::RecordShapingEventsTask;
+pub use record_sync_revenue::RecordSyncRevenueTask;
@@ -1,5 +1,7 @@
 use crate::app::pipeline::events::billing::context::BillingEventContext;
+use crate::core::demand::takerate;
 use crate::core::firestore::counters::deal::{DealCounterStore, DealCounters};
+use crate::core::firestore::counters::funnel::DealFunnelCounters;
 use anyhow::{Error, anyhow};
 use pipeline::BlockingTask;
 use rtb::child_span_info;
@@ -40,8 +42,22 @@ impl BlockingTask<BillingEventContext, Error> for RecordDealBillingCountersTask
         let mut counters = DealCounters::default();
         counters.impression();
         counters.record_spend(details.cpm_gross, details.cpm_cost);
+
+        if let Some(fee) = deal.curation_fee {
+            counters.record_curation_fee(takerate::curation_fee(
+                details.cpm_gross,
+                details.cpm_cost,
+                fee,
+            ));
+        }
+
         self.deal_store.merge(&deal.id, &counters);
 
+        let mut funnel = DealFunnelCounters::default();
+        funnel.billed();
+        self.deal_store
+            .merge_funnel(&deal.id, &details.bidder_id, &funnel);
+
         Ok(())
     }
 }
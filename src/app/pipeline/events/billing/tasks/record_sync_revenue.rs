@@ -0,0 +1,41 @@
+use crate::app::pipeline::events::billing::context::BillingEventContext;
+use crate::core::usersync::SyncRevenue;
+use anyhow::{Error, anyhow};
+use pipeline::BlockingTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+
+/// Records the revenue of RTB impressions per bidder, weighting
+/// which bidders user syncs favour. Always runs, regardless of
+/// whether Firestore counter stores are present
+pub struct RecordSyncRevenueTask {
+    revenue: Arc<SyncRevenue>,
+}
+
+impl RecordSyncRevenueTask {
+    pub fn new(revenue: Arc<SyncRevenue>) -> Self {
+        Self { revenue }
+    }
+}
+
+impl BlockingTask<BillingEventContext, Error> for RecordSyncRevenueTask {
+    fn run(&self, context: &BillingEventContext) -> Result<(), Error> {
+        let _span = child_span_info!("record_sync_revenue_task").entered();
+
+        // Direct campaign bids have no RTB bidder to sync
+        if let Some(notice) = context.bid_notice.get() {
+            if notice.direct.is_some() {
+                return Ok(());
+            }
+        }
+
+        let details = context
+            .details
+            .get()
+            .ok_or_else(|| anyhow!("No billing event details on context!"))?;
+
+        self.revenue.record(&details.bidder_id, details.cpm_gross);
+
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
+use crate::app::pipeline::ortb::targeting::ContextualSignals;
 use crate::core::enrichment::device::DeviceInfo;
+use crate::core::firestore::counters::funnel::DealFunnelCounters;
+use crate::core::floors::FloorKey;
 use crate::core::models::bidder::{Bidder, Endpoint};
 use crate::core::models::buyer::Buyer;
 use crate::core::models::campaign::Campaign;
@@ -7,9 +10,12 @@ use crate::core::models::deal::Deal;
 use crate::core::models::placement::Placement;
 use crate::core::models::publisher::Publisher;
 use crate::core::shaping::tree::TreeShaper;
+use chrono::{DateTime, Utc};
+use compact_str::CompactString;
 use derivative::Derivative;
 use derive_builder::Builder;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use rtb::bid_request::user::Eid;
 use rtb::bid_response::{Bid, SeatBid};
 use rtb::common::DataUrl;
 use rtb::common::bidresponsestate::BidResponseState;
@@ -165,6 +171,9 @@ pub enum BidderResponseState {
     Timeout,
     /// Error experienced sending request such as broken url, bad dns
     Error(String),
+    /// Our adapter failed to process the endpoint's response, which
+    /// isn't held against the endpoint's health
+    AdapterError(String),
     /// Unknown or unexpected http status response (status code, reason message)
     Unknown(u32, String),
     /// No bid received as defined by http 204 of 200 with empty seatbid.
@@ -185,6 +194,8 @@ pub enum CalloutSkipReason {
     TrafficShaping,
     QpsLimit,
     EndpointRotation,
+    /// Endpoint circuit breaker is open and this callout was not a probe
+    CircuitOpen,
 }
 
 /// The ['DataUrl'] notification events are sent to,
@@ -231,13 +242,40 @@ pub struct BidderContext {
     pub callouts: Vec<BidderCallout>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct IdentityContext {
     /// Our web cookie id for this user, which has been extracted
     /// from cookie or provided to us in buyeruid from seller. If
     /// this is present, req.user.id will now be set to this val
     pub local_uid: OnceLock<String>,
-    // later, ramp ids or.. whatever
+    /// First party segments the local uid is a member of
+    pub segments: OnceLock<Vec<CompactString>>,
+    /// Extended ids from the request plus our local uid as an EID,
+    /// one entry per source. Passed to bidders per their allowlist
+    pub eids: OnceLock<Vec<Eid>>,
+}
+
+/// Floor keys and effective floors per imp and whether the auction
+/// was picked for floor exploration, set by FloorsTask. The request
+/// keeps the publisher floors, callouts are sent the effective ones
+#[derive(Debug, Default)]
+pub struct AuctionFloors {
+    pub explore: bool,
+    /// Imp id -> the key its floor was resolved for
+    pub keys: HashMap<String, FloorKey>,
+    /// Imp id -> highest of the publisher, static rule and learned floor
+    pub effective: HashMap<String, f64>,
+}
+
+impl AuctionFloors {
+    /// Raise the callout request imp floors to the effective floors
+    pub fn apply(&self, req: &mut BidRequest) {
+        for imp in req.imp.iter_mut() {
+            if let Some(floor) = self.effective.get(&imp.id) {
+                imp.bidfloor = imp.bidfloor.max(*floor);
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, EnumString, AsRefStr, Display)]
@@ -258,11 +296,25 @@ pub enum PublisherBlockReason {
     MissingAppSite,
     MissingAppSiteDomain,
     TmaxTooLow,
+    UnsupportedCurrency,
+    /// Id of the supply quality rule which blocked the request
+    SupplyRule(String),
+}
+
+impl PublisherBlockReason {
+    /// Reason reported on blocked request counters, supply
+    /// rule blocks include the rule id, e.g. SupplyRule:my-rule
+    pub fn tag(&self) -> String {
+        match self {
+            PublisherBlockReason::SupplyRule(id) => format!("{self}:{id}"),
+            _ => self.to_string(),
+        }
+    }
 }
 
 /// IP, UA, client hints, referer, and cookies from the inbound HTTP request.
 #[allow(dead_code)]
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct HttpRequestContext {
     /// Real client IP resolved from CF-Connecting-IP → X-Forwarded-For → socket
     pub ip: Option<IpAddr>,
@@ -274,6 +326,25 @@ pub struct HttpRequestContext {
     pub cookies: HashMap<String, String>,
 }
 
+/// Per auction deal funnel counts keyed by (deal id, bidder id)
+#[derive(Debug, Default)]
+pub struct DealFunnelBuffer {
+    stages: Mutex<HashMap<(String, String), DealFunnelCounters>>,
+}
+
+impl DealFunnelBuffer {
+    pub fn record(&self, deal_id: &str, bidder_id: &str, f: impl FnOnce(&mut DealFunnelCounters)) {
+        let mut stages = self.stages.lock();
+        f(stages
+            .entry((deal_id.to_string(), bidder_id.to_string()))
+            .or_default());
+    }
+
+    pub fn take(&self) -> HashMap<(String, String), DealFunnelCounters> {
+        std::mem::take(&mut *self.stages.lock())
+    }
+}
+
 /// Top level auction context object which carries all context required
 /// to fullfill a request pipeline
 ///
@@ -305,6 +376,9 @@ pub struct AuctionContext {
     pub placement: Option<Arc<Placement>>,
     /// Locally assigned but globally unique identifier for this auction
     pub event_id: String,
+    /// When the auction was received, targeting
+    /// schedules are evaluated against this
+    pub received_at: DateTime<Utc>,
     /// Tag to describe the inbound source of this request, e.g. rtb, rtb_protobuf, prebid, etc
     pub source: String,
     pub req: RwLock<BidRequest>,
@@ -328,6 +402,17 @@ pub struct AuctionContext {
     /// from reaching auction, so we may persist these
     /// stats as individually reportable
     pub block_reason: OnceLock<PublisherBlockReason>,
+    /// Currency the publisher expects response prices in, set during
+    /// enrichment. All prices inside the auction are in accounting currency
+    pub currency: OnceLock<String>,
+    /// Floor keys and exploration decision, set by FloorsTask
+    pub floors: OnceLock<AuctionFloors>,
+    /// Categories and keywords for targeting, built
+    /// on the first evaluation which filters on them
+    pub contextual: OnceLock<ContextualSignals>,
+    /// Deal funnel stages counted during matching,
+    /// completed and persisted by the deal funnel finalizer
+    pub deal_funnel: DealFunnelBuffer,
     /// Write-once extension store — for attaching pipeline-extension data without
     /// modifying this struct. See [`Extensions`].
     #[allow(dead_code)]
@@ -351,6 +436,7 @@ impl AuctionContext {
             publisher,
             placement,
             event_id: Uuid::new_v4().to_string(),
+            received_at: Utc::now(),
             source,
             req: RwLock::new(req),
             res: OnceLock::new(),
@@ -358,6 +444,10 @@ impl AuctionContext {
             direct_bid_staging: tokio::sync::Mutex::new(Vec::new()),
             rtb_nbr: OnceLock::new(),
             block_reason: OnceLock::new(),
+            currency: OnceLock::new(),
+            floors: OnceLock::new(),
+            contextual: OnceLock::new(),
+            deal_funnel: DealFunnelBuffer::default(),
             ext: Extensions::default(),
         }
     }
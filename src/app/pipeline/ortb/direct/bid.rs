@@ -60,6 +60,11 @@ pub fn synthesize_bid(
     bid_ctx
 }
 
+/// The synthetic bidder id direct campaign bids of a buyer are seated under
+pub fn direct_bidder_id(buyer_id: &str) -> String {
+    format!("direct:{buyer_id}")
+}
+
 /// Wraps a single direct campaign BidContext into a full BidderContext
 /// with a synthetic "direct" Bidder, so it flows through shared bid
 /// tasks (margin, notice URLs, settlement) uniformly with RTB bids.
@@ -70,7 +75,7 @@ pub fn wrap_in_bidder_context(bid_ctx: BidContext) -> BidderContext {
         .map(|d| d.buyer.id.as_str())
         .unwrap_or("unknown");
 
-    let seat = format!("direct:{buyer_id}");
+    let seat = direct_bidder_id(buyer_id);
 
     let synthetic_bidder = Arc::new(Bidder {
         id: seat.clone(),
@@ -36,57 +36,22 @@ pub fn resolve(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::models::campaign::{
-        BudgetType, Campaign, CampaignPacing, CampaignTargeting, PricingStrategy,
-    };
-    use crate::core::models::common::Status;
-    use crate::core::models::deal::{Deal, DealOwner, DealPricing, DealTargeting, DemandPolicy};
+    use crate::core::models::campaign::{Campaign, CampaignTargeting};
+    use crate::core::models::deal::Deal;
     use crate::core::models::targeting::CommonTargeting;
-    use chrono::Utc;
 
     fn stub_campaign(deal_ids: Vec<String>) -> Campaign {
         Campaign {
-            status: Status::Active,
-            buyer_id: "co1".into(),
-            id: "c1".into(),
-            start_date: Utc::now() - chrono::Duration::hours(1),
-            end_date: Utc::now() + chrono::Duration::hours(1),
-            name: "test".into(),
-            pacing: CampaignPacing::Fast,
-            budget: 1000.0,
-            budget_type: BudgetType::Total,
-            strategy: PricingStrategy::FixedPrice(5.0),
-            advertiser_id: "adv1".into(),
             targeting: CampaignTargeting {
                 common: CommonTargeting::default(),
                 deal_ids,
             },
-            click_url: None,
-            creatives: vec![],
-            delivery_state: Default::default(),
+            ..Campaign::stub("c1")
         }
     }
 
     fn stub_deal(id: &str) -> Deal {
-        Deal {
-            status: Status::Active,
-            id: id.into(),
-            name: "test deal".into(),
-            policy: DemandPolicy::Direct {
-                buyer_ids: vec!["co1".into()],
-            },
-            owner: DealOwner::Platform,
-            pricing: DealPricing::Inherit,
-            targeting: DealTargeting {
-                common: CommonTargeting::default(),
-            },
-            start_date: None,
-            end_date: None,
-            delivery_goal: None,
-            pacing: None,
-            takes_priority: false,
-            delivery_state: Default::default(),
-        }
+        Deal::stub(id)
     }
 
     #[test]
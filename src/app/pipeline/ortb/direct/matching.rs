@@ -9,6 +9,7 @@ use rtb::bid_request::Imp;
 use std::sync::Arc;
 use tracing::{Level, debug, enabled, trace, warn};
 
+use super::bid;
 use super::deals;
 use super::pacing::DealPacer;
 use super::settlement;
@@ -148,8 +149,8 @@ fn build_deal_map(
             continue;
         }
 
-        if !deal_pacer.passes(deal) {
-            trace!(deal = %deal.id, "Deal didn't pass impression pacing");
+        if !deal.allows_publisher(&ctx.publisher) {
+            trace!(deal = %deal.id, "Publisher not opted in to curated deal");
             continue;
         }
 
@@ -158,6 +159,23 @@ fn build_deal_map(
             _ => continue,
         };
 
+        let passes = deal_pacer.passes(deal);
+
+        for buyer_id in buyer_ids {
+            ctx.deal_funnel
+                .record(&deal.id, &bid::direct_bidder_id(buyer_id), |c| {
+                    c.eligible();
+                    if !passes {
+                        c.paced_out();
+                    }
+                });
+        }
+
+        if !passes {
+            trace!(deal = %deal.id, "Deal didn't pass impression pacing");
+            continue;
+        }
+
         trace!(deal = %deal.id, buyers = buyer_ids.len(), "Deal matched");
 
         for buyer_id in buyer_ids {
@@ -304,11 +322,9 @@ fn is_campaign_eligible(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::models::campaign::{
-        BudgetType, Campaign, CampaignPacing, CampaignTargeting, PricingStrategy,
-    };
-    use crate::core::models::common::Status;
-    use crate::core::models::deal::{Deal, DealOwner, DealPricing, DealTargeting, DemandPolicy};
+    use crate::core::models::campaign::{Campaign, CampaignTargeting, PricingStrategy};
+    use crate::core::models::deal::{Deal, DealOwner, DealPricing};
+    use crate::core::models::publisher::Publisher;
     use crate::core::models::targeting::CommonTargeting;
     use rtb::BidRequestBuilder;
     use rtb::bid_request::ImpBuilder;
@@ -319,6 +335,9 @@ mod tests {
         fn passes(&self, _deal: &Deal) -> bool {
             true
         }
+        fn behind_pace(&self, _deal: &Deal) -> bool {
+            false
+        }
         fn record_impression(&self, _deal_id: &str) {}
     }
 
@@ -328,26 +347,18 @@ mod tests {
         fn passes(&self, _deal: &Deal) -> bool {
             false
         }
+        fn behind_pace(&self, _deal: &Deal) -> bool {
+            false
+        }
         fn record_impression(&self, _deal_id: &str) {}
     }
 
     fn stub_campaign_open(id: &str, price: f64) -> Campaign {
         Campaign {
-            status: Status::Active,
-            buyer_id: "co1".into(),
-            id: id.into(),
-            start_date: Utc::now() - chrono::Duration::hours(1),
-            end_date: Utc::now() + chrono::Duration::hours(1),
             name: format!("campaign_{id}"),
-            pacing: CampaignPacing::Fast,
-            budget: 1000.0,
-            budget_type: BudgetType::Total,
             strategy: PricingStrategy::FixedPrice(price),
             advertiser_id: format!("adv_{id}"),
-            targeting: CampaignTargeting::default(),
-            click_url: None,
-            creatives: vec![],
-            delivery_state: Default::default(),
+            ..Campaign::stub(id)
         }
     }
 
@@ -363,23 +374,9 @@ mod tests {
 
     fn stub_deal(id: &str, pricing: DealPricing) -> Deal {
         Deal {
-            status: Status::Active,
-            id: id.into(),
             name: format!("deal_{id}"),
-            policy: DemandPolicy::Direct {
-                buyer_ids: vec!["co1".into()],
-            },
-            owner: DealOwner::Platform,
             pricing,
-            targeting: DealTargeting {
-                common: CommonTargeting::default(),
-            },
-            start_date: None,
-            end_date: None,
-            delivery_goal: None,
-            pacing: None,
-            takes_priority: false,
-            delivery_state: Default::default(),
+            ..Deal::stub(id)
         }
     }
 
@@ -569,6 +566,44 @@ mod tests {
         assert!(result.candidates.is_empty());
     }
 
+    #[test]
+    fn buyer_deal_requires_curation_opt_in() {
+        let deal = Arc::new(Deal {
+            owner: DealOwner::Buyer { id: "co1".into() },
+            ..stub_deal("d1", DealPricing::Fixed(8.0))
+        });
+        let c = Arc::new(stub_campaign_deal("c1", 5.0, vec!["d1".into()]));
+        let mut ctx = default_ctx();
+        let imp = default_imp();
+
+        let result = match_imp(
+            &[deal.clone()],
+            &[c.clone()],
+            &noop_by_buyer,
+            &FillPolicy::HighestPrice,
+            &AllPassDealPacer,
+            &ctx,
+            &imp,
+        );
+        assert!(result.candidates.is_empty());
+
+        ctx.publisher = Arc::new(Publisher {
+            curation_opt_in: true,
+            ..(*ctx.publisher).clone()
+        });
+
+        let result = match_imp(
+            &[deal],
+            &[c],
+            &noop_by_buyer,
+            &FillPolicy::HighestPrice,
+            &AllPassDealPacer,
+            &ctx,
+            &imp,
+        );
+        assert_eq!(result.candidates.len(), 1);
+    }
+
     #[test]
     fn outside_flight_excluded() {
         let mut c = stub_campaign_open("c1", 5.0);
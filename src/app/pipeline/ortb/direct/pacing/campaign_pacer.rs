@@ -255,8 +255,6 @@ mod tests {
     use crate::app::pipeline::ortb::direct::pacing::reservation::{
         system_epoch_clock, system_fine_clock,
     };
-    use crate::core::models::campaign::{CampaignTargeting, DeliveryState, PricingStrategy};
-    use crate::core::models::common::Status;
     use async_trait::async_trait;
     use chrono::{DateTime, TimeZone, Utc};
     use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
@@ -315,6 +313,9 @@ mod tests {
             self.0
         }
         fn on_change(&self, _cb: Box<dyn Fn(usize) + Send + Sync>) {}
+        fn peers(&self) -> Vec<String> {
+            Vec::new()
+        }
     }
 
     fn fake_clock(start_epoch: u64) -> (EpochClock, Arc<AtomicU64>) {
@@ -342,41 +343,17 @@ mod tests {
         end: DateTime<Utc>,
     ) -> Campaign {
         Campaign {
-            status: Status::Active,
-            buyer_id: "co1".into(),
-            id: "c1".into(),
             start_date: start,
             end_date: end,
-            name: "test".into(),
-            pacing,
-            budget,
-            budget_type: BudgetType::Total,
-            strategy: PricingStrategy::FixedPrice(5.0),
-            advertiser_id: "adv1".into(),
-            targeting: CampaignTargeting::default(),
-            click_url: None,
-            creatives: vec![],
-            delivery_state: DeliveryState::default(),
+            ..campaign(pacing, budget)
         }
     }
 
     fn campaign(pacing: CampaignPacing, budget: f64) -> Campaign {
         Campaign {
-            status: Status::Active,
-            buyer_id: "co1".into(),
-            id: "c1".into(),
-            start_date: Utc::now() - chrono::Duration::hours(1),
-            end_date: Utc::now() + chrono::Duration::hours(1),
-            name: "test".into(),
             pacing,
             budget,
-            budget_type: BudgetType::Total,
-            strategy: PricingStrategy::FixedPrice(5.0),
-            advertiser_id: "adv1".into(),
-            targeting: CampaignTargeting::default(),
-            click_url: None,
-            creatives: vec![],
-            delivery_state: DeliveryState::default(),
+            ..Campaign::stub("c1")
         }
     }
 
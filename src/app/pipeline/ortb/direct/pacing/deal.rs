@@ -54,6 +54,16 @@ impl EvenDealPacer {
         persisted + buffered
     }
 
+    fn daily_imps(&self, deal_id: &str) -> u64 {
+        let persisted = self.tracker.daily_impressions(deal_id);
+        let buffered = self
+            .rings
+            .get(deal_id)
+            .map(|r| r.total() as u64)
+            .unwrap_or(0);
+        persisted + buffered
+    }
+
     fn reserve(&self, deal_id: &str) {
         if let Some(ring) = self.rings.get_mut(deal_id) {
             ring.reserve(1.0);
@@ -191,12 +201,7 @@ impl DealPacer for EvenDealPacer {
             }
             DeliveryGoal::Daily(daily_limit) => {
                 // Hard cap: today's delivered count (tracker + buffered ring)
-                let daily_delivered = self.tracker.daily_impressions(&deal.id)
-                    + self
-                        .rings
-                        .get(&deal.id)
-                        .map(|r| r.total() as u64)
-                        .unwrap_or(0);
+                let daily_delivered = self.daily_imps(&deal.id);
                 if daily_delivered >= *daily_limit {
                     return false;
                 }
@@ -220,6 +225,42 @@ impl DealPacer for EvenDealPacer {
         pass
     }
 
+    fn behind_pace(&self, deal: &Deal) -> bool {
+        match &deal.delivery_goal {
+            None => false,
+            Some(DeliveryGoal::Total(limit)) => {
+                let delivered = self.effective_imps(&deal.id);
+                if delivered >= *limit {
+                    return false;
+                }
+
+                // Without a full flight window there is no schedule
+                // to fall behind, so the deal never takes priority
+                let (Some(start), Some(end)) = (deal.start_date, deal.end_date) else {
+                    return false;
+                };
+
+                let now_secs = (self.fine_clock)();
+                let start_secs = start.timestamp() as f64;
+                let flight_secs = (end.timestamp() as f64 - start_secs).max(1.0);
+                let elapsed = ((now_secs - start_secs) / flight_secs).clamp(0.0, 1.0);
+
+                (delivered as f64) < *limit as f64 * elapsed
+            }
+            Some(DeliveryGoal::Daily(daily_limit)) => {
+                let delivered = self.daily_imps(&deal.id);
+                if delivered >= *daily_limit {
+                    return false;
+                }
+
+                let now_epoch = (self.clock)() as f64;
+                let elapsed = (now_epoch % SECS_PER_DAY) / SECS_PER_DAY;
+
+                (delivered as f64) < *daily_limit as f64 * elapsed
+            }
+        }
+    }
+
     fn record_impression(&self, deal_id: &str) {
         self.tracker.record_impression(deal_id);
     }
@@ -243,12 +284,7 @@ impl EvenDealPacer {
                 }
             }
             DeliveryGoal::Daily(daily_limit) => {
-                let daily_delivered = self.tracker.daily_impressions(&deal.id)
-                    + self
-                        .rings
-                        .get(&deal.id)
-                        .map(|r| r.total() as u64)
-                        .unwrap_or(0);
+                let daily_delivered = self.daily_imps(&deal.id);
                 if daily_delivered >= *daily_limit {
                     DeliveryState::DailyBudgetExhausted
                 } else {
@@ -272,9 +308,6 @@ mod tests {
     use crate::app::pipeline::ortb::direct::pacing::reservation::{
         system_epoch_clock, system_fine_clock,
     };
-    use crate::core::models::common::Status;
-    use crate::core::models::deal::{DealOwner, DealPricing, DealTargeting, DemandPolicy};
-    use crate::core::models::targeting::CommonTargeting;
     use chrono::{DateTime, TimeZone, Utc};
     use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
 
@@ -340,25 +373,12 @@ mod tests {
         pacing: Option<DealPacing>,
         end_date: Option<DateTime<Utc>>,
     ) -> Deal {
-        Deal {
-            status: Status::Active,
-            id: "d1".into(),
-            name: "test deal".into(),
-            policy: DemandPolicy::Direct {
-                buyer_ids: vec!["co1".into()],
-            },
-            owner: DealOwner::Platform,
-            pricing: DealPricing::Inherit,
-            targeting: DealTargeting {
-                common: CommonTargeting::default(),
-            },
-            start_date: Some(Utc::now() - chrono::Duration::hours(1)),
-            end_date,
-            delivery_goal: goal,
+        make_deal_with_dates(
+            goal,
             pacing,
-            takes_priority: false,
-            delivery_state: Default::default(),
-        }
+            Some(Utc::now() - chrono::Duration::hours(1)),
+            end_date,
+        )
     }
 
     fn make_deal_with_dates(
@@ -368,23 +388,11 @@ mod tests {
         end_date: Option<DateTime<Utc>>,
     ) -> Deal {
         Deal {
-            status: Status::Active,
-            id: "d1".into(),
-            name: "test deal".into(),
-            policy: DemandPolicy::Direct {
-                buyer_ids: vec!["co1".into()],
-            },
-            owner: DealOwner::Platform,
-            pricing: DealPricing::Inherit,
-            targeting: DealTargeting {
-                common: CommonTargeting::default(),
-            },
             start_date,
             end_date,
             delivery_goal: goal,
             pacing,
-            takes_priority: false,
-            delivery_state: Default::default(),
+            ..Deal::stub("d1")
         }
     }
 
@@ -407,6 +415,53 @@ mod tests {
         EvenDealPacer::new(tracker, Box::new(|| 1), clock, fine_clock)
     }
 
+    /// A guaranteed flight is behind pace while delivery trails
+    /// the elapsed share of the flight.
+    #[test]
+    fn total_flight_behind_pace() {
+        let deal = make_deal_with_dates(
+            Some(DeliveryGoal::Total(1000)),
+            Some(DealPacing::Even),
+            Some(Utc::now() - chrono::Duration::hours(1)),
+            Some(Utc::now() + chrono::Duration::hours(1)),
+        );
+
+        assert!(make_pacer(100).behind_pace(&deal));
+        assert!(!make_pacer(600).behind_pace(&deal));
+        assert!(!make_pacer(1000).behind_pace(&deal));
+        assert!(!make_pacer(0).behind_pace(&make_deal(None, None, None)));
+    }
+
+    /// A total goal without flight dates has no schedule to be behind.
+    #[test]
+    fn total_without_flight_not_behind_pace() {
+        let deal = make_deal(Some(DeliveryGoal::Total(1000)), None, None);
+        assert!(!make_pacer(0).behind_pace(&deal));
+
+        let open_ended = make_deal_with_dates(
+            Some(DeliveryGoal::Total(1000)),
+            None,
+            Some(Utc::now() - chrono::Duration::hours(1)),
+            None,
+        );
+        assert!(!make_pacer(0).behind_pace(&open_ended));
+    }
+
+    /// Daily goals are on pace once delivery matches the elapsed share of the day.
+    #[test]
+    fn daily_behind_pace() {
+        // Midday UTC
+        let (clock, _) = fake_clock(1_700_000_000 - (1_700_000_000 % 86_400) + 43_200);
+        let (fine_clock, _) = fake_fine_clock(0);
+        let deal = make_deal(Some(DeliveryGoal::Daily(1000)), None, None);
+
+        let behind = make_pacer_with_clocks(400, clock.clone(), fine_clock.clone());
+        assert!(behind.behind_pace(&deal));
+
+        let on_pace = make_pacer_with_clocks(600, clock, fine_clock);
+        assert!(!on_pace.behind_pace(&deal));
+    }
+
     /// Deals with no delivery goal bypass pacing entirely.
     #[test]
     fn no_goal_always_passes() {
@@ -56,6 +56,11 @@ pub trait DealPacer: Send + Sync {
     /// its rate limit window.
     fn passes(&self, deal: &Deal) -> bool;
 
+    /// Is this deal delivering below its schedule right now?
+    /// Read-only, consumes no pacing budget. False for deals
+    /// without a delivery goal or which have met it.
+    fn behind_pace(&self, deal: &Deal) -> bool;
+
     /// Record a delivered impression against this deal.
     /// Called from the billing events pipeline.
     fn record_impression(&self, deal_id: &str);
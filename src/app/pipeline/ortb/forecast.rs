@@ -0,0 +1,380 @@
+use crate::app::config::ForecastConfig;
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::HttpRequestContext;
+use crate::app::pipeline::ortb::targeting::matches_targeting;
+use crate::core::enrichment::device::DeviceInfo;
+use crate::core::models::deal::Deal;
+use crate::core::models::placement::Placement;
+use crate::core::models::publisher::Publisher;
+use crate::core::models::targeting::CommonTargeting;
+use chrono::{DateTime, Utc};
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use parking_lot::Mutex;
+use rtb::BidRequest;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shortest window projections are made over, so a freshly started
+/// node doesn't extrapolate a few seconds of traffic to a whole day
+const MIN_WINDOW: Duration = Duration::from_secs(60);
+
+const DAY: Duration = Duration::from_hours(24);
+
+/// Floor quantiles reported for matched imps
+const FLOOR_QUANTILES: [f64; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+
+/// Post enrichment snapshot of a sampled auction, holding only what
+/// targeting is evaluated on. User and IP fields are stripped
+struct Sample {
+    at: Instant,
+    received_at: DateTime<Utc>,
+    publisher: Arc<Publisher>,
+    placement: Option<Arc<Placement>>,
+    req: BidRequest,
+    device: Option<DeviceInfo>,
+}
+
+impl Sample {
+    fn new(ctx: &AuctionContext) -> Self {
+        let mut req = ctx.req.read().clone();
+        req.user = None;
+
+        if let Some(device) = req.device.as_mut() {
+            device.ip.clear();
+            device.ipv6.clear();
+            device.ifa.clear();
+        }
+
+        Self {
+            at: Instant::now(),
+            received_at: ctx.received_at,
+            publisher: ctx.publisher.clone(),
+            placement: ctx.placement.clone(),
+            req,
+            device: ctx.device.get().cloned(),
+        }
+    }
+
+    /// A throwaway auction context to evaluate targeting against
+    fn context(&self) -> AuctionContext {
+        let mut ctx = AuctionContext::new(
+            String::new(),
+            String::new(),
+            self.publisher.clone(),
+            self.placement.clone(),
+            self.req.clone(),
+            HttpRequestContext::default(),
+        );
+
+        // schedules are evaluated as of the sampled auction
+        ctx.received_at = self.received_at;
+
+        if let Some(device) = &self.device {
+            let _ = ctx.device.set(device.clone());
+        }
+
+        ctx
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FloorQuantile {
+    pub quantile: f64,
+    pub floor: f64,
+}
+
+/// Floors of the matched imps in accounting currency, as sent by
+/// the publisher before any learned floors are applied
+#[derive(Debug, Default, Serialize)]
+pub struct FloorDistribution {
+    /// Share of matched imps without a floor
+    pub unfloored_share: f64,
+    /// Quantiles over the floored imps
+    pub quantiles: Vec<FloorQuantile>,
+}
+
+/// Matched imps which an active deal's targeting also matches
+#[derive(Debug, Serialize)]
+pub struct DealOverlap {
+    pub deal_id: String,
+    pub name: String,
+    /// Share of the matched imps the deal also matches
+    pub share: f64,
+    pub projected_daily_imps: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Forecast {
+    /// Sampled auctions evaluated
+    pub samples: usize,
+    /// Traffic window the samples span
+    pub window_secs: u64,
+    /// Sampled imps the targeting matched
+    pub matched_imps: u64,
+    /// Matched imps scaled up to the whole cluster's daily traffic
+    pub projected_daily_imps: u64,
+    pub floors: FloorDistribution,
+    /// Active deals overlapping the targeting, largest overlap first
+    pub deal_overlap: Vec<DealOverlap>,
+}
+
+/// Scales imps matched in a sample window up to a daily
+/// impression count across the cluster
+fn project_daily(matched: u64, sample_rate: f32, window: Duration, cluster_size: usize) -> u64 {
+    if sample_rate <= 0.0 {
+        return 0;
+    }
+
+    let window = window.max(MIN_WINDOW);
+    let per_window = matched as f64 / sample_rate as f64;
+    let daily = per_window * DAY.as_secs_f64() / window.as_secs_f64();
+
+    (daily * cluster_size.max(1) as f64).round() as u64
+}
+
+/// Nearest rank quantiles over ascending sorted floors
+fn floor_distribution(mut floors: Vec<f64>, matched: u64) -> FloorDistribution {
+    if matched == 0 {
+        return FloorDistribution::default();
+    }
+
+    floors.sort_by(f64::total_cmp);
+
+    let unfloored_share = (matched - floors.len() as u64) as f64 / matched as f64;
+
+    let quantiles = if floors.is_empty() {
+        Vec::new()
+    } else {
+        FLOOR_QUANTILES
+            .iter()
+            .map(|&quantile| {
+                let rank = (quantile * floors.len() as f64).ceil() as usize;
+                FloorQuantile {
+                    quantile,
+                    floor: floors[rank.clamp(1, floors.len()) - 1],
+                }
+            })
+            .collect()
+    };
+
+    FloorDistribution {
+        unfloored_share,
+        quantiles,
+    }
+}
+
+/// Keeps a downsampled reservoir of recent enriched auctions, which
+/// proposed deal or campaign targeting is evaluated against to
+/// forecast availability before anything is booked. Samples carry
+/// no user or identity data, so user segment targeting never matches
+pub struct Forecaster {
+    config: ForecastConfig,
+    cluster_size: Box<dyn Fn() -> usize + Send + Sync>,
+    samples: Mutex<VecDeque<Arc<Sample>>>,
+    limiter: DefaultDirectRateLimiter,
+}
+
+impl Forecaster {
+    pub fn new(config: ForecastConfig, cluster_size: Box<dyn Fn() -> usize + Send + Sync>) -> Self {
+        let per_minute = NonZeroU32::new(config.max_requests_per_minute).unwrap_or(NonZeroU32::MIN);
+
+        Self {
+            samples: Mutex::new(VecDeque::with_capacity(config.max_samples)),
+            limiter: RateLimiter::direct(Quota::per_minute(per_minute)),
+            config,
+            cluster_size,
+        }
+    }
+
+    /// Whether another forecast may be evaluated now, each
+    /// evaluation walks the whole reservoir so they're rate limited
+    pub fn try_acquire(&self) -> bool {
+        self.limiter.check().is_ok()
+    }
+
+    /// Samples the auction into the reservoir at the configured rate,
+    /// evicting the oldest sample once full
+    pub fn observe(&self, ctx: &AuctionContext) {
+        if self.config.max_samples == 0 || fastrand::f32() >= self.config.sample_rate {
+            return;
+        }
+
+        self.push(Sample::new(ctx));
+    }
+
+    fn push(&self, sample: Sample) {
+        let mut samples = self.samples.lock();
+
+        while samples.len() >= self.config.max_samples.max(1) {
+            samples.pop_front();
+        }
+
+        samples.push_back(Arc::new(sample));
+    }
+
+    /// Evaluates the targeting against every sampled imp, with
+    /// overlap against the given active deals
+    pub fn evaluate(&self, targeting: &CommonTargeting, deals: &[Arc<Deal>]) -> Forecast {
+        // evaluate over a snapshot so sampling isn't held up
+        let samples: Vec<Arc<Sample>> = self.samples.lock().iter().cloned().collect();
+
+        let window = samples.first().map(|s| s.at.elapsed()).unwrap_or_default();
+
+        let mut matched: u64 = 0;
+        let mut floors = Vec::new();
+        let mut overlap = vec![0u64; deals.len()];
+
+        for sample in &samples {
+            let ctx = &sample.context();
+            let req = ctx.req.read();
+
+            for imp in &req.imp {
+                if !matches_targeting(targeting, ctx, imp) {
+                    continue;
+                }
+
+                matched += 1;
+
+                if imp.bidfloor > 0.0 {
+                    floors.push(imp.bidfloor);
+                }
+
+                for (count, deal) in overlap.iter_mut().zip(deals) {
+                    if deal.allows_publisher(&ctx.publisher)
+                        && matches_targeting(&deal.targeting.common, ctx, imp)
+                    {
+                        *count += 1;
+                    }
+                }
+            }
+        }
+
+        let cluster_size = (self.cluster_size)();
+        let sample_rate = self.config.sample_rate;
+
+        let mut deal_overlap: Vec<DealOverlap> = deals
+            .iter()
+            .zip(overlap)
+            .filter(|(_, count)| *count > 0)
+            .map(|(deal, count)| DealOverlap {
+                deal_id: deal.id.clone(),
+                name: deal.name.clone(),
+                share: count as f64 / matched as f64,
+                projected_daily_imps: project_daily(count, sample_rate, window, cluster_size),
+            })
+            .collect();
+
+        deal_overlap.sort_by(|a, b| b.share.total_cmp(&a.share));
+
+        Forecast {
+            samples: samples.len(),
+            window_secs: window.as_secs(),
+            matched_imps: matched,
+            projected_daily_imps: project_daily(matched, sample_rate, window, cluster_size),
+            floors: floor_distribution(floors, matched),
+            deal_overlap,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::targeting::ListFilter;
+    use compact_str::CompactString;
+    use rtb::bid_request::Imp;
+
+    fn sample(pubid: &str, floors: &[f64]) -> Sample {
+        let ctx = AuctionContext::test_default(pubid);
+        ctx.req.write().imp = floors
+            .iter()
+            .map(|&bidfloor| Imp {
+                bidfloor,
+                ..Default::default()
+            })
+            .collect();
+
+        Sample {
+            at: Instant::now() - Duration::from_hours(1),
+            ..Sample::new(&ctx)
+        }
+    }
+
+    fn forecaster(max_samples: usize) -> Forecaster {
+        Forecaster::new(
+            ForecastConfig {
+                enabled: true,
+                sample_rate: 0.5,
+                max_samples,
+                max_requests_per_minute: 1,
+            },
+            Box::new(|| 2),
+        )
+    }
+
+    #[test]
+    fn projects_sampled_window_to_cluster_day() {
+        // 10 imps at 50% sampling over an hour, on 2 nodes
+        assert_eq!(project_daily(10, 0.5, Duration::from_hours(1), 2), 960);
+        // short windows are projected as if they spanned the minimum
+        assert_eq!(
+            project_daily(1, 1.0, Duration::from_secs(1), 1),
+            project_daily(1, 1.0, MIN_WINDOW, 1)
+        );
+        assert_eq!(project_daily(10, 0.0, Duration::from_hours(1), 1), 0);
+    }
+
+    #[test]
+    fn evaluates_targeting_against_reservoir() {
+        let forecaster = forecaster(2);
+        forecaster.push(sample("evicted", &[9.0]));
+        forecaster.push(sample("pub1", &[1.0, 0.0, 3.0]));
+        forecaster.push(sample("pub2", &[2.0]));
+
+        let targeting = CommonTargeting {
+            pub_id_filter: ListFilter::Allow([CompactString::from("pub1")].into_iter().collect()),
+            ..Default::default()
+        };
+
+        let forecast = forecaster.evaluate(&targeting, &[]);
+
+        assert_eq!(forecast.samples, 2);
+        assert_eq!(forecast.matched_imps, 3);
+        assert_eq!(forecast.projected_daily_imps, 3 * 2 * 24 * 2);
+        assert!((forecast.floors.unfloored_share - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(forecast.floors.quantiles[0].floor, 1.0);
+        assert_eq!(forecast.floors.quantiles[4].floor, 3.0);
+    }
+
+    #[test]
+    fn strips_user_and_ip_from_samples() {
+        let ctx = AuctionContext::test_default("pub1");
+        {
+            let mut req = ctx.req.write();
+            req.user = Some(Default::default());
+            req.device = Some(rtb::bid_request::Device {
+                ip: "192.0.2.1".into(),
+                ifa: "ifa".into(),
+                ..Default::default()
+            });
+        }
+
+        let sample = Sample::new(&ctx);
+        let device = sample.req.device.as_ref().unwrap();
+
+        assert!(sample.req.user.is_none());
+        assert!(device.ip.is_empty());
+        assert!(device.ifa.is_empty());
+    }
+
+    #[test]
+    fn rate_limits_evaluations() {
+        let forecaster = forecaster(1);
+
+        assert!(forecaster.try_acquire());
+        assert!(!forecaster.try_acquire());
+    }
+}
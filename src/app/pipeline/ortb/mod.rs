@@ -1,5 +1,6 @@
 mod context;
 pub mod direct;
+pub mod forecast;
 mod pipeline;
 pub mod targeting;
 mod tasks;
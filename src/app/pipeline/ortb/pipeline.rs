@@ -1,16 +1,20 @@
 use crate::app::context::StartupContext;
 use crate::app::pipeline::ortb::tasks::finalizers::{
-    AuctionBidCountersTask, CampaignCountersTask, DealBidCountersTask, DemandCountersTask,
-    PubCountersTask,
+    AuctionBidCountersTask, CampaignCountersTask, DealBidCountersTask, DealFunnelTask,
+    DemandCountersTask, PubCountersTask,
 };
 use crate::app::pipeline::ortb::{AuctionContext, tasks};
 use crate::app::span::WrappedPipelineTask;
 use crate::core::demand::client::DemandClient;
+use crate::core::demand::health::EndpointHealthTracker;
+use crate::core::demand::latency::LatencyTracker;
+use crate::core::floors::FloorEngine;
 use crate::core::models::placement::FillPolicy;
 use anyhow::{Error, anyhow, bail};
 use async_trait::async_trait;
 use pipeline::{AsyncTask, Pipeline, PipelineBuilder};
 use rtb::child_span_info;
+use std::sync::Arc;
 use tracing::{Instrument, debug};
 
 // ---------------------------------------------------------------------------
@@ -39,13 +43,32 @@ fn build_enrichment_pipeline(
         .get()
         .ok_or(anyhow!("Config not set when building enrichment pipeline"))?;
 
+    let currency = context.currency.get().ok_or(anyhow!(
+        "Currency service not set when building enrichment pipeline"
+    ))?;
+
+    let segment_store = context
+        .segment_store
+        .get()
+        .ok_or_else(|| anyhow!("No segment store"))?;
+
+    let supply_rules = context
+        .supply_rule_manager
+        .get()
+        .ok_or_else(|| anyhow!("No supply rule manager"))?;
+
     let mut builder = PipelineBuilder::new()
         .with_blocking(Box::new(tasks::enrichment::PublisherEnabledCheckTask))
         .with_blocking(Box::new(tasks::enrichment::ValidateRequestTask))
+        .with_blocking(Box::new(tasks::enrichment::CurrencyNormalizeTask::new(
+            currency.clone(),
+        )))
         .with_blocking(Box::new(tasks::enrichment::SchainHopsGlobalFilter::new(
             config.schain_limit,
         )))
-        .with_blocking(Box::new(tasks::enrichment::JunkFilterTask));
+        .with_blocking(Box::new(tasks::enrichment::SupplyRulesTask::new(
+            supply_rules.clone(),
+        )));
 
     if !config.skip_ip_block {
         builder = builder.with_blocking(Box::new(tasks::enrichment::IpBlockTask::new(
@@ -53,7 +76,7 @@ fn build_enrichment_pipeline(
         )));
     }
 
-    let pipeline = builder
+    builder = builder
         .with_blocking(Box::new(tasks::enrichment::DeviceLookupTask::new(
             device_lookup,
         )))
@@ -62,7 +85,23 @@ fn build_enrichment_pipeline(
         .with_blocking(Box::new(tasks::enrichment::SchainAppendTask::new(
             config.schain.clone(),
         )))
-        .with_blocking(Box::new(tasks::enrichment::LocalIdentityTask))
+        .with_blocking(Box::new(tasks::enrichment::LocalIdentityTask::new(
+            config
+                .rxid_eid_source
+                .clone()
+                .or_else(|| config.cookie_domain.clone()),
+        )))
+        .with_async(Box::new(tasks::enrichment::SegmentLoadTask::new(
+            segment_store.clone(),
+        )));
+
+    if let Some(forecaster) = context.forecaster.get() {
+        builder = builder.with_blocking(Box::new(tasks::enrichment::ForecastSampleTask::new(
+            forecaster.clone(),
+        )));
+    }
+
+    let pipeline = builder
         .build()
         .expect("Enrichment pipeline should have tasks");
 
@@ -83,6 +122,11 @@ fn build_rtb_sub_pipeline(
         .get()
         .ok_or(anyhow!("Cluster manager not set"))?;
 
+    let qps_coordinator = context
+        .qps_coordinator
+        .get()
+        .ok_or(anyhow!("QPS coordinator not set"))?;
+
     let bidder_manager = context
         .bidder_manager
         .get()
@@ -100,6 +144,7 @@ fn build_rtb_sub_pipeline(
 
     let demand_client =
         DemandClient::new().or_else(|e| bail!("RTB pipeline client failed: {}", e))?;
+    demand_client.watch_endpoints(bidder_manager);
 
     let deal_manager = context
         .deal_manager
@@ -108,28 +153,79 @@ fn build_rtb_sub_pipeline(
 
     let deal_pacer = context.deal_pacer.get().ok_or(anyhow!("No deal pacer"))?;
 
-    let pipeline = PipelineBuilder::new()
+    let config = context
+        .config
+        .get()
+        .ok_or(anyhow!("Config not set when building rtb pipeline"))?;
+
+    let adapter_registry = context
+        .adapter_registry
+        .get()
+        .ok_or(anyhow!("No bidder adapter registry"))?;
+
+    let currency = context.currency.get().ok_or(anyhow!(
+        "Currency service not set when building rtb pipeline"
+    ))?;
+
+    let callout_latency = Arc::new(LatencyTracker::new());
+    let endpoint_health = Arc::new(EndpointHealthTracker::new(config.circuit_breaker.clone()));
+
+    let floor_engine = Arc::new(FloorEngine::new(config.floors.clone()));
+
+    let mut builder = PipelineBuilder::new()
+        .with_async(Box::new(tasks::rtb::FloorsTask::new(
+            floor_engine.clone(),
+            currency.clone(),
+        )))
         .with_async(Box::new(tasks::rtb::BidderMatchingTask::new(
             bidder_manager.clone(),
             deal_manager.clone(),
             deal_pacer.clone(),
         )))
-        .with_async(Box::new(tasks::rtb::FloorsMarkupTask))
+        .with_async(Box::new(tasks::rtb::FloorsMarkupTask::new(
+            deal_manager.clone(),
+        )))
         .with_async(Box::new(tasks::rtb::IdentityDemandTask::new(
             sync_store.clone(),
-        )))
+        )));
+
+    if let Some(user_data) = &config.segments.user_data {
+        builder = builder.with_async(Box::new(tasks::rtb::SegmentDemandTask::new(
+            user_data.clone(),
+        )));
+    }
+
+    let pipeline = builder
         .with_async(Box::new(tasks::rtb::MultiImpBreakoutTask))
+        .with_async(Box::new(tasks::rtb::CircuitBreakerTask::new(
+            endpoint_health.clone(),
+        )))
         .with_async(Box::new(tasks::rtb::TrafficShapingTask::new(
             shaping_manager.clone(),
         )))
         .with_async(Box::new(tasks::rtb::QpslimiterTask::new(
             bidder_manager.clone(),
             cluster_manager.clone(),
+            qps_coordinator.clone(),
+        )))
+        .with_async(Box::new(tasks::rtb::CalloutTmaxTask::new(
+            callout_latency.clone(),
         )))
-        .with_async(Box::new(tasks::rtb::BidderCalloutsTask::new(demand_client)))
+        .with_async(Box::new(tasks::rtb::BidderAdapterTask::new(
+            adapter_registry.clone(),
+        )))
+        .with_async(Box::new(tasks::rtb::BidderCalloutsTask::new(
+            demand_client,
+            callout_latency,
+            endpoint_health,
+            adapter_registry.clone(),
+        )))
+        .with_async(Box::new(tasks::rtb::BidCurrencyTask::new(currency.clone())))
         .with_async(Box::new(tasks::rtb::RtbDealAttributionTask::new(
             deal_manager.clone(),
         )))
+        .with_async(Box::new(tasks::rtb::DealPriceEnforcementTask))
+        .with_async(Box::new(tasks::rtb::FloorLearningTask::new(floor_engine)))
         .build()
         .expect("RTB sub-pipeline should have tasks");
 
@@ -270,7 +366,14 @@ fn build_finalizers_pipeline(
         .ok_or_else(|| anyhow!("No deal counter store option set on context"))?;
 
     if let Some(deal_store) = deal_store_opt {
-        pipeline_builder.add_async(Box::new(DealBidCountersTask::new(deal_store.clone())));
+        if let Some(deal_mgr) = context.deal_manager.get() {
+            pipeline_builder.add_async(Box::new(DealBidCountersTask::new(
+                deal_store.clone(),
+                deal_mgr.clone(),
+            )));
+        }
+
+        pipeline_builder.add_async(Box::new(DealFunnelTask::new(deal_store.clone())));
     }
 
     Ok(pipeline_builder.build())
@@ -288,6 +391,7 @@ struct AuctionOrchestratorTask {
     conditional_rtb: ConditionalRtbTask,
     merge_task: tasks::direct::MergeDirectBidsTask,
     shared_pipeline: Pipeline<AuctionContext, Error>,
+    settlement_task: tasks::settlement::BidSettlementTask,
     finalizers_pipeline: Option<Pipeline<AuctionContext, Error>>,
 }
 
@@ -339,7 +443,7 @@ impl AuctionOrchestratorTask {
         let shared_res = self.shared_pipeline.run(ctx).await;
 
         // Phase 6: Settlement
-        let settlement_res = tasks::settlement::BidSettlementTask.run(ctx).await;
+        let settlement_res = self.settlement_task.run(ctx).await;
 
         // Surface RTB error if no bids were produced from any source
         if let Some(e) = rtb_err {
@@ -386,6 +490,15 @@ pub fn build_auction_pipeline(
     let shared_pipeline = build_shared_bid_pipeline(context)?;
     let finalizers_pipeline = build_finalizers_pipeline(context)?;
 
+    let currency = context.currency.get().ok_or(anyhow!(
+        "Currency service not set when building auction pipeline"
+    ))?;
+
+    let deal_pacer = context
+        .deal_pacer
+        .get()
+        .ok_or(anyhow!("No deal pacer set when building auction pipeline"))?;
+
     // Direct campaign task — optional, only if managers were loaded
     let direct_task: Option<Box<dyn AsyncTask<AuctionContext, Error>>> = match (
         context.campaign_manager.get(),
@@ -426,6 +539,10 @@ pub fn build_auction_pipeline(
         conditional_rtb: ConditionalRtbTask::new(rtb_sub_pipeline),
         merge_task: tasks::direct::MergeDirectBidsTask,
         shared_pipeline,
+        settlement_task: tasks::settlement::BidSettlementTask::new(
+            currency.clone(),
+            deal_pacer.clone(),
+        ),
         finalizers_pipeline,
     };
 
@@ -1,8 +1,89 @@
 use crate::app::pipeline::ortb::AuctionContext;
-use crate::core::models::targeting::{AllowedPropertyTypes, CommonTargeting};
-use compact_str::CompactString;
+use crate::core::models::targeting::{AllowedPropertyTypes, CommonTargeting, ListFilter};
+use ahash::AHashSet;
+use compact_str::{CompactString, ToCompactString};
 use rtb::bid_request::{DistributionchannelOneof, Imp};
 
+/// IAB Content Category Taxonomy 1.0, also assumed when cattax is absent
+const CATTAX_IAB_CONTENT_1_0: i32 = 1;
+
+/// Categories and keywords of the site or app and its content,
+/// built once per request on first use from [`AuctionContext::contextual`]
+#[derive(Debug, Default)]
+pub struct ContextualSignals {
+    /// Upper cased categories plus the parent of each IAB
+    /// subcategory, so that a filter on IAB2 covers IAB2-3
+    cats: AHashSet<CompactString>,
+    /// Lower cased keywords
+    keywords: AHashSet<CompactString>,
+}
+
+impl ContextualSignals {
+    /// Configured category filters are IAB 1.0, so categories from
+    /// other taxonomies are skipped. Their ids are numeric and would
+    /// otherwise collide or lose the parent relationship
+    fn add_cats<'a>(&mut self, cattax: i32, cats: impl IntoIterator<Item = &'a String>) {
+        if cattax != 0 && cattax != CATTAX_IAB_CONTENT_1_0 {
+            return;
+        }
+
+        for cat in cats.into_iter().map(|c| c.trim()).filter(|c| !c.is_empty()) {
+            let cat = cat.to_uppercase();
+
+            if let Some((parent, _)) = cat.split_once('-') {
+                self.cats.insert(parent.to_compact_string());
+            }
+
+            self.cats.insert(cat.to_compact_string());
+        }
+    }
+
+    /// Keywords are a comma separated list
+    fn add_keywords(&mut self, keywords: &str) {
+        self.keywords.extend(
+            keywords
+                .split(',')
+                .map(|k| k.trim())
+                .filter(|k| !k.is_empty())
+                .map(|k| k.to_lowercase().to_compact_string()),
+        );
+    }
+
+    pub fn from_channel(channel: Option<&DistributionchannelOneof>) -> Self {
+        let mut signals = Self::default();
+
+        match channel {
+            Some(DistributionchannelOneof::Site(site)) => {
+                signals.add_cats(
+                    site.cattax,
+                    site.cat.iter().chain(&site.sectioncat).chain(&site.pagecat),
+                );
+                signals.add_keywords(&site.keywords);
+
+                if let Some(content) = &site.content {
+                    signals.add_cats(content.cattax, &content.cat);
+                    signals.add_keywords(&content.keywords);
+                }
+            }
+            Some(DistributionchannelOneof::App(app)) => {
+                signals.add_cats(
+                    app.cattax,
+                    app.cat.iter().chain(&app.sectioncat).chain(&app.pagecat),
+                );
+                signals.add_keywords(&app.keywords);
+
+                if let Some(content) = &app.content {
+                    signals.add_cats(content.cattax, &content.cat);
+                    signals.add_keywords(&content.keywords);
+                }
+            }
+            _ => {}
+        }
+
+        signals
+    }
+}
+
 /// Evaluates whether a [`CommonTargeting`] passes for a specific imp.
 pub fn matches_targeting(targeting: &CommonTargeting, ctx: &AuctionContext, imp: &Imp) -> bool {
     let req = ctx.req.read();
@@ -19,6 +100,13 @@ pub fn matches_targeting(targeting: &CommonTargeting, ctx: &AuctionContext, imp:
         return false;
     }
 
+    if !targeting
+        .geo
+        .check(req.device.as_ref().and_then(|d| d.geo.as_ref()))
+    {
+        return false;
+    }
+
     // ctx.device populated by DeviceLookupTask; None if lookup didn't run or failed
     let device = ctx.device.get();
 
@@ -62,6 +150,31 @@ pub fn matches_targeting(targeting: &CommonTargeting, ctx: &AuctionContext, imp:
         return false;
     }
 
+    if !matches!(targeting.cats_filter, ListFilter::Any)
+        || !matches!(targeting.keywords_filter, ListFilter::Any)
+    {
+        let signals = ctx
+            .contextual
+            .get_or_init(|| ContextualSignals::from_channel(channel));
+
+        if !targeting.cats_filter.check_any(&signals.cats)
+            || !targeting.keywords_filter.check_any(&signals.keywords)
+        {
+            return false;
+        }
+    }
+
+    let segments = ctx
+        .identity
+        .get()
+        .and_then(|i| i.segments.get())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    if !targeting.segments_filter.check_any(segments) {
+        return false;
+    }
+
     match targeting.allowed_property_types {
         AllowedPropertyTypes::Any => {}
         AllowedPropertyTypes::App => {
@@ -81,6 +194,18 @@ pub fn matches_targeting(targeting: &CommonTargeting, ctx: &AuctionContext, imp:
         }
     }
 
+    if let Some(schedule) = &targeting.schedule {
+        let user_utcoffset = req
+            .device
+            .as_ref()
+            .and_then(|d| d.geo.as_ref())
+            .map(|g| g.utcoffset);
+
+        if !schedule.check(ctx.received_at, user_utcoffset) {
+            return false;
+        }
+    }
+
     true
 }
 
@@ -88,8 +213,13 @@ pub fn matches_targeting(targeting: &CommonTargeting, ctx: &AuctionContext, imp:
 mod tests {
     use super::*;
     use crate::core::enrichment::device::{DeviceInfoBuilder, DeviceType, Os};
-    use crate::core::models::targeting::{AllowedPropertyTypes, CommonTargeting, ListFilter};
+    use crate::core::geo::{GeoRadius, GeoRadiusIndex};
+    use crate::core::models::targeting::{
+        AllowedPropertyTypes, CommonTargeting, DaypartWindow, GeoTargeting, ListFilter,
+        ScheduleTargeting, ScheduleTimezone,
+    };
     use ahash::AHashSet;
+    use chrono::{TimeZone, Utc, Weekday};
     use compact_str::CompactString;
     use rtb::BidRequestBuilder;
     use rtb::bid_request::{DeviceBuilder, GeoBuilder, ImpBuilder, SiteBuilder};
@@ -400,4 +530,352 @@ mod tests {
         let imp = default_imp();
         assert!(matches_targeting(&targeting, &ctx, &imp));
     }
+
+    fn weekday_evenings(timezone: ScheduleTimezone) -> CommonTargeting {
+        CommonTargeting {
+            schedule: Some(ScheduleTargeting {
+                timezone,
+                windows: vec![DaypartWindow {
+                    days: vec![
+                        Weekday::Mon,
+                        Weekday::Tue,
+                        Weekday::Wed,
+                        Weekday::Thu,
+                        Weekday::Fri,
+                    ],
+                    start_hour: 18,
+                    end_hour: 2,
+                }],
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn schedule_fixed_timezone() {
+        let targeting = weekday_evenings(ScheduleTimezone::Fixed { offset_mins: -300 });
+        let mut ctx = default_ctx();
+        let imp = default_imp();
+
+        // Fri 2026-01-02 23:00 UTC is Fri 18:00 EST
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 2, 23, 0, 0).unwrap();
+        assert!(matches_targeting(&targeting, &ctx, &imp));
+
+        // Sat 06:30 UTC is Sat 01:30 EST, still in Friday's window
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 3, 6, 30, 0).unwrap();
+        assert!(matches_targeting(&targeting, &ctx, &imp));
+
+        // Sat 23:00 UTC is Sat 18:00 EST
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 3, 23, 0, 0).unwrap();
+        assert!(!matches_targeting(&targeting, &ctx, &imp));
+    }
+
+    #[test]
+    fn schedule_user_timezone() {
+        let targeting = weekday_evenings(ScheduleTimezone::User {
+            fallback_offset_mins: 0,
+        });
+        let imp = default_imp();
+
+        // Mon 2026-01-05 10:00 UTC, the user at UTC+9 is at 19:00
+        let mut ctx = ctx_with_geo("JP");
+        ctx.req
+            .get_mut()
+            .device
+            .as_mut()
+            .unwrap()
+            .geo
+            .as_mut()
+            .unwrap()
+            .utcoffset = 540;
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 5, 10, 0, 0).unwrap();
+        assert!(matches_targeting(&targeting, &ctx, &imp));
+
+        // no utcoffset falls back to UTC, 10:00 is outside the window
+        let mut ctx = default_ctx();
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 5, 10, 0, 0).unwrap();
+        assert!(!matches_targeting(&targeting, &ctx, &imp));
+    }
+
+    #[test]
+    fn schedule_iana_timezone_follows_dst() {
+        let targeting = weekday_evenings(ScheduleTimezone::Zone {
+            tz: chrono_tz::America::New_York,
+        });
+        let mut ctx = default_ctx();
+        let imp = default_imp();
+
+        // Fri 2026-01-02 23:00 UTC is Fri 18:00 EST
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 2, 23, 0, 0).unwrap();
+        assert!(matches_targeting(&targeting, &ctx, &imp));
+
+        // Fri 2026-07-03 22:00 UTC is Fri 18:00 EDT, but 17:00 EST
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 7, 3, 22, 0, 0).unwrap();
+        assert!(matches_targeting(&targeting, &ctx, &imp));
+    }
+
+    #[test]
+    fn schedule_half_hour_offset() {
+        let targeting = weekday_evenings(ScheduleTimezone::Fixed { offset_mins: 330 });
+        let mut ctx = default_ctx();
+        let imp = default_imp();
+
+        // Mon 2026-01-05 12:30 UTC is 18:00 IST, 12:29 is 17:59
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 5, 12, 30, 0).unwrap();
+        assert!(matches_targeting(&targeting, &ctx, &imp));
+        ctx.received_at = Utc.with_ymd_and_hms(2026, 1, 5, 12, 29, 0).unwrap();
+        assert!(!matches_targeting(&targeting, &ctx, &imp));
+    }
+
+    #[test]
+    fn schedule_validation() {
+        let window = |start_hour, end_hour| ScheduleTargeting {
+            timezone: ScheduleTimezone::Fixed { offset_mins: 0 },
+            windows: vec![DaypartWindow {
+                days: vec![Weekday::Mon],
+                start_hour,
+                end_hour,
+            }],
+        };
+
+        assert!(window(0, 24).validate().is_ok());
+        assert!(window(22, 2).validate().is_ok());
+        assert!(window(24, 2).validate().is_err());
+        assert!(window(2, 0).validate().is_err());
+        assert!(window(2, 25).validate().is_err());
+        assert!(window(9, 9).validate().is_err());
+
+        let far_offset = ScheduleTargeting {
+            timezone: ScheduleTimezone::Fixed {
+                offset_mins: 24 * 60,
+            },
+            windows: vec![],
+        };
+        assert!(far_offset.validate().is_err());
+    }
+
+    #[test]
+    fn configured_geo_values_normalized() {
+        let mut targeting = CommonTargeting {
+            geo: GeoTargeting {
+                regions_filter: allow_set(vec![CompactString::from(" ny ")]),
+                cities_filter: deny_set(vec![CompactString::from("Buffalo")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        targeting.normalize();
+        let imp = default_imp();
+
+        let set_geo = |region: &str, city: &str| {
+            let mut ctx = ctx_with_geo("US");
+            let geo = ctx
+                .req
+                .get_mut()
+                .device
+                .as_mut()
+                .unwrap()
+                .geo
+                .as_mut()
+                .unwrap();
+            geo.region = region.to_string();
+            geo.city = city.to_string();
+            ctx
+        };
+
+        assert!(matches_targeting(
+            &targeting,
+            &set_geo("NY", "New York"),
+            &imp
+        ));
+        assert!(!matches_targeting(
+            &targeting,
+            &set_geo("NY", "BUFFALO"),
+            &imp
+        ));
+    }
+
+    #[test]
+    fn sub_country_geo() {
+        let targeting = CommonTargeting {
+            geos_filter: allow_set(vec![CompactString::from("US")]),
+            geo: GeoTargeting {
+                regions_filter: allow_set(vec![CompactString::from("NY")]),
+                cities_filter: deny_set(vec![CompactString::from("buffalo")]),
+                radius: Some(GeoRadiusIndex::from(vec![GeoRadius {
+                    lat: 40.758,
+                    lon: -73.9855,
+                    radius_km: 5.0,
+                }])),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let imp = default_imp();
+
+        let set_geo = |region: &str, city: &str, lat: f64, lon: f64| {
+            let mut ctx = ctx_with_geo("US");
+            let geo = ctx
+                .req
+                .get_mut()
+                .device
+                .as_mut()
+                .unwrap()
+                .geo
+                .as_mut()
+                .unwrap();
+            geo.region = region.to_string();
+            geo.city = city.to_string();
+            geo.lat = lat;
+            geo.lon = lon;
+            ctx
+        };
+
+        assert!(matches_targeting(
+            &targeting,
+            &set_geo("ny", "New York", 40.7484, -73.9857),
+            &imp
+        ));
+        // outside the radius
+        assert!(!matches_targeting(
+            &targeting,
+            &set_geo("NY", "Albany", 42.6526, -73.7562),
+            &imp
+        ));
+        // denied city, whatever the location
+        assert!(!matches_targeting(
+            &targeting,
+            &set_geo("NY", "Buffalo", 40.7484, -73.9857),
+            &imp
+        ));
+        // no location
+        assert!(!matches_targeting(
+            &targeting,
+            &set_geo("NY", "New York", 0.0, 0.0),
+            &imp
+        ));
+    }
+
+    #[test]
+    fn category_hierarchy_and_keywords() {
+        let targeting = CommonTargeting {
+            cats_filter: allow_set(vec![CompactString::from("IAB2")]),
+            keywords_filter: deny_set(vec![CompactString::from("recall")]),
+            ..Default::default()
+        };
+        let imp = default_imp();
+
+        let site_ctx = |pagecat: &str, keywords: &str| {
+            let mut ctx = ctx_with_site("example.com");
+            if let Some(DistributionchannelOneof::Site(site)) =
+                ctx.req.get_mut().distributionchannel_oneof.as_mut()
+            {
+                site.pagecat = vec![pagecat.to_string()];
+                site.keywords = keywords.to_string();
+            }
+            ctx
+        };
+
+        assert!(matches_targeting(
+            &targeting,
+            &site_ctx("IAB2-3", "cars, suv"),
+            &imp
+        ));
+        assert!(!matches_targeting(
+            &targeting,
+            &site_ctx("IAB3", "cars"),
+            &imp
+        ));
+        assert!(!matches_targeting(
+            &targeting,
+            &site_ctx("IAB2", "suv, Recall"),
+            &imp
+        ));
+        // no categories can't satisfy an allow list
+        assert!(!matches_targeting(&targeting, &default_ctx(), &imp));
+    }
+
+    #[test]
+    fn configured_cats_and_keywords_normalized() {
+        let mut targeting = CommonTargeting {
+            cats_filter: allow_set(vec![CompactString::from(" iab2 ")]),
+            keywords_filter: deny_set(vec![CompactString::from(" Recall")]),
+            ..Default::default()
+        };
+        targeting.normalize();
+        let imp = default_imp();
+
+        let site_ctx = |pagecat: &str, keywords: &str| {
+            let mut ctx = ctx_with_site("example.com");
+            if let Some(DistributionchannelOneof::Site(site)) =
+                ctx.req.get_mut().distributionchannel_oneof.as_mut()
+            {
+                site.pagecat = vec![pagecat.to_string()];
+                site.keywords = keywords.to_string();
+            }
+            ctx
+        };
+
+        assert!(matches_targeting(
+            &targeting,
+            &site_ctx("iab2-3", "cars"),
+            &imp
+        ));
+        assert!(!matches_targeting(
+            &targeting,
+            &site_ctx("IAB2", "RECALL"),
+            &imp
+        ));
+    }
+
+    #[test]
+    fn other_taxonomy_cats_skipped() {
+        let targeting = CommonTargeting {
+            cats_filter: allow_set(vec![CompactString::from("IAB2")]),
+            ..Default::default()
+        };
+        let imp = default_imp();
+
+        let mut ctx = ctx_with_site("example.com");
+        if let Some(DistributionchannelOneof::Site(site)) =
+            ctx.req.get_mut().distributionchannel_oneof.as_mut()
+        {
+            // Content Taxonomy 2.0 ids
+            site.cattax = 2;
+            site.cat = vec!["IAB2".to_string()];
+        }
+
+        assert!(!matches_targeting(&targeting, &ctx, &imp));
+    }
+
+    #[test]
+    fn segment_allow_and_deny() {
+        let allow = CommonTargeting {
+            segments_filter: allow_set(vec![CompactString::from("saw-campaign-1")]),
+            ..Default::default()
+        };
+        let deny = CommonTargeting {
+            segments_filter: deny_set(vec![CompactString::from("converted")]),
+            ..Default::default()
+        };
+        let imp = default_imp();
+
+        // unrecognized users have no segments
+        let ctx = default_ctx();
+        assert!(!matches_targeting(&allow, &ctx, &imp));
+        assert!(matches_targeting(&deny, &ctx, &imp));
+
+        let ctx = default_ctx();
+        let identity = ctx.identity.get_or_init(Default::default);
+        identity
+            .segments
+            .set(vec![
+                CompactString::from("saw-campaign-1"),
+                CompactString::from("converted"),
+            ])
+            .unwrap();
+
+        assert!(matches_targeting(&allow, &ctx, &imp));
+        assert!(!matches_targeting(&deny, &ctx, &imp));
+    }
 }
@@ -0,0 +1,111 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::PublisherBlockReason;
+use crate::core::currency::{CurrencyService, normalize_code};
+use anyhow::anyhow;
+use pipeline::BlockingTask;
+use rtb::child_span_info;
+use rtb::common::bidresponsestate::BidResponseState;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Normalizes imp and deal floors into the accounting currency and
+/// records the currency the publisher expects the response in, so
+/// every downstream price comparison happens in a single currency.
+/// Requests priced in a currency we have no rate for are rejected.
+/// Without configured rates the request is left as is
+pub struct CurrencyNormalizeTask {
+    currency: Arc<CurrencyService>,
+}
+
+impl CurrencyNormalizeTask {
+    pub fn new(currency: Arc<CurrencyService>) -> Self {
+        Self { currency }
+    }
+
+    fn reject(&self, context: &AuctionContext, code: &str) -> Result<(), anyhow::Error> {
+        context
+            .res
+            .set(BidResponseState::NoBidReason {
+                reqid: context.original_auction_id.clone(),
+                nbr: rtb::spec::openrtb::nobidreason::INVALID_REQUEST,
+                desc: Some("Unsupported currency".into()),
+            })
+            .map_err(|_| anyhow!("Failed to attach block pub reason on ctx"))?;
+
+        context
+            .block_reason
+            .set(PublisherBlockReason::UnsupportedCurrency)
+            .map_err(|_| anyhow!("Failed to attach block pub reason on ctx"))?;
+
+        Err(anyhow!("Auction priced in unsupported currency {}", code))
+    }
+}
+
+impl BlockingTask<AuctionContext, anyhow::Error> for CurrencyNormalizeTask {
+    fn run(&self, context: &AuctionContext) -> Result<(), anyhow::Error> {
+        let _span = child_span_info!("currency_normalize_task").entered();
+
+        let accounting = self.currency.accounting();
+        let rates = self.currency.rates();
+
+        let mut req = context.req.write();
+
+        // per spec bids are in the first allowed currency, USD if none
+        let pub_currency = normalize_code(req.cur.first().map(String::as_str).unwrap_or(""));
+
+        if rates.passthrough {
+            return context
+                .currency
+                .set(pub_currency)
+                .map_err(|_| anyhow!("Publisher currency already set on context"));
+        }
+
+        if rates.convert(1.0, accounting, &pub_currency).is_none() {
+            return self.reject(context, &pub_currency);
+        }
+
+        for imp in req.imp.iter_mut() {
+            let Some(floor) = rates.convert(imp.bidfloor, &imp.bidfloorcur, accounting) else {
+                let code = normalize_code(&imp.bidfloorcur);
+                return self.reject(context, &code);
+            };
+
+            if floor != imp.bidfloor {
+                debug!(
+                    "Normalized imp floor {} {} -> {} {}",
+                    imp.bidfloor, imp.bidfloorcur, floor, accounting
+                );
+            }
+
+            imp.bidfloor = floor;
+            imp.bidfloorcur = accounting.to_string();
+
+            let Some(pmp) = imp.pmp.as_mut() else {
+                continue;
+            };
+
+            for deal in pmp.deals.iter_mut() {
+                match rates.convert(deal.bidfloor, &deal.bidfloorcur, accounting) {
+                    Some(floor) => {
+                        deal.bidfloor = floor;
+                        deal.bidfloorcur = accounting.to_string();
+                    }
+                    None => {
+                        let code = normalize_code(&deal.bidfloorcur);
+                        return self.reject(context, &code);
+                    }
+                }
+            }
+        }
+
+        // demand is only ever asked to bid in accounting currency
+        req.cur = vec![accounting.to_string()];
+
+        context
+            .currency
+            .set(pub_currency)
+            .map_err(|_| anyhow!("Publisher currency already set on context"))?;
+
+        Ok(())
+    }
+}
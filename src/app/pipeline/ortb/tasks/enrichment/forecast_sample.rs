@@ -0,0 +1,28 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::forecast::Forecaster;
+use anyhow::Error;
+use pipeline::BlockingTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+
+/// Last enrichment step, offers the fully enriched request
+/// to the forecasting reservoir which samples a fraction of them
+pub struct ForecastSampleTask {
+    forecaster: Arc<Forecaster>,
+}
+
+impl ForecastSampleTask {
+    pub fn new(forecaster: Arc<Forecaster>) -> Self {
+        Self { forecaster }
+    }
+}
+
+impl BlockingTask<AuctionContext, anyhow::Error> for ForecastSampleTask {
+    fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let _span = child_span_info!("forecast_sample_task").entered();
+
+        self.forecaster.observe(context);
+
+        Ok(())
+    }
+}
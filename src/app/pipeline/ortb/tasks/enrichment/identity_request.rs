@@ -5,6 +5,8 @@ use anyhow::{Error, anyhow};
 use opentelemetry::metrics::Counter;
 use opentelemetry::{KeyValue, global};
 use pipeline::BlockingTask;
+use rtb::bid_request::user::Eid;
+use rtb::bid_request::user::eid::Uid;
 use rtb::bid_request::{DistributionchannelOneof, UserBuilder};
 use rtb::child_span_info;
 use std::sync::LazyLock;
@@ -47,23 +49,83 @@ fn extract_cookies_buyeruid(context: &AuctionContext, cookie_param: &str) -> Opt
     context.http.cookies.get(cookie_param).cloned()
 }
 
+/// Agent type of ids tied to a browser or device, per AdCOM
+const EID_ATYPE_DEVICE: i32 = 1;
+
+/// Takes the inbound user.eids off the request, so they're only
+/// sent on to bidders allowing each source, by default all bidders.
+/// Sources are lower cased, and entries without a source or ids
+/// are dropped
+fn take_req_eids(req: &mut rtb::BidRequest) -> Vec<Eid> {
+    let Some(user) = req.user.as_mut() else {
+        return Vec::new();
+    };
+
+    let mut eids: Vec<Eid> = Vec::with_capacity(user.eids.len());
+
+    for mut eid in std::mem::take(&mut user.eids) {
+        eid.source = eid.source.trim().to_lowercase();
+        eid.uids.retain(|uid| !uid.id.trim().is_empty());
+
+        if eid.source.is_empty() || eid.uids.is_empty() {
+            continue;
+        }
+
+        match eids.iter_mut().find(|e| e.source == eid.source) {
+            Some(existing) => existing.uids.extend(eid.uids),
+            None => eids.push(eid),
+        }
+    }
+
+    eids
+}
+
+/// Our local uid as an EID under the configured source
+fn rxid_eid(source: &str, local_uid: &str) -> Eid {
+    Eid {
+        source: source.to_string(),
+        uids: vec![Uid {
+            id: local_uid.to_string(),
+            atype: EID_ATYPE_DEVICE,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
 /// Task responsible for extracting our local exchange uid value
-/// and (in the future) any other identity values that apply
-/// to this request as a whole e.g. RampId envelopes.
-/// This extracts and attached them to the auction context,
-/// and will place our primary local uid value in the
-/// 'user.buyerid' value. A later task should handle
-/// mapping and injecting of bidder specific values based
-/// on our request-wide IDs this task handles
-/// TODO this only handles web cookies and supply
-/// provided buyeruid values for now, no external IDs yet
-pub struct LocalIdentityTask;
+/// and the extended ids (EIDs) which apply to this request as a
+/// whole, e.g. UID2, ID5 or RampId. This extracts and attaches
+/// them to the auction context, and will place our primary local
+/// uid value in the 'user.buyerid' value. Our local uid is also
+/// added as an EID when an rxid EID source is configured. A later
+/// task handles mapping and injecting of bidder specific values
+/// based on our request-wide IDs this task handles
+pub struct LocalIdentityTask {
+    /// EID source our local uid is shared under, e.g. our domain
+    rxid_eid_source: Option<String>,
+}
 
 impl LocalIdentityTask {
-    fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
-        let span = Span::current();
+    /// A leading dot is trimmed from the source, as
+    /// when it defaults to a cookie domain like .example.com
+    pub fn new(rxid_eid_source: Option<String>) -> Self {
+        let rxid_eid_source = rxid_eid_source
+            .map(|s| s.trim().trim_start_matches('.').to_lowercase())
+            .filter(|s| !s.is_empty());
+
+        Self { rxid_eid_source }
+    }
 
-        let mut req = context.req.write();
+    /// Resolves our local uid from the request or cookies and
+    /// places it in user.id, None for app requests or when
+    /// the user isn't recognized
+    fn resolve_local_uid(
+        &self,
+        context: &AuctionContext,
+        req: &mut rtb::BidRequest,
+    ) -> Result<Option<String>, Error> {
+        let span = Span::current();
 
         if matches!(
             req.distributionchannel_oneof,
@@ -79,7 +141,7 @@ impl LocalIdentityTask {
                 }
             }
 
-            return Ok(());
+            return Ok(None);
         }
 
         let publisher = &context.publisher;
@@ -89,7 +151,26 @@ impl LocalIdentityTask {
             KeyValue::new("pub_name", publisher.name.clone()),
         ];
 
-        let local_uid = match extract_req_buyeruid(&req) {
+        if usersync::utils::is_opted_out(&context.http.cookies) {
+            debug!("User opted out, not recognizing them");
+
+            // including any publisher provided id and audience data
+            if let Some(user) = req.user.as_mut() {
+                user.buyeruid.clear();
+                user.id.clear();
+                user.data.clear();
+            }
+
+            span.record("local_source", "opted_out");
+            attrs.push(KeyValue::new("opted_out", true));
+            COUNTER_BUYERUID_MATCHES.add(1, &attrs);
+
+            return Ok(None);
+        }
+
+        attrs.push(KeyValue::new("opted_out", false));
+
+        let local_uid = match extract_req_buyeruid(req) {
             Some(uid) => {
                 debug!("Received matched buyeruid in request");
 
@@ -116,7 +197,7 @@ impl LocalIdentityTask {
                     attrs.push(KeyValue::new("matched_user", false));
                     COUNTER_BUYERUID_MATCHES.add(1, &attrs);
 
-                    return Ok(());
+                    return Ok(None);
                 }
             },
         };
@@ -139,14 +220,47 @@ impl LocalIdentityTask {
             }
         }
 
+        Ok(Some(local_uid))
+    }
+
+    fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let mut req = context.req.write();
+
+        let mut eids = take_req_eids(&mut req);
+        let local_uid = self.resolve_local_uid(context, &mut req)?;
+
+        // opted out users' ids aren't passed on, whoever set them
+        if usersync::utils::is_opted_out(&context.http.cookies) {
+            eids.clear();
+        }
+
+        if let (Some(uid), Some(source)) = (&local_uid, &self.rxid_eid_source) {
+            eids.push(rxid_eid(source, uid));
+        }
+
+        Span::current().record("eids", eids.len());
+
+        if local_uid.is_none() && eids.is_empty() {
+            return Ok(());
+        }
+
         let identity = context.identity.get_or_init(|| IdentityContext::default());
 
-        identity
-            .local_uid
-            .set(local_uid.clone())
-            .map_err(|_| anyhow!("Failed to set local_uid on identity context?!"))?;
+        if let Some(local_uid) = local_uid {
+            debug!("Recognized web user (local_uid) {}", local_uid);
+
+            identity
+                .local_uid
+                .set(local_uid)
+                .map_err(|_| anyhow!("Failed to set local_uid on identity context?!"))?;
+        }
 
-        debug!("Recognized web user (local_uid) {}", local_uid);
+        if !eids.is_empty() {
+            identity
+                .eids
+                .set(eids)
+                .map_err(|_| anyhow!("Failed to set eids on identity context?!"))?;
+        }
 
         Ok(())
     }
@@ -157,9 +271,100 @@ impl BlockingTask<AuctionContext, Error> for LocalIdentityTask {
         let _span = child_span_info!(
             "user_syncs_task",
             local_uid = tracing::field::Empty,
-            local_source = tracing::field::Empty
+            local_source = tracing::field::Empty,
+            eids = tracing::field::Empty
         );
 
         self.run0(context)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rtb::bid_request::User;
+
+    fn eid(source: &str, ids: &[&str]) -> Eid {
+        Eid {
+            source: source.to_string(),
+            uids: ids
+                .iter()
+                .map(|id| Uid {
+                    id: id.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn takes_normalized_eids_off_request() {
+        let mut req = rtb::BidRequest {
+            user: Some(User {
+                eids: vec![
+                    eid("UIDAPI.com", &["a"]),
+                    eid("id5-sync.com", &[""]),
+                    eid("", &["b"]),
+                    eid("uidapi.com", &["c"]),
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let eids = take_req_eids(&mut req);
+
+        assert_eq!(eids.len(), 1);
+        assert_eq!(eids[0].source, "uidapi.com");
+        assert_eq!(eids[0].uids.len(), 2);
+        assert!(req.user.unwrap().eids.is_empty());
+    }
+
+    #[test]
+    fn rxid_source_trims_cookie_domain_dot() {
+        let task = LocalIdentityTask::new(Some(".Example.com".to_string()));
+        assert_eq!(task.rxid_eid_source.as_deref(), Some("example.com"));
+
+        assert!(
+            LocalIdentityTask::new(Some(".".to_string()))
+                .rxid_eid_source
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn opted_out_user_ids_and_data_cleared() {
+        let mut ctx = AuctionContext::test_default("pub");
+        ctx.http.cookies.insert(
+            usersync::constants::CONST_REX_OPTOUT_COOKIE.to_string(),
+            "1".to_string(),
+        );
+        ctx.http.cookies.insert(
+            usersync::constants::CONST_REX_COOKIE_ID_PARAM.to_string(),
+            "rx-abc".to_string(),
+        );
+
+        let req = ctx.req.get_mut();
+        req.distributionchannel_oneof = Some(DistributionchannelOneof::Site(Default::default()));
+        req.user = Some(User {
+            id: "publisher-ppid".into(),
+            buyeruid: "rx-abc".into(),
+            data: vec![Default::default()],
+            eids: vec![eid("uidapi.com", &["a"])],
+            ..Default::default()
+        });
+
+        LocalIdentityTask::new(Some("example.com".into()))
+            .run0(&ctx)
+            .unwrap();
+
+        let req = ctx.req.read();
+        let user = req.user.as_ref().unwrap();
+        assert!(user.id.is_empty());
+        assert!(user.buyeruid.is_empty());
+        assert!(user.data.is_empty());
+        assert!(user.eids.is_empty());
+        assert!(ctx.identity.get().is_none());
+    }
+}
@@ -1,9 +1,15 @@
 mod auction_id;
 pub use auction_id::AuctionIdTask;
 
+mod currency_normalize;
+pub use currency_normalize::CurrencyNormalizeTask;
+
 mod device_lookup;
 pub use device_lookup::DeviceLookupTask;
 
+mod forecast_sample;
+pub use forecast_sample::ForecastSampleTask;
+
 mod hops_filter;
 pub use hops_filter::SchainHopsGlobalFilter;
 
@@ -13,15 +19,18 @@ pub use identity_request::LocalIdentityTask;
 mod ip_block;
 pub use ip_block::IpBlockTask;
 
-mod junk_filter;
-pub use junk_filter::JunkFilterTask;
-
 mod pub_lookup;
 pub use pub_lookup::PublisherEnabledCheckTask;
 
 mod schain_append;
 pub use schain_append::SchainAppendTask;
 
+mod segment_load;
+pub use segment_load::SegmentLoadTask;
+
+mod supply_rules;
+pub use supply_rules::SupplyRulesTask;
+
 mod tmax_offset;
 pub use tmax_offset::TmaxOffsetTask;
 
@@ -0,0 +1,57 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::core::segments::SegmentStore;
+use anyhow::{Error, anyhow};
+use async_trait::async_trait;
+use compact_str::CompactString;
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+use tracing::{Instrument, Span, debug};
+
+/// Loads the first party segments of the recognized local
+/// uid onto the identity context for segment targeting.
+/// Runs after LocalIdentityTask
+pub struct SegmentLoadTask {
+    store: Arc<dyn SegmentStore>,
+}
+
+impl SegmentLoadTask {
+    pub fn new(store: Arc<dyn SegmentStore>) -> Self {
+        Self { store }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let Some(identity) = context.identity.get() else {
+            return Ok(());
+        };
+
+        let Some(local_uid) = identity.local_uid.get() else {
+            return Ok(());
+        };
+
+        let segments: Vec<CompactString> = self
+            .store
+            .load(local_uid)
+            .await
+            .into_iter()
+            .map(CompactString::from)
+            .collect();
+
+        debug!("Loaded {} segments for {}", segments.len(), local_uid);
+        Span::current().record("segments", segments.len());
+
+        identity
+            .segments
+            .set(segments)
+            .map_err(|_| anyhow!("Segments already set on identity context?!"))
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for SegmentLoadTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("segment_load_task", segments = tracing::field::Empty);
+
+        self.run0(context).instrument(span).await
+    }
+}
@@ -0,0 +1,137 @@
+use crate::app::pipeline::ortb::context::PublisherBlockReason;
+use crate::app::pipeline::ortb::{AuctionContext, telemetry};
+use crate::core::filters::supply::{SupplyFacts, store_id_from_url};
+use crate::core::managers::SupplyRuleManager;
+use crate::core::models::supply_rule::SupplyRuleAction;
+use crate::core::spec::nobidreasons;
+use anyhow::{Error, anyhow};
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+use pipeline::BlockingTask;
+use rtb::BidRequest;
+use rtb::bid_request::DistributionchannelOneof;
+use rtb::child_span_info;
+use rtb::common::bidresponsestate::BidResponseState;
+use std::sync::{Arc, LazyLock};
+use tracing::{Span, debug};
+
+static COUNTER_REQUEST_FLAGGED: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:supply:requests")
+        .u64_counter("requests.flagged")
+        .with_description("Publisher requests matching a flagging supply rule")
+        .with_unit("1")
+        .build()
+});
+
+fn supply_facts(pub_id: &str, req: &BidRequest) -> SupplyFacts {
+    let mut facts = SupplyFacts {
+        pub_id: pub_id.to_string(),
+        ..Default::default()
+    };
+
+    match req.distributionchannel_oneof.as_ref() {
+        Some(DistributionchannelOneof::App(app)) => {
+            facts.bundle = app.bundle.trim().to_lowercase();
+            facts.domain = app.domain.trim().to_lowercase();
+            facts.store_id = store_id_from_url(&app.storeurl);
+        }
+        Some(DistributionchannelOneof::Site(site)) => {
+            facts.domain = site.domain.trim().to_lowercase();
+        }
+        _ => {}
+    }
+
+    if let Some(device) = req.device.as_ref() {
+        let ip = if device.ip.is_empty() {
+            &device.ipv6
+        } else {
+            &device.ip
+        };
+
+        facts.ip = ip.parse().ok();
+        facts.user_agent = device.ua.to_lowercase();
+    }
+
+    facts
+}
+
+/// Blocks or flags supply matching the configured supply quality
+/// rules, e.g. spoofed premium app bundles that shouldn't be
+/// available on the open market. Blocked requests are tagged with
+/// the rule which fired
+pub struct SupplyRulesTask {
+    rules: Arc<SupplyRuleManager>,
+}
+
+impl SupplyRulesTask {
+    pub fn new(rules: Arc<SupplyRuleManager>) -> Self {
+        Self { rules }
+    }
+}
+
+impl BlockingTask<AuctionContext, Error> for SupplyRulesTask {
+    fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let parent_span = Span::current();
+        let span = child_span_info!(
+            "supply_rules_task",
+            supply_rules_matched = tracing::field::Empty
+        )
+        .entered();
+
+        let req = context.req.read();
+
+        // validation rejects these with a response first, this backs it up
+        if req.distributionchannel_oneof.is_none() {
+            return Err(anyhow!("No distributionchannel"));
+        }
+
+        let facts = supply_facts(&context.publisher.id, &req);
+        drop(req);
+        let matched = self.rules.matching(&facts);
+
+        if matched.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<&str> = matched.iter().map(|r| r.id.as_str()).collect();
+        span.record("supply_rules_matched", ids.join(",").as_str());
+
+        for rule in matched
+            .iter()
+            .filter(|r| r.action == SupplyRuleAction::Flag)
+        {
+            COUNTER_REQUEST_FLAGGED.add(
+                1,
+                &[
+                    KeyValue::new("pub_id", facts.pub_id.clone()),
+                    KeyValue::new("rule", rule.id.clone()),
+                ],
+            );
+        }
+
+        let Some(rule) = matched.iter().find(|r| r.action == SupplyRuleAction::Block) else {
+            debug!("Request flagged by supply rules {:?}", ids);
+            return Ok(());
+        };
+
+        let brs = BidResponseState::NoBidReason {
+            reqid: context.original_auction_id.clone(),
+            nbr: nobidreasons::BLOCKED_BY_SUPPLY_RULE,
+            desc: Some("Blocked by supply rule".into()),
+        };
+
+        context
+            .res
+            .set(brs)
+            .map_err(|_| anyhow!("Someone already set a BidResponseState!"))?;
+
+        context
+            .block_reason
+            .set(PublisherBlockReason::SupplyRule(rule.id.clone()))
+            .map_err(|_| anyhow!("Failed to attach block pub reason on ctx"))?;
+
+        parent_span.record(telemetry::SPAN_REQ_BLOCK_REASON, "supply_rule");
+
+        Err(anyhow!("Blocked by supply rule {}", rule.id))
+    }
+}
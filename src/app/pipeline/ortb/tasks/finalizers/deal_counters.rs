@@ -1,26 +1,81 @@
 use crate::app::pipeline::ortb::AuctionContext;
-use crate::app::pipeline::ortb::context::BidderResponseState;
+use crate::app::pipeline::ortb::context::{BidderCallout, BidderResponseState};
 use crate::core::firestore::counters::deal::{DealCounterStore, DealCounters};
+use crate::core::managers::DealManager;
+use crate::core::spec::lossreasons;
 use anyhow::Error;
 use async_trait::async_trait;
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
 use pipeline::AsyncTask;
 use rtb::child_span_info;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use tracing::Instrument;
 
+static COUNTER_DEALS_PG_MISSED_BIDS: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:demand:deals")
+        .u64_counter("deals.pg_missed_bids")
+        .with_description("Guaranteed deal callouts the bidder didn't bid through the deal on")
+        .with_unit("1")
+        .build()
+});
+
 /// Records auction-phase bid counts for deals.
 ///
 /// Iterates the unified `bidders` list after merge and counts a bid for every
-/// bid response (direct or RTB) that has a deal attributed to it. Impressions
-/// and spend are recorded separately in the billing pipeline when the billing
-/// event fires.
+/// bid response (direct or RTB) that has a deal attributed to it. Callouts
+/// offering a guaranteed deal are counted as PG requests, missed when the
+/// bidder didn't bid through the deal. Impressions and spend are recorded
+/// separately in the billing pipeline when the billing event fires.
 pub struct DealBidCountersTask {
     store: Arc<DealCounterStore>,
+    deal_manager: Arc<DealManager>,
 }
 
 impl DealBidCountersTask {
-    pub fn new(store: Arc<DealCounterStore>) -> Self {
-        Self { store }
+    pub fn new(store: Arc<DealCounterStore>, deal_manager: Arc<DealManager>) -> Self {
+        Self {
+            store,
+            deal_manager,
+        }
+    }
+
+    /// Counts a PG request for every guaranteed deal offered on the
+    /// callout, missed if the bidder didn't bid through it
+    fn record_pg_requests(&self, bidder_id: &str, callout: &BidderCallout, bid_deals: &[&str]) {
+        let offered = callout
+            .req
+            .imp
+            .iter()
+            .filter_map(|imp| imp.pmp.as_ref())
+            .flat_map(|pmp| pmp.deals.iter());
+
+        for rtb_deal in offered {
+            let guaranteed = self
+                .deal_manager
+                .get(&rtb_deal.id)
+                .is_some_and(|deal| deal.guaranteed);
+
+            if !guaranteed {
+                continue;
+            }
+
+            let bid = bid_deals.contains(&rtb_deal.id.as_str());
+
+            let mut counters = DealCounters::default();
+            counters.pg_request(bid);
+            self.store.merge(&rtb_deal.id, &counters);
+
+            if !bid {
+                COUNTER_DEALS_PG_MISSED_BIDS.add(
+                    1,
+                    &[
+                        KeyValue::new("deal_id", rtb_deal.id.clone()),
+                        KeyValue::new("bidder_id", bidder_id.to_owned()),
+                    ],
+                );
+            }
+        }
     }
 
     async fn run0(&self, ctx: &AuctionContext) -> Result<(), Error> {
@@ -32,23 +87,35 @@ impl DealBidCountersTask {
                     Some(r) => r,
                     None => continue,
                 };
-                let bid_response = match &response.state {
-                    BidderResponseState::Bid(br) => br,
-                    _ => continue,
-                };
 
-                for seat in &bid_response.seatbids {
-                    for bid_ctx in &seat.bids {
-                        let deal = match bid_ctx.deal.get() {
-                            Some(d) => d,
-                            None => continue,
-                        };
+                let mut bid_deals: Vec<&str> = Vec::new();
 
-                        let mut counters = DealCounters::default();
-                        counters.bid();
-                        self.store.merge(&deal.id, &counters);
+                if let BidderResponseState::Bid(bid_response) = &response.state {
+                    for seat in &bid_response.seatbids {
+                        for bid_ctx in &seat.bids {
+                            let deal = match bid_ctx.deal.get() {
+                                Some(d) => d,
+                                None => continue,
+                            };
+
+                            let mut counters = DealCounters::default();
+                            counters.bid();
+
+                            if bid_ctx
+                                .filter_reason
+                                .as_ref()
+                                .is_some_and(|(code, _)| lossreasons::is_deal_price_mismatch(*code))
+                            {
+                                counters.price_rejection();
+                            }
+
+                            self.store.merge(&deal.id, &counters);
+                            bid_deals.push(&deal.id);
+                        }
                     }
                 }
+
+                self.record_pg_requests(&bidder_ctx.bidder.id, callout, &bid_deals);
             }
         }
 
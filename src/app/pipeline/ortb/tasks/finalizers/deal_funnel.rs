@@ -0,0 +1,161 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::BidderResponseState;
+use crate::core::firestore::counters::deal::DealCounterStore;
+use anyhow::Error;
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use rtb::BidResponse;
+use rtb::child_span_info;
+use rtb::common::bidresponsestate::BidResponseState;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::Instrument;
+
+/// The (seat, bid id) of the highest priced bid per imp
+/// in the final response, i.e. the exchange auction winners
+fn auction_winners(res: &BidResponse) -> HashSet<(&str, &str)> {
+    let mut top: HashMap<&str, (f64, &str, &str)> = HashMap::new();
+
+    for seat in &res.seatbid {
+        for bid in &seat.bid {
+            let entry = top.entry(bid.impid.as_str()).or_insert((
+                bid.price,
+                seat.seat.as_str(),
+                bid.id.as_str(),
+            ));
+
+            if bid.price > entry.0 {
+                *entry = (bid.price, seat.seat.as_str(), bid.id.as_str());
+            }
+        }
+    }
+
+    top.into_values()
+        .map(|(_, seat, bid_id)| (seat, bid_id))
+        .collect()
+}
+
+/// Completes the per auction deal funnel and merges it into the
+/// deal funnel store. Eligible and paced out stages are counted
+/// during deal matching, this adds requests sent with each deal
+/// in pmp, bids through the deal, filtered bids by reason, and
+/// exchange auction wins. Billed impressions are recorded by the
+/// billing pipeline.
+pub struct DealFunnelTask {
+    store: Arc<DealCounterStore>,
+}
+
+impl DealFunnelTask {
+    pub fn new(store: Arc<DealCounterStore>) -> Self {
+        Self { store }
+    }
+
+    async fn run0(&self, ctx: &AuctionContext) -> Result<(), Error> {
+        let funnel = &ctx.deal_funnel;
+        let bidders = ctx.bidders.lock().await;
+
+        let winners = match ctx.res.get() {
+            Some(BidResponseState::Bid(res)) => auction_winners(res),
+            _ => HashSet::new(),
+        };
+
+        for bidder_ctx in bidders.iter() {
+            let bidder_id = bidder_ctx.bidder.id.as_str();
+
+            for callout in &bidder_ctx.callouts {
+                let Some(response) = callout.response.get() else {
+                    continue;
+                };
+
+                let offered = callout
+                    .req
+                    .imp
+                    .iter()
+                    .filter_map(|imp| imp.pmp.as_ref())
+                    .flat_map(|pmp| pmp.deals.iter());
+
+                for rtb_deal in offered {
+                    funnel.record(&rtb_deal.id, bidder_id, |c| c.request());
+                }
+
+                let BidderResponseState::Bid(bid_response) = &response.state else {
+                    continue;
+                };
+
+                for seat in &bid_response.seatbids {
+                    for bid_ctx in &seat.bids {
+                        let Some(deal) = bid_ctx.deal.get() else {
+                            continue;
+                        };
+
+                        let won = winners.contains(&(bidder_id, bid_ctx.bid.id.as_str()));
+
+                        funnel.record(&deal.id, bidder_id, |c| {
+                            c.bid();
+
+                            if let Some((code, _)) = &bid_ctx.filter_reason {
+                                c.filtered(*code);
+                            } else if won {
+                                c.won();
+                            }
+                        });
+                    }
+                }
+            }
+        }
+
+        for ((deal_id, bidder_id), counters) in funnel.take() {
+            self.store.merge_funnel(&deal_id, &bidder_id, &counters);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for DealFunnelTask {
+    async fn run(&self, ctx: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("deal_funnel_task");
+        self.run0(ctx).instrument(span).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rtb::bid_response::{Bid, SeatBid};
+
+    fn bid(id: &str, impid: &str, price: f64) -> Bid {
+        Bid {
+            id: id.to_string(),
+            impid: impid.to_string(),
+            price,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn winners_are_top_bid_per_imp() {
+        let res = BidResponse {
+            seatbid: vec![
+                SeatBid {
+                    seat: "a".to_string(),
+                    bid: vec![bid("a1", "imp1", 2.0), bid("a2", "imp2", 1.0)],
+                    ..Default::default()
+                },
+                SeatBid {
+                    seat: "b".to_string(),
+                    bid: vec![bid("b1", "imp1", 3.0)],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let winners = auction_winners(&res);
+
+        assert_eq!(winners.len(), 2);
+        assert!(winners.contains(&("b", "b1")));
+        assert!(winners.contains(&("a", "a2")));
+    }
+}
@@ -33,6 +33,7 @@ fn build_endpoint_counters(bidder_callout: &BidderCallout) -> Result<DemandCount
                 match &response.state {
                     BidderResponseState::Timeout => counters.timeout(),
                     BidderResponseState::Error(_) => counters.error(),
+                    BidderResponseState::AdapterError(_) => counters.error(),
                     BidderResponseState::Unknown(_, _) => counters.error(),
                     BidderResponseState::NoBid(_) => {}
                     BidderResponseState::Bid(bid_ctx) => {
@@ -53,6 +54,7 @@ fn build_endpoint_counters(bidder_callout: &BidderCallout) -> Result<DemandCount
             CalloutSkipReason::TrafficShaping => counters.request_shaping_blocked(),
             CalloutSkipReason::QpsLimit => counters.request_qps_limited(),
             CalloutSkipReason::EndpointRotation => {}
+            CalloutSkipReason::CircuitOpen => counters.request_circuit_open(),
         },
     }
 
@@ -94,6 +96,7 @@ impl DemandCountersTask {
             let mut bidder_bids_filtered = 0;
             let mut bidder_timeouts = 0;
             let mut bidder_errors = 0;
+            let mut bidder_circuit_open = 0;
 
             for bidder_callout in bidder_context.callouts.iter() {
                 if let Some(skip_reason) = bidder_callout.skip_reason.get() {
@@ -101,6 +104,7 @@ impl DemandCountersTask {
                         CalloutSkipReason::TrafficShaping => "traffic_shaping",
                         CalloutSkipReason::QpsLimit => "qps_limit",
                         CalloutSkipReason::EndpointRotation => "endpoint_rotation",
+                        CalloutSkipReason::CircuitOpen => "circuit_open",
                     };
 
                     COUNTER_CALLOUT_SKIP.add(
@@ -132,6 +136,7 @@ impl DemandCountersTask {
                 bidder_bids_filtered += counters.bids_filtered;
                 bidder_timeouts += counters.timeouts;
                 bidder_errors += counters.errors;
+                bidder_circuit_open += counters.requests_circuit_open;
             }
 
             let bidder_counters = DemandCounters {
@@ -141,6 +146,7 @@ impl DemandCountersTask {
                 bids_filtered: bidder_bids_filtered,
                 timeouts: bidder_timeouts,
                 errors: bidder_errors,
+                requests_circuit_open: bidder_circuit_open,
                 ..Default::default()
             };
 
@@ -1,11 +1,13 @@
 mod bid_counters;
 mod campaign_counters;
 mod deal_counters;
+mod deal_funnel;
 mod demand_counters;
 mod pub_counters;
 
 pub use bid_counters::AuctionBidCountersTask;
 pub use campaign_counters::CampaignCountersTask;
 pub use deal_counters::DealBidCountersTask;
+pub use deal_funnel::DealFunnelTask;
 pub use demand_counters::DemandCountersTask;
 pub use pub_counters::PubCountersTask;
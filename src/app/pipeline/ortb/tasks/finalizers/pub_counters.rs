@@ -1,5 +1,7 @@
 use crate::app::pipeline::ortb::AuctionContext;
-use crate::app::pipeline::ortb::context::{BidderContext, BidderResponseState, IdentityContext};
+use crate::app::pipeline::ortb::context::{
+    BidderContext, BidderResponseState, IdentityContext, PublisherBlockReason,
+};
 use crate::core::firestore::counters::publisher::{PublisherCounterStore, PublisherCounters};
 use crate::core::models::creative::CreativeFormat;
 use crate::core::spec::{Channel, StatsDeviceType};
@@ -49,7 +51,17 @@ fn identity_app_has_ifa(device: Option<&Device>) -> bool {
     device.map_or(false, |d| !d.ifa.is_empty())
 }
 
+fn identity_has_eids(identity: Option<&IdentityContext>) -> bool {
+    identity
+        .and_then(|i| i.eids.get())
+        .is_some_and(|eids| !eids.is_empty())
+}
+
 fn has_cookie_or_maid(request: &BidRequest, identity: Option<&IdentityContext>) -> bool {
+    if identity_has_eids(identity) {
+        return true;
+    }
+
     match request.distributionchannel_oneof.as_ref() {
         Some(DistributionchannelOneof::Site(_)) => identity_has_cookie(identity),
         Some(DistributionchannelOneof::App(_)) => identity_app_has_ifa(request.device.as_ref()),
@@ -221,7 +233,7 @@ impl PubCountersTask {
             let reason = context
                 .block_reason
                 .get()
-                .map(|r| r.to_string())
+                .map(PublisherBlockReason::tag)
                 .unwrap_or_else(|| "unknown".to_string());
 
             COUNTER_REQUEST_BLOCKED.add(
@@ -0,0 +1,104 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::BidderResponseState;
+use crate::core::currency::{CurrencyService, normalize_code};
+use anyhow::Error;
+use async_trait::async_trait;
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::{Arc, LazyLock};
+use tracing::{Instrument, debug};
+
+static COUNTER_BIDS_CONVERTED: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:demand:callouts")
+        .u64_counter("callouts.bids.currency")
+        .with_description("Bids converted from a non accounting currency")
+        .with_unit("1")
+        .build()
+});
+
+/// Runs after BidderCalloutsTask. Converts bids priced in any currency
+/// other than the accounting currency, so margin, floors and settlement
+/// only ever compare like for like. Bids in an unknown currency are filtered.
+/// Without configured rates bids are left as is
+pub struct BidCurrencyTask {
+    currency: Arc<CurrencyService>,
+}
+
+impl BidCurrencyTask {
+    pub fn new(currency: Arc<CurrencyService>) -> Self {
+        Self { currency }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let accounting = self.currency.accounting();
+        let rates = self.currency.rates();
+
+        if rates.passthrough {
+            return Ok(());
+        }
+
+        let mut bidders = context.bidders.lock().await;
+
+        for bidder_context in bidders.iter_mut() {
+            for callout in bidder_context.callouts.iter_mut() {
+                let Some(response) = callout.response.get_mut() else {
+                    continue;
+                };
+
+                let BidderResponseState::Bid(bid_response) = &mut response.state else {
+                    continue;
+                };
+
+                let bid_currency = normalize_code(&bid_response.response.cur);
+                if bid_currency == accounting {
+                    continue;
+                }
+
+                for seat_context in bid_response.seatbids.iter_mut() {
+                    for bid_context in seat_context.bids.iter_mut() {
+                        let Some(price) =
+                            rates.convert(bid_context.bid.price, &bid_currency, accounting)
+                        else {
+                            bid_context.filter_reason.replace((
+                                rtb::spec::openrtb::lossreason::INVALID_BID_RESPONSE,
+                                format!("Unsupported bid currency {}", bid_currency),
+                            ));
+                            continue;
+                        };
+
+                        debug!(
+                            "Converted bid {} {} -> {} {}",
+                            bid_context.bid.price, bid_currency, price, accounting
+                        );
+
+                        bid_context.bid.price = price;
+                        bid_context.original_bid_price = price;
+
+                        COUNTER_BIDS_CONVERTED.add(
+                            1,
+                            &[
+                                KeyValue::new("bidder", bidder_context.bidder.name.clone()),
+                                KeyValue::new("currency", bid_currency.clone()),
+                            ],
+                        );
+                    }
+                }
+
+                bid_response.response.cur = accounting.to_string();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for BidCurrencyTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("bid_currency_task");
+
+        self.run0(context).instrument(span).await
+    }
+}
@@ -0,0 +1,65 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::core::demand::adapters::AdapterRegistry;
+use anyhow::Error;
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+use tracing::{Instrument, debug, warn};
+
+/// Runs each bidder's selected ['BidderAdapter'] request hook over
+/// its callout requests. Runs last before callouts so adapters see
+/// the final request, e.g. after margins and buyeruid injection
+pub struct BidderAdapterTask {
+    registry: Arc<AdapterRegistry>,
+}
+
+impl BidderAdapterTask {
+    pub fn new(registry: Arc<AdapterRegistry>) -> Self {
+        Self { registry }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let mut bidders = context.bidders.lock().await;
+
+        for bidder_context in bidders.iter_mut() {
+            let bidder = bidder_context.bidder.clone();
+            let adapter = self.registry.adapter_for(&bidder);
+
+            for callout in bidder_context.callouts.iter_mut() {
+                if callout.skip_reason.get().is_some() {
+                    continue;
+                }
+
+                debug!(
+                    "Adapting request to {} with adapter {}",
+                    callout.endpoint.name,
+                    adapter.name()
+                );
+
+                // a failed adaptation still sends the request as the
+                // adapter left it, rather than dropping the callout
+                if let Err(e) = adapter.adapt_request(&bidder, &callout.endpoint, &mut callout.req)
+                {
+                    warn!(
+                        "Adapter {} failed on request to {}: {}",
+                        adapter.name(),
+                        callout.endpoint.name,
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for BidderAdapterTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("bidder_adapter_task");
+
+        self.run0(context).instrument(span).await
+    }
+}
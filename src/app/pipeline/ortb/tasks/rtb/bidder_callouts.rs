@@ -2,7 +2,11 @@ use crate::app::pipeline::ortb::AuctionContext;
 use crate::app::pipeline::ortb::context::{
     BidResponseContext, BidderCallout, BidderContext, BidderResponse, BidderResponseState,
 };
+use crate::core::demand::adapters::{AdapterRegistry, BidderAdapter};
 use crate::core::demand::client::{DemandClient, DemandResponse};
+use crate::core::demand::health::{CalloutOutcome, EndpointHealthTracker};
+use crate::core::demand::latency::LatencyTracker;
+use crate::core::models::bidder::Bidder;
 use anyhow::Error;
 use async_trait::async_trait;
 use futures_util::future::join_all;
@@ -11,7 +15,7 @@ use opentelemetry::{KeyValue, global};
 use pipeline::AsyncTask;
 use reqwest::StatusCode;
 use rtb::{BidResponse, child_span_info};
-use std::sync::{LazyLock, OnceLock};
+use std::sync::{Arc, LazyLock, OnceLock};
 use std::time::{Duration, Instant};
 use tokio::time::timeout;
 use tracing::{Instrument, debug, warn};
@@ -166,8 +170,32 @@ async fn record_bids(
     }
 }
 
+fn record_adapter_error(
+    context: &BidderCallout,
+    context_response: &OnceLock<BidderResponse>,
+    adapter: &dyn BidderAdapter,
+    e: Error,
+    start: &Instant,
+) {
+    let set = context_response.set(BidderResponse {
+        latency: start.elapsed(),
+        state: BidderResponseState::AdapterError(format!(
+            "Adapter {} failed on response from {}: {}",
+            adapter.name(),
+            context.endpoint.url,
+            e
+        )),
+    });
+
+    if let Err(_) = set {
+        warn!("Tried to assign adapter error condition but response state exists");
+    }
+}
+
 async fn record_bid_response_state(
     context: &BidderCallout,
+    bidder: &Bidder,
+    adapter: Arc<dyn BidderAdapter>,
     callout_result: impl Future<Output = Result<DemandResponse, Error>>,
 ) {
     let start = Instant::now();
@@ -193,7 +221,11 @@ async fn record_bid_response_state(
         return warn!("Received 200 but empty body from {}", context.endpoint.name);
     }
 
-    let bid_response = res.response.unwrap();
+    let mut bid_response = res.response.unwrap();
+
+    if let Err(e) = adapter.adapt_response(bidder, &context.endpoint, &mut bid_response) {
+        return record_adapter_error(context, context_response, adapter.as_ref(), e, &start);
+    }
 
     if bid_response.nbr > 0 || bid_response.seatbid.is_empty() {
         return record_200_nobid(context, context_response, &bid_response, &start).await;
@@ -270,11 +302,24 @@ fn record_counter_metrics(context: &AuctionContext, bidders: &Vec<BidderContext>
 
 pub struct BidderCalloutsTask {
     client: DemandClient,
+    latency: Arc<LatencyTracker>,
+    health: Arc<EndpointHealthTracker>,
+    adapters: Arc<AdapterRegistry>,
 }
 
 impl BidderCalloutsTask {
-    pub fn new(client: DemandClient) -> Self {
-        Self { client }
+    pub fn new(
+        client: DemandClient,
+        latency: Arc<LatencyTracker>,
+        health: Arc<EndpointHealthTracker>,
+        adapters: Arc<AdapterRegistry>,
+    ) -> Self {
+        Self {
+            client,
+            latency,
+            health,
+            adapters,
+        }
     }
 
     fn send_bidder_callouts(&self, bidders: &Vec<BidderContext>) -> Vec<impl Future<Output = ()>> {
@@ -288,6 +333,9 @@ impl BidderCalloutsTask {
                 continue;
             }
 
+            let bidder = &bidder_context.bidder;
+            let adapter = self.adapters.adapter_for(bidder);
+
             for callout in callouts.iter() {
                 if let Some(skip_reason) = callout.skip_reason.get() {
                     debug!(
@@ -305,7 +353,17 @@ impl BidderCalloutsTask {
                     &callout.req,
                 );
 
-                let handled_fut = record_bid_response_state(callout, res_fut);
+                // each endpoint is held only to its own resolved tmax, so
+                // the auction closes as soon as every endpoint is done
+                let deadline = Duration::from_millis(callout.req.tmax.max(0) as u64);
+                let adapter = adapter.clone();
+                let handled_fut = async move {
+                    let recorded = record_bid_response_state(callout, bidder, adapter, res_fut);
+
+                    if timeout(deadline, recorded).await.is_err() {
+                        debug!("Callout to {} hit its own tmax", callout.endpoint.name);
+                    }
+                };
 
                 futs.push(handled_fut);
             }
@@ -323,7 +381,7 @@ impl BidderCalloutsTask {
 
                 if callout.response.get().is_none() {
                     let br = BidderResponse {
-                        latency: Duration::from_secs(1),
+                        latency: Duration::from_millis(callout.req.tmax.max(0) as u64),
                         state: BidderResponseState::Timeout,
                     };
 
@@ -346,6 +404,53 @@ impl BidderCalloutsTask {
         }
     }
 
+    /// Feed observed latencies back for adaptive endpoint tmax. Errors
+    /// are skipped as they are usually immediate connection failures
+    fn record_latencies(&self, bidders: &Vec<BidderContext>) {
+        for bidder in bidders.iter() {
+            for callout in bidder.callouts.iter() {
+                if callout.skip_reason.get().is_some() {
+                    continue;
+                }
+
+                match callout.response.get() {
+                    Some(BidderResponse {
+                        state: BidderResponseState::Error(_),
+                        ..
+                    })
+                    | None => continue,
+                    Some(response) => self
+                        .latency
+                        .record(callout.endpoint.stable_id(), response.latency),
+                }
+            }
+        }
+    }
+
+    /// Feed callout outcomes to the endpoint circuit breakers. Adapter
+    /// failures are our own, the endpoint responded so counts healthy
+    fn record_health(&self, bidders: &Vec<BidderContext>) {
+        for bidder in bidders.iter() {
+            for callout in bidder.callouts.iter() {
+                if callout.skip_reason.get().is_some() {
+                    continue;
+                }
+
+                let outcome = match callout.response.get().map(|r| &r.state) {
+                    Some(BidderResponseState::Timeout) => CalloutOutcome::Timeout,
+                    Some(BidderResponseState::Error(_)) => CalloutOutcome::Error,
+                    Some(BidderResponseState::Unknown(status, _)) if *status >= 500 => {
+                        CalloutOutcome::Error
+                    }
+                    Some(_) => CalloutOutcome::Healthy,
+                    None => continue,
+                };
+
+                self.health.record(callout.endpoint.stable_id(), outcome);
+            }
+        }
+    }
+
     async fn send_all(&self, context: &AuctionContext) -> Result<(), Error> {
         let bidders = context.bidders.lock().await;
 
@@ -366,6 +471,10 @@ impl BidderCalloutsTask {
 
         self.record_timeouts(&bidders);
 
+        self.record_latencies(&bidders);
+
+        self.record_health(&bidders);
+
         record_counter_metrics(context, &bidders);
 
         Ok(())
@@ -88,6 +88,12 @@ fn matches_endpoint(
         return false;
     }
 
+    if !targeting.geo.check(Some(geo)) {
+        span.record("bidder_endpoint_filter_reason", "geo_sub_country");
+
+        return false;
+    }
+
     let format_match = req.imp.iter().any(|imp| {
         let f = &targeting.formats;
 
@@ -217,16 +223,34 @@ fn get_filtered_matching(
 ///
 /// Returns (matched_deals, is_private_auction).
 fn eval_deals_for_imp(
+    bidder_id: &str,
     bidder_deals: &BidderDeals,
     deal_pacer: &dyn DealPacer,
     ctx: &AuctionContext,
     imp: &rtb::bid_request::Imp,
 ) -> (Vec<Arc<Deal>>, bool) {
+    let deal_passes = |d: &&Arc<Deal>| {
+        if !matches_targeting(&d.targeting.common, ctx, imp) {
+            return false;
+        }
+
+        let passes = deal_pacer.passes(d);
+
+        ctx.deal_funnel.record(&d.id, bidder_id, |c| {
+            c.eligible();
+            if !passes {
+                c.paced_out();
+            }
+        });
+
+        passes
+    };
+
     // Check private deals first
     let private_matches: Vec<Arc<Deal>> = bidder_deals
         .private
         .iter()
-        .filter(|d| matches_targeting(&d.targeting.common, ctx, imp) && deal_pacer.passes(d))
+        .filter(deal_passes)
         .cloned()
         .collect();
 
@@ -238,7 +262,7 @@ fn eval_deals_for_imp(
     let open_matches: Vec<Arc<Deal>> = bidder_deals
         .open
         .iter()
-        .filter(|d| matches_targeting(&d.targeting.common, ctx, imp) && deal_pacer.passes(d))
+        .filter(deal_passes)
         .cloned()
         .collect();
 
@@ -267,6 +291,7 @@ fn inject_pmp(imp: &mut rtb::bid_request::Imp, deals: &[Arc<Deal>], is_private:
             RtbDeal {
                 id: d.id.clone(),
                 bidfloor,
+                bidfloorcur: imp.bidfloorcur.clone(),
                 at,
                 wseat,
                 ..Default::default()
@@ -343,10 +368,14 @@ impl BidderMatchingTask {
                 let mut req = context.req.read().clone();
                 let mut any_imp_has_deal = false;
 
+                if let Some(floors) = context.floors.get() {
+                    floors.apply(&mut req);
+                }
+
                 if let Some(ref deals) = bidder_deals {
                     for imp in req.imp.iter_mut() {
                         let (matched_deals, is_private) =
-                            eval_deals_for_imp(deals, &*self.deal_pacer, context, imp);
+                            eval_deals_for_imp(&bidder.id, deals, &*self.deal_pacer, context, imp);
                         if !matched_deals.is_empty() {
                             rtb_deals_injected_count += matched_deals.len() as u64;
                             inject_pmp(imp, &matched_deals, is_private);
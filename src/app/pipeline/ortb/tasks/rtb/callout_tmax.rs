@@ -0,0 +1,74 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::core::demand::latency::LatencyTracker;
+use anyhow::Error;
+use async_trait::async_trait;
+use opentelemetry::metrics::Histogram;
+use opentelemetry::{KeyValue, global};
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::{Arc, LazyLock};
+use tracing::{Instrument, debug};
+
+static HIST_CALLOUT_TMAX: LazyLock<Histogram<u64>> = LazyLock::new(|| {
+    global::meter("rex:demand:callouts")
+        .u64_histogram("callouts.tmax")
+        .with_description("Resolved outbound tmax per endpoint callout")
+        .with_unit("ms")
+        .build()
+});
+
+/// Resolves each callout's deadline from its endpoint ['EndpointTimeout']
+/// policy and writes it as the outbound tmax, which the callouts task
+/// then enforces per callout. Never exceeds the (offset) auction tmax
+pub struct CalloutTmaxTask {
+    latency: Arc<LatencyTracker>,
+}
+
+impl CalloutTmaxTask {
+    pub fn new(latency: Arc<LatencyTracker>) -> Self {
+        Self { latency }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let auction_tmax = context.req.read().tmax.max(0) as u32;
+
+        let mut bidders = context.bidders.lock().await;
+
+        for bidder_context in bidders.iter_mut() {
+            for callout in bidder_context.callouts.iter_mut() {
+                if callout.skip_reason.get().is_some() {
+                    continue;
+                }
+
+                let p95_ms = self.latency.p95_ms(callout.endpoint.stable_id());
+                let tmax = callout.endpoint.timeout.resolve(auction_tmax, p95_ms);
+
+                debug!(
+                    "Endpoint {} callout tmax {}ms (auction {}ms, p95 {:?})",
+                    callout.endpoint.name, tmax, auction_tmax, p95_ms
+                );
+
+                callout.req.tmax = tmax as i32;
+
+                HIST_CALLOUT_TMAX.record(
+                    tmax as u64,
+                    &[
+                        KeyValue::new("bidder", bidder_context.bidder.name.clone()),
+                        KeyValue::new("endpoint", callout.endpoint.name.clone()),
+                    ],
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for CalloutTmaxTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("callout_tmax_task");
+
+        self.run0(context).instrument(span).await
+    }
+}
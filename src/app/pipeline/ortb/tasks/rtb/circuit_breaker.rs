@@ -0,0 +1,60 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::CalloutSkipReason;
+use crate::core::demand::health::EndpointHealthTracker;
+use anyhow::Error;
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+use tracing::{Instrument, debug, warn};
+
+/// Suppresses callouts to endpoints whose circuit breaker is open,
+/// letting only probe traffic through until they recover. Runs
+/// ahead of shaping and QPS limiting so suppressed callouts don't
+/// consume shaping decisions or QPS tokens
+pub struct CircuitBreakerTask {
+    health: Arc<EndpointHealthTracker>,
+}
+
+impl CircuitBreakerTask {
+    pub fn new(health: Arc<EndpointHealthTracker>) -> Self {
+        Self { health }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let bidders = context.bidders.lock().await;
+
+        for bidder_context in bidders.iter() {
+            for callout in bidder_context.callouts.iter() {
+                if callout.skip_reason.get().is_some() {
+                    continue;
+                }
+
+                if self.health.allow(callout.endpoint.stable_id()) {
+                    continue;
+                }
+
+                debug!(
+                    "Endpoint {} circuit open, suppressing callout",
+                    callout.endpoint.name
+                );
+
+                callout
+                    .skip_reason
+                    .set(CalloutSkipReason::CircuitOpen)
+                    .unwrap_or_else(|_| warn!("Failed assigning circuit open skip reason"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for CircuitBreakerTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("circuit_breaker_task");
+
+        self.run0(context).instrument(span).await
+    }
+}
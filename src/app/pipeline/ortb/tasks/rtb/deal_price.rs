@@ -0,0 +1,225 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::BidderResponseState;
+use crate::core::models::deal::DealPriceEnforcement;
+use crate::core::spec::lossreasons;
+use anyhow::Error;
+use async_trait::async_trait;
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+use pipeline::AsyncTask;
+use rtb::BidRequest;
+use rtb::bid_request::imp::pmp::Deal as RtbDeal;
+use rtb::child_span_info;
+use std::sync::LazyLock;
+use tracing::{Instrument, Span, debug};
+
+/// Bids within a cent CPM of the deal price are accepted as is
+const PRICE_TOLERANCE: f64 = 0.01;
+
+/// OpenRTB deal auction type for a fixed, agreed price
+const AT_FIXED_PRICE: i32 = 3;
+
+static COUNTER_DEALS_PRICE_REJECTED: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:demand:deals")
+        .u64_counter("deals.rtb_price_rejected")
+        .with_description("RTB deal bids rejected for not meeting the deal price")
+        .with_unit("1")
+        .build()
+});
+
+static COUNTER_DEALS_PRICE_CLAMPED: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:demand:deals")
+        .u64_counter("deals.rtb_price_clamped")
+        .with_description("RTB fixed price deal bids lowered to the fixed price")
+        .with_unit("1")
+        .build()
+});
+
+#[derive(Debug, PartialEq)]
+enum DealPriceCheck {
+    Accept,
+    Clamp(f64),
+    Reject(u32),
+}
+
+/// Checks a deal bid against the deal price offered to the bidder,
+/// which is already marked up by the deal margin
+fn check_deal_price(
+    price: f64,
+    offered: &RtbDeal,
+    enforcement: DealPriceEnforcement,
+) -> DealPriceCheck {
+    if price < offered.bidfloor - PRICE_TOLERANCE {
+        return DealPriceCheck::Reject(lossreasons::BID_BELOW_DEAL_FLOOR);
+    }
+
+    if offered.at != AT_FIXED_PRICE || price <= offered.bidfloor + PRICE_TOLERANCE {
+        return DealPriceCheck::Accept;
+    }
+
+    match enforcement {
+        DealPriceEnforcement::Reject => DealPriceCheck::Reject(lossreasons::BID_BELOW_DEAL_FLOOR),
+        DealPriceEnforcement::Clamp => DealPriceCheck::Clamp(offered.bidfloor),
+    }
+}
+
+fn offered_deal<'a>(req: &'a BidRequest, impid: &str, dealid: &str) -> Option<&'a RtbDeal> {
+    req.imp
+        .iter()
+        .find(|imp| imp.id == impid)
+        .and_then(|imp| imp.pmp.as_ref())
+        .and_then(|pmp| pmp.deals.iter().find(|d| d.id == dealid))
+}
+
+/// Runs after RtbDealAttributionTask. Enforces the deal price sent to
+/// the bidder on every attributed deal bid: bids below a deal floor or
+/// fixed price are rejected, and bids above a fixed price are rejected
+/// or clamped to it per the deal's [`DealPriceEnforcement`]. Bids for
+/// deals which weren't offered on the imp are left to attribution
+pub struct DealPriceEnforcementTask;
+
+impl DealPriceEnforcementTask {
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let mut bidders = context.bidders.lock().await;
+        let mut rejected: u64 = 0;
+        let mut clamped: u64 = 0;
+
+        for bidder_ctx in bidders.iter_mut() {
+            for callout in bidder_ctx.callouts.iter_mut() {
+                let Some(response) = callout.response.get_mut() else {
+                    continue;
+                };
+
+                let BidderResponseState::Bid(bid_response) = &mut response.state else {
+                    continue;
+                };
+
+                for seat_ctx in bid_response.seatbids.iter_mut() {
+                    for bid_ctx in seat_ctx.bids.iter_mut() {
+                        if bid_ctx.filter_reason.is_some() {
+                            continue;
+                        }
+
+                        let Some(deal) = bid_ctx.deal.get() else {
+                            continue;
+                        };
+
+                        let bid = &bid_ctx.bid;
+                        let Some(offered) = offered_deal(&callout.req, &bid.impid, &bid.dealid)
+                        else {
+                            continue;
+                        };
+
+                        match check_deal_price(bid.price, offered, deal.price_enforcement) {
+                            DealPriceCheck::Accept => {}
+                            DealPriceCheck::Clamp(price) => {
+                                debug!(
+                                    "Clamping deal {} bid ${} to fixed price ${}",
+                                    deal.id, bid.price, price
+                                );
+
+                                bid_ctx.bid.price = price;
+                                bid_ctx.original_bid_price = price;
+                                clamped += 1;
+                            }
+                            DealPriceCheck::Reject(code) => {
+                                let reason = format!(
+                                    "Deal {} bid ${} does not meet deal price ${}",
+                                    deal.id, bid.price, offered.bidfloor
+                                );
+
+                                debug!("{}", reason);
+
+                                bid_ctx.filter_reason.replace((code, reason));
+                                rejected += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let pub_id = context.publisher.id.clone();
+
+        if rejected > 0 {
+            COUNTER_DEALS_PRICE_REJECTED.add(rejected, &[KeyValue::new("pub_id", pub_id.clone())]);
+        }
+
+        if clamped > 0 {
+            COUNTER_DEALS_PRICE_CLAMPED.add(clamped, &[KeyValue::new("pub_id", pub_id)]);
+        }
+
+        let span = Span::current();
+        span.record("deals_price_rejected", rejected);
+        span.record("deals_price_clamped", clamped);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for DealPriceEnforcementTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!(
+            "deal_price_enforcement_task",
+            deals_price_rejected = tracing::field::Empty,
+            deals_price_clamped = tracing::field::Empty
+        );
+
+        self.run0(context).instrument(span).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offered(bidfloor: f64, at: i32) -> RtbDeal {
+        RtbDeal {
+            id: "d1".to_string(),
+            bidfloor,
+            at,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn floor_deal_rejects_below_floor_only() {
+        let deal = offered(5.0, 1);
+
+        assert_eq!(
+            check_deal_price(4.0, &deal, DealPriceEnforcement::Reject),
+            DealPriceCheck::Reject(lossreasons::BID_BELOW_DEAL_FLOOR)
+        );
+        assert_eq!(
+            check_deal_price(4.995, &deal, DealPriceEnforcement::Reject),
+            DealPriceCheck::Accept
+        );
+        assert_eq!(
+            check_deal_price(9.0, &deal, DealPriceEnforcement::Reject),
+            DealPriceCheck::Accept
+        );
+    }
+
+    #[test]
+    fn fixed_deal_rejects_or_clamps_above_price() {
+        let deal = offered(5.0, AT_FIXED_PRICE);
+
+        assert_eq!(
+            check_deal_price(5.0, &deal, DealPriceEnforcement::Reject),
+            DealPriceCheck::Accept
+        );
+        assert_eq!(
+            check_deal_price(6.0, &deal, DealPriceEnforcement::Reject),
+            DealPriceCheck::Reject(lossreasons::BID_BELOW_DEAL_FLOOR)
+        );
+        assert_eq!(
+            check_deal_price(6.0, &deal, DealPriceEnforcement::Clamp),
+            DealPriceCheck::Clamp(5.0)
+        );
+        assert_eq!(
+            check_deal_price(4.0, &deal, DealPriceEnforcement::Clamp),
+            DealPriceCheck::Reject(lossreasons::BID_BELOW_DEAL_FLOOR)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::BidderResponseState;
+use crate::core::demand::takerate;
+use crate::core::floors::FloorEngine;
+use anyhow::Error;
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+use tracing::Instrument;
+
+/// Feeds bids from exploration auctions back into the floor engine.
+/// Runs after BidCurrencyTask so prices are in accounting currency and
+/// after deal attribution, recording bids net of their effective margin
+/// and bid adjustment, the same terms floors are set in
+pub struct FloorLearningTask {
+    engine: Arc<FloorEngine>,
+}
+
+impl FloorLearningTask {
+    pub fn new(engine: Arc<FloorEngine>) -> Self {
+        Self { engine }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let Some(floors) = context.floors.get().filter(|f| f.explore) else {
+            return Ok(());
+        };
+
+        let publisher = &context.publisher;
+        let bidders = context.bidders.lock().await;
+
+        for bidder_context in bidders.iter() {
+            let bidder = &bidder_context.bidder;
+            let adjustment = bidder.adjustment_factor();
+
+            for callout in bidder_context.callouts.iter() {
+                let Some(BidderResponseState::Bid(bid_response)) =
+                    callout.response.get().map(|r| &r.state)
+                else {
+                    continue;
+                };
+
+                let bids = bid_response
+                    .seatbids
+                    .iter()
+                    .flat_map(|s| s.bids.iter())
+                    .filter(|b| b.filter_reason.is_none());
+
+                for bid_context in bids {
+                    if let Some(key) = floors.keys.get(&bid_context.bid.impid) {
+                        let margin = takerate::effective_margin(
+                            publisher,
+                            bidder,
+                            bid_context.deal.get().map(|d| d.as_ref()),
+                        );
+                        let net_price =
+                            takerate::net_bid(bid_context.original_bid_price, margin, adjustment);
+
+                        self.engine.record(key, net_price);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for FloorLearningTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("floor_learning_task");
+
+        self.run0(context).instrument(span).await
+    }
+}
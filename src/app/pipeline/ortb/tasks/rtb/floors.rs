@@ -0,0 +1,140 @@
+use crate::app::pipeline::ortb::AuctionContext;
+use crate::app::pipeline::ortb::context::AuctionFloors;
+use crate::core::currency::CurrencyService;
+use crate::core::floors::{FloorEngine, FloorKey, imp_format, static_floor};
+use anyhow::Error;
+use async_trait::async_trait;
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+use tracing::{Instrument, debug, warn};
+
+static COUNTER_FLOORS_RAISED: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:floors")
+        .u64_counter("floors.raised")
+        .with_description("Imp floors raised by a static rule or learned floor")
+        .with_unit("1")
+        .build()
+});
+
+/// Resolves the effective imp floors from the publisher's static floor
+/// rules and, outside of exploration auctions, the learned floor for the
+/// imp. The request keeps the publisher floors, bidder matching applies
+/// the effective floors to every callout before FloorsMarkupTask adds
+/// margin on top
+pub struct FloorsTask {
+    engine: Arc<FloorEngine>,
+    currency: Arc<CurrencyService>,
+}
+
+impl FloorsTask {
+    pub fn new(engine: Arc<FloorEngine>, currency: Arc<CurrencyService>) -> Self {
+        Self { engine, currency }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let publisher = &context.publisher;
+        let explore = self.engine.explore();
+
+        let devtype = context
+            .device
+            .get()
+            .map(|d| d.devtype.clone())
+            .unwrap_or_default();
+
+        let req = context.req.read();
+
+        let country = req
+            .device
+            .as_ref()
+            .and_then(|d| d.geo.as_ref())
+            .map(|g| g.country.to_ascii_uppercase())
+            .unwrap_or_default();
+
+        let mut keys = HashMap::with_capacity(req.imp.len());
+        let mut effective = HashMap::with_capacity(req.imp.len());
+
+        for imp in req.imp.iter() {
+            let key = FloorKey {
+                pub_id: publisher.id.clone(),
+                placement_id: context
+                    .placement
+                    .as_ref()
+                    .map(|p| p.id.clone())
+                    .unwrap_or_else(|| imp.tagid.clone()),
+                country: country.clone(),
+                devtype: devtype.clone(),
+                format: imp_format(imp),
+            };
+
+            let rule_floor = static_floor(&publisher.floor_rules, &key).and_then(|rule| {
+                let floor = self
+                    .currency
+                    .model_to_accounting(rule.floor, rule.currency.as_deref());
+
+                if floor.is_none() {
+                    warn!(
+                        "Ignoring floor rule for pub {}, no FX rate for {:?}",
+                        publisher.id, rule.currency
+                    );
+                }
+
+                floor
+            });
+
+            let learned_floor = match explore {
+                true => None,
+                false => self.engine.learned_floor(&key),
+            };
+
+            let mut imp_floor = imp.bidfloor;
+
+            for (source, floor) in [("static", rule_floor), ("learned", learned_floor)] {
+                let Some(floor) = floor.filter(|f| *f > imp_floor) else {
+                    continue;
+                };
+
+                debug!(
+                    "Raising imp {} floor ${} -> ${} from {} floor",
+                    imp.id, imp_floor, floor, source
+                );
+
+                imp_floor = floor;
+
+                COUNTER_FLOORS_RAISED.add(
+                    1,
+                    &[
+                        KeyValue::new("pub_id", publisher.id.clone()),
+                        KeyValue::new("source", source),
+                    ],
+                );
+            }
+
+            keys.insert(imp.id.clone(), key);
+            effective.insert(imp.id.clone(), imp_floor);
+        }
+
+        context
+            .floors
+            .set(AuctionFloors {
+                explore,
+                keys,
+                effective,
+            })
+            .unwrap_or_else(|_| warn!("Auction floors already set"));
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for FloorsTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("floors_task");
+
+        self.run0(context).instrument(span).await
+    }
+}
@@ -1,56 +1,95 @@
 use crate::app::pipeline::ortb::AuctionContext;
 use crate::core::demand::takerate;
+use crate::core::managers::DealManager;
+use crate::core::models::bidder::Bidder;
+use crate::core::models::deal::Deal;
+use crate::core::models::publisher::Publisher;
 use anyhow::Error;
 use async_trait::async_trait;
 use pipeline::AsyncTask;
-use rtb::child_span_info;
+use rtb::{BidRequest, child_span_info};
+use std::sync::Arc;
 use tracing::{Instrument, debug};
 
 pub const MIN_FLOOR: f64 = 0.25;
 
-pub struct FloorsMarkupTask;
-
-impl FloorsMarkupTask {
-    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
-        let publisher = &context.publisher;
-
-        let mut req = context.req.write();
+/// Applies the minimum floor and margin markup to each callout
+/// request, after static and learned floors have been set by
+/// FloorsTask and deals injected by bidder matching. Floors are
+/// marked up by the bidder's effective margin and bid adjustment,
+/// and deal floors by the deal's margin override if it has one
+pub struct FloorsMarkupTask {
+    deal_manager: Arc<DealManager>,
+}
 
-        for imp in req.imp.iter_mut() {
-            if imp.bidfloor < MIN_FLOOR {
-                debug!(
-                    "Applying min floor to imp &{} -> &{}",
-                    imp.bidfloor, MIN_FLOOR
-                );
-                imp.bidfloor = MIN_FLOOR;
-                continue;
-            }
+fn markup_request(
+    req: &mut BidRequest,
+    publisher: &Publisher,
+    bidder: &Bidder,
+    find_deal: impl Fn(&str) -> Option<Arc<Deal>>,
+) {
+    let margin = takerate::effective_margin(publisher, bidder, None);
+    let adjustment = bidder.adjustment_factor();
 
-            let new_imp_floor = takerate::markup_floor(imp.bidfloor, publisher.margin);
+    for imp in req.imp.iter_mut() {
+        let new_imp_floor = if imp.bidfloor < MIN_FLOOR {
+            debug!(
+                "Applying min floor to imp &{} -> &{}",
+                imp.bidfloor, MIN_FLOOR
+            );
+            MIN_FLOOR
+        } else {
+            let new_imp_floor = takerate::bidder_floor(imp.bidfloor, margin, adjustment);
             debug!(
                 "Marking up imp floor from ${} -> ${}",
                 imp.bidfloor, new_imp_floor
             );
+            new_imp_floor
+        };
+
+        imp.bidfloor = new_imp_floor;
 
-            imp.bidfloor = new_imp_floor;
+        let pmp = match &mut imp.pmp {
+            Some(pmp) => pmp,
+            None => continue,
+        };
 
-            let pmp = match &mut imp.pmp {
-                Some(pmp) => pmp,
-                None => continue,
+        for deal in pmp.deals.iter_mut() {
+            // we need to markup deal floors, too, even on unfloored
+            // imps or the margin comes out of fixed price deals,
+            // and ensure they meet or exceed the imp floor
+            let deal_margin = match find_deal(&deal.id) {
+                Some(d) => takerate::effective_margin(publisher, bidder, Some(&d)),
+                None => margin,
             };
 
-            for deal in pmp.deals.iter_mut() {
-                // we need to markup deal floors, too
-                // and ensure they meet or exceed the imp floor
-                let min_deal_floor = takerate::markup_floor(deal.bidfloor, publisher.margin);
-                let new_deal_floor = min_deal_floor.max(new_imp_floor);
+            let min_deal_floor = takerate::bidder_floor(deal.bidfloor, deal_margin, adjustment);
+            let new_deal_floor = min_deal_floor.max(new_imp_floor);
 
-                debug!(
-                    "Bringing deal floor ${} up to new imp floor ${}",
-                    deal.bidfloor, new_deal_floor
-                );
+            debug!(
+                "Bringing deal floor ${} up to new imp floor ${}",
+                deal.bidfloor, new_deal_floor
+            );
 
-                deal.bidfloor = new_deal_floor;
+            deal.bidfloor = new_deal_floor;
+        }
+    }
+}
+
+impl FloorsMarkupTask {
+    pub fn new(deal_manager: Arc<DealManager>) -> Self {
+        Self { deal_manager }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let publisher = &context.publisher;
+        let mut bidders = context.bidders.lock().await;
+
+        for bidder_context in bidders.iter_mut() {
+            for callout in bidder_context.callouts.iter_mut() {
+                markup_request(&mut callout.req, publisher, &bidder_context.bidder, |id| {
+                    self.deal_manager.get(id)
+                });
             }
         }
 
@@ -66,3 +105,49 @@ impl AsyncTask<AuctionContext, Error> for FloorsMarkupTask {
         self.run0(context).instrument(span).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::deal::DealPricing;
+    use rtb::bid_request::{Deal as RtbDeal, Imp, Pmp};
+
+    #[test]
+    fn unfloored_imp_marks_up_fixed_deal_floor() {
+        let publisher = Publisher {
+            margin: 10,
+            ..Default::default()
+        };
+        let bidder = Bidder::default();
+        let deal = Arc::new(Deal {
+            pricing: DealPricing::Fixed(8.0),
+            margin: Some(20),
+            ..Deal::stub("d1")
+        });
+
+        let mut req = BidRequest {
+            imp: vec![Imp {
+                bidfloor: 0.0,
+                pmp: Some(Pmp {
+                    deals: vec![RtbDeal {
+                        id: "d1".to_string(),
+                        bidfloor: 8.0,
+                        at: 3,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        markup_request(&mut req, &publisher, &bidder, |id| {
+            (id == "d1").then(|| deal.clone())
+        });
+
+        let imp = &req.imp[0];
+        assert_eq!(imp.bidfloor, MIN_FLOOR);
+        assert_eq!(imp.pmp.as_ref().unwrap().deals[0].bidfloor, 10.0);
+    }
+}
@@ -1,12 +1,16 @@
 use crate::app::pipeline::ortb::AuctionContext;
 use crate::app::pipeline::ortb::context::{BidderContext, IdentityContext};
+use crate::core::models::bidder::Bidder;
 use crate::core::models::publisher::Publisher;
+use crate::core::usersync;
 use crate::core::usersync::SyncStore;
 use anyhow::Error;
 use async_trait::async_trait;
 use opentelemetry::metrics::Counter;
 use opentelemetry::{KeyValue, global};
 use pipeline::AsyncTask;
+use rtb::bid_request::User;
+use rtb::bid_request::user::Eid;
 use rtb::child_span_info;
 use std::sync::{Arc, LazyLock};
 use tracing::{Instrument, Span, debug, trace, warn};
@@ -19,7 +23,23 @@ static COUNTER_BUYERUID_MATCHES: LazyLock<Counter<u64>> = LazyLock::new(|| {
         .build()
 });
 
-/// Responsible for injecting buyeruid values into bidder specific callouts
+static COUNTER_EID_INJECTIONS: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    global::meter("rex:demand:syncing")
+        .u64_counter("syncing.demand_eid_injections")
+        .with_description("Count of bidders sent user eids, by bidder")
+        .with_unit("1")
+        .build()
+});
+
+/// The eids whose source the bidder allows
+fn bidder_eids(eids: &[Eid], bidder: &Bidder) -> Vec<Eid> {
+    eids.iter()
+        .filter(|eid| bidder.allows_eid_source(&eid.source))
+        .cloned()
+        .collect()
+}
+
+/// Responsible for injecting buyeruid values and eids into bidder specific callouts
 pub struct IdentityDemandTask {
     store: Arc<dyn SyncStore>,
 }
@@ -114,18 +134,65 @@ impl IdentityDemandTask {
         }
     }
 
+    /// Injects the request eids into callouts of bidders allowing
+    /// their source, creating the user object where missing
+    fn inject_eids(identity: &IdentityContext, bidder_contexts: &mut [BidderContext]) {
+        let eids = match identity.eids.get() {
+            Some(eids) if !eids.is_empty() => eids,
+            _ => return,
+        };
+
+        for bidder_context in bidder_contexts.iter_mut() {
+            let allowed = bidder_eids(eids, &bidder_context.bidder);
+
+            if allowed.is_empty() {
+                continue;
+            }
+
+            COUNTER_EID_INJECTIONS.add(
+                1,
+                &[
+                    KeyValue::new("bidder_id", bidder_context.bidder.id.clone()),
+                    KeyValue::new("bidder_name", bidder_context.bidder.name.clone()),
+                ],
+            );
+
+            debug!(
+                "Injecting {} eids for bidder {} callouts",
+                allowed.len(),
+                bidder_context.bidder.name
+            );
+
+            for callout in bidder_context.callouts.iter_mut() {
+                callout
+                    .req
+                    .user
+                    .get_or_insert_with(User::default)
+                    .eids
+                    .extend(allowed.iter().cloned());
+            }
+        }
+    }
+
     async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
         let identity = match context.identity.get() {
             Some(identity) => identity,
             None => return Ok(()),
         };
 
+        if usersync::utils::is_opted_out(&context.http.cookies) {
+            debug!("User opted out, not injecting buyeruids or eids");
+            return Ok(());
+        }
+
         let mut bidders = context.bidders.lock().await;
         let publisher = &context.publisher;
 
         self.inject_buyer_uids(identity, &mut bidders, publisher)
             .await;
 
+        Self::inject_eids(identity, &mut bidders);
+
         Ok(())
     }
 }
@@ -138,3 +205,49 @@ impl AsyncTask<AuctionContext, Error> for IdentityDemandTask {
         self.run0(context).instrument(span).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::pipeline::ortb::context::BidderCallout;
+    use rtb::bid_request::user::eid::Uid;
+
+    fn bidder_context(id: &str, eid_sources: Option<Vec<String>>) -> BidderContext {
+        BidderContext {
+            bidder: Arc::new(Bidder {
+                id: id.to_string(),
+                eid_sources,
+                ..Default::default()
+            }),
+            callouts: vec![BidderCallout::default()],
+        }
+    }
+
+    #[test]
+    fn eids_sent_to_bidders_without_allowlist() {
+        let identity = IdentityContext::default();
+        identity
+            .eids
+            .set(vec![Eid {
+                source: "uidapi.com".to_string(),
+                uids: vec![Uid {
+                    id: "uid2-token".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }])
+            .unwrap();
+
+        let mut bidders = vec![
+            bidder_context("open", None),
+            bidder_context("restricted", Some(vec!["liveramp.com".to_string()])),
+        ];
+
+        IdentityDemandTask::inject_eids(&identity, &mut bidders);
+
+        let eids = |b: &BidderContext| b.callouts[0].req.user.as_ref().map_or(0, |u| u.eids.len());
+
+        assert_eq!(eids(&bidders[0]), 1);
+        assert_eq!(eids(&bidders[1]), 0);
+    }
+}
@@ -319,6 +319,11 @@ mod tests {
                 gzip: false,
                 multi_imp: false,
                 usersync: None,
+                adapter: None,
+                margin: None,
+                bid_adjustment: None,
+                share_segments: false,
+                eid_sources: None,
             }),
             callouts: vec![BidderCallout {
                 endpoint: endpoint.clone(),
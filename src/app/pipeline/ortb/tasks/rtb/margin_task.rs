@@ -1,13 +1,15 @@
 use crate::app::pipeline::ortb::AuctionContext;
 use crate::app::pipeline::ortb::context::{BidderCallout, BidderResponseState};
 use crate::core::demand::takerate;
+use crate::core::models::bidder::Bidder;
+use crate::core::models::publisher::Publisher;
 use anyhow::Error;
 use async_trait::async_trait;
 use pipeline::AsyncTask;
 use rtb::child_span_info;
 use tracing::{Instrument, debug, warn};
 
-fn apply_margin(callout: &mut BidderCallout, take_rate: u32) {
+fn apply_margin(callout: &mut BidderCallout, publisher: &Publisher, bidder: &Bidder) {
     let res = match callout.response.get_mut() {
         Some(res) => res,
         None => return,
@@ -26,6 +28,13 @@ fn apply_margin(callout: &mut BidderCallout, take_rate: u32) {
                 continue;
             }
 
+            let take_rate = takerate::effective_margin(
+                publisher,
+                bidder,
+                bid_context.deal.get().map(|d| d.as_ref()),
+            );
+            let adjustment = bidder.adjustment_factor();
+
             let bid = &mut bid_context.bid;
 
             if bid.price != bid_context.original_bid_price {
@@ -34,14 +43,14 @@ fn apply_margin(callout: &mut BidderCallout, take_rate: u32) {
                 );
             }
 
-            let reduced_bid = takerate::markdown_bid(bid.price, take_rate);
+            let reduced_bid = takerate::net_bid(bid.price, take_rate, adjustment);
 
             bid_context.reduced_bid_price.replace(reduced_bid);
             bid.price = reduced_bid;
 
             debug!(
-                "Applied margin of {}% bid ${} -> ${}",
-                take_rate, bid_context.original_bid_price, bid.price
+                "Applied margin of {}% and adjustment x{} bid ${} -> ${}",
+                take_rate, adjustment, bid_context.original_bid_price, bid.price
             );
         }
     }
@@ -59,7 +68,7 @@ impl BidMarginTask {
                     continue;
                 }
 
-                apply_margin(callout, publisher.margin);
+                apply_margin(callout, publisher, &bidder_context.bidder);
             }
         }
 
@@ -1,18 +1,42 @@
+mod bid_currency;
+pub use bid_currency::BidCurrencyTask;
+
+mod bidder_adapters;
+pub use bidder_adapters::BidderAdapterTask;
+
 mod bidder_callouts;
 pub use bidder_callouts::BidderCalloutsTask;
 
+mod callout_tmax;
+pub use callout_tmax::CalloutTmaxTask;
+
+mod circuit_breaker;
+pub use circuit_breaker::CircuitBreakerTask;
+
 mod deal_attribution;
 pub use deal_attribution::RtbDealAttributionTask;
 
+mod deal_price;
+pub use deal_price::DealPriceEnforcementTask;
+
 mod bidder_matching;
 pub use bidder_matching::BidderMatchingTask;
 
+mod floor_learning;
+pub use floor_learning::FloorLearningTask;
+
+mod floors;
+pub use floors::FloorsTask;
+
 mod floors_markup;
 pub use floors_markup::FloorsMarkupTask;
 
 mod identity_demand;
 pub use identity_demand::IdentityDemandTask;
 
+mod segment_demand;
+pub use segment_demand::SegmentDemandTask;
+
 mod imp_breakout;
 pub use imp_breakout::MultiImpBreakoutTask;
 
@@ -1,6 +1,6 @@
 use crate::app::pipeline::ortb::AuctionContext;
 use crate::app::pipeline::ortb::context::{BidderCallout, CalloutSkipReason};
-use crate::core::cluster::ClusterDiscovery;
+use crate::core::cluster::{ClusterDiscovery, QpsCoordinator};
 use crate::core::managers::{DemandChange, DemandManager};
 use crate::core::models::bidder::Endpoint;
 use crate::core::spec::nobidreasons;
@@ -16,49 +16,133 @@ use std::num::NonZeroU32;
 use std::sync::Arc;
 use tracing::{Instrument, Span, debug, trace, warn};
 
-fn create_limiter(endpoint: &Endpoint, cluster_sz: usize) -> Option<DefaultDirectRateLimiter> {
-    if endpoint.qps < 1 {
+/// A rate limiter alongside the node-local QPS it was built
+/// for, so it is only rebuilt when its share actually changes
+struct EndpointLimiter {
+    local_qps: u32,
+    limiter: DefaultDirectRateLimiter,
+}
+
+fn create_limiter(
+    endpoint: &Endpoint,
+    coordinator: &QpsCoordinator,
+    cluster_sz: usize,
+) -> Option<EndpointLimiter> {
+    let local_qps = coordinator.local_qps(endpoint, cluster_sz);
+
+    if local_qps < 1 {
         debug!("Endpoint {} QPS limit: None", endpoint.name);
         None
     } else {
-        let local_qps = (endpoint.qps / cluster_sz).max(1);
         debug!(
             "Endpoint {} QPS limit: {} ({} effective locally)",
             endpoint.name, endpoint.qps, local_qps
         );
-        Some(RateLimiter::direct(Quota::per_second(
-            NonZeroU32::new(local_qps as u32).unwrap(),
-        )))
+        Some(EndpointLimiter {
+            local_qps,
+            limiter: RateLimiter::direct(Quota::per_second(NonZeroU32::new(local_qps).unwrap())),
+        })
+    }
+}
+
+/// Permits currently available in the limiter, consuming them.
+/// Only for limiters about to be replaced, as governor can't peek
+fn drain_available(limiter: &DefaultDirectRateLimiter, burst: u32) -> u32 {
+    let mut available = 0;
+    let mut step = burst.checked_next_power_of_two().unwrap_or(burst);
+
+    // failed checks consume nothing, so descending powers of
+    // two sum exactly to what was available
+    while step > 0 {
+        if let Some(n) = NonZeroU32::new(step) {
+            if matches!(limiter.check_n(n), Ok(Ok(()))) {
+                available += step;
+            }
+        }
+        step /= 2;
+    }
+
+    available
+}
+
+/// Limiter for a new local share which starts with no more permits
+/// than the replaced one had left, so a rebalance never hands every
+/// node a fresh full burst
+fn resize_limiter(previous: &EndpointLimiter, local_qps: u32) -> Option<EndpointLimiter> {
+    let burst = NonZeroU32::new(local_qps)?;
+    let limiter = RateLimiter::direct(Quota::per_second(burst));
+
+    let carried = drain_available(&previous.limiter, previous.local_qps).min(local_qps);
+
+    if let Some(spent) = NonZeroU32::new(local_qps - carried) {
+        let _ = limiter.check_n(spent);
     }
+
+    Some(EndpointLimiter { local_qps, limiter })
 }
 
+type LimitersMap = HashMap<String, Option<EndpointLimiter>>;
+
 /// Responsible for enforcing QPS limits per endpoint,
-/// for callouts which do not already have a skip_reason assigned
+/// for callouts which do not already have a skip_reason assigned.
+/// Local limits are sized by the ['QpsCoordinator']
 pub struct QpslimiterTask {
-    endpoints: Arc<RwLock<HashMap<String, Option<DefaultDirectRateLimiter>>>>,
+    endpoints: Arc<RwLock<LimitersMap>>,
+    coordinator: Arc<QpsCoordinator>,
 }
 
 impl QpslimiterTask {
-    pub fn new(bidder_manager: Arc<DemandManager>, cluster: Arc<dyn ClusterDiscovery>) -> Self {
-        let endpoint_limiters = Self::rebuild_limiters_map(&bidder_manager, cluster.cluster_size());
+    pub fn new(
+        bidder_manager: Arc<DemandManager>,
+        cluster: Arc<dyn ClusterDiscovery>,
+        coordinator: Arc<QpsCoordinator>,
+    ) -> Self {
+        let endpoint_limiters =
+            Self::rebuild_limiters_map(&bidder_manager, &coordinator, cluster.cluster_size());
         let endpoints = Arc::new(RwLock::new(endpoint_limiters));
 
         // Cluster size change -> full rebuild
         let endpoints_ref = endpoints.clone();
         let bidder_manager_ref = bidder_manager.clone();
+        let coordinator_ref = coordinator.clone();
         cluster.on_change(Box::new(move |cluster_sz| {
             debug!(
                 "Cluster size changed to {}, rebuilding QPS limiters map",
                 cluster_sz
             );
-            let new_limiters =
-                QpslimiterTask::rebuild_limiters_map(&bidder_manager_ref, cluster_sz);
+            let new_limiters = QpslimiterTask::rebuild_limiters_map(
+                &bidder_manager_ref,
+                &coordinator_ref,
+                cluster_sz,
+            );
             *endpoints_ref.write() = new_limiters;
         }));
 
+        // Coordination cycle -> only replace limiters whose share moved.
+        // Weak refs since the coordinator owns this callback
+        let endpoints_ref = Arc::downgrade(&endpoints);
+        let bidder_manager_ref = bidder_manager.clone();
+        let coordinator_ref = Arc::downgrade(&coordinator);
+        let cluster_ref = cluster.clone();
+        coordinator.on_rebalance(Box::new(move || {
+            let (endpoints, coordinator) =
+                match (endpoints_ref.upgrade(), coordinator_ref.upgrade()) {
+                    (Some(endpoints), Some(coordinator)) => (endpoints, coordinator),
+                    _ => return,
+                };
+
+            QpslimiterTask::rebalance_limiters(
+                &endpoints,
+                &bidder_manager_ref,
+                &coordinator,
+                cluster_ref.cluster_size(),
+            );
+        }));
+
         // Demand change -> incremental update
         let endpoints_ref = endpoints.clone();
         let cluster_ref = cluster.clone();
+        let coordinator_ref = coordinator.clone();
         bidder_manager.on_change(Box::new(move |change| {
             let cluster_sz = cluster_ref.cluster_size();
             let mut map = endpoints_ref.write();
@@ -72,7 +156,10 @@ impl QpslimiterTask {
                             continue;
                         }
                         debug!("Adding QPS limiter for endpoint {}", ep.name);
-                        map.insert(ep.stable_id().to_string(), create_limiter(ep, cluster_sz));
+                        map.insert(
+                            ep.stable_id().to_string(),
+                            create_limiter(ep, &coordinator_ref, cluster_sz),
+                        );
                     }
                 }
                 DemandChange::Modified {
@@ -87,7 +174,10 @@ impl QpslimiterTask {
                     }
                     for ep in new_eps {
                         if ep.enabled {
-                            map.insert(ep.stable_id().to_string(), create_limiter(ep, cluster_sz));
+                            map.insert(
+                                ep.stable_id().to_string(),
+                                create_limiter(ep, &coordinator_ref, cluster_sz),
+                            );
                         }
                     }
                 }
@@ -106,22 +196,30 @@ impl QpslimiterTask {
             }
         }));
 
-        Self { endpoints }
+        Self {
+            endpoints,
+            coordinator,
+        }
     }
 
-    fn rebuild_limiters_map(
-        bidder_manager: &DemandManager,
-        cluster_sz: usize,
-    ) -> HashMap<String, Option<DefaultDirectRateLimiter>> {
-        let mut endpoints_limiters = HashMap::new();
-
-        let cluster_sz = if cluster_sz > 1 {
+    fn effective_cluster_size(cluster_sz: usize) -> usize {
+        if cluster_sz > 1 {
             cluster_sz
         } else {
             warn!("Cluster size reported 0, counting self. New cluster state?");
 
             1
-        };
+        }
+    }
+
+    fn rebuild_limiters_map(
+        bidder_manager: &DemandManager,
+        coordinator: &QpsCoordinator,
+        cluster_sz: usize,
+    ) -> LimitersMap {
+        let mut endpoints_limiters = HashMap::new();
+
+        let cluster_sz = Self::effective_cluster_size(cluster_sz);
 
         bidder_manager
             .bidders_endpoints()
@@ -133,7 +231,7 @@ impl QpslimiterTask {
                     }
                     endpoints_limiters.insert(
                         endpoint.stable_id().to_string(),
-                        create_limiter(endpoint, cluster_sz),
+                        create_limiter(endpoint, coordinator, cluster_sz),
                     );
                 }
             });
@@ -141,6 +239,47 @@ impl QpslimiterTask {
         endpoints_limiters
     }
 
+    fn rebalance_limiters(
+        endpoints: &RwLock<LimitersMap>,
+        bidder_manager: &DemandManager,
+        coordinator: &QpsCoordinator,
+        cluster_sz: usize,
+    ) {
+        let cluster_sz = cluster_sz.max(1);
+
+        for (_, bidder_endpoints) in bidder_manager.bidders_endpoints() {
+            for endpoint in bidder_endpoints {
+                if !endpoint.enabled {
+                    continue;
+                }
+
+                let local_qps = coordinator.local_qps(&endpoint, cluster_sz);
+                let current_qps = endpoints
+                    .read()
+                    .get(endpoint.stable_id())
+                    .map(|entry| entry.as_ref().map_or(0, |l| l.local_qps));
+
+                if current_qps == Some(local_qps) {
+                    continue;
+                }
+
+                trace!(
+                    "Rebalancing endpoint {} local QPS {:?} -> {}",
+                    endpoint.name, current_qps, local_qps
+                );
+
+                let mut map = endpoints.write();
+
+                let resized = match map.get(endpoint.stable_id()) {
+                    Some(Some(previous)) => resize_limiter(previous, local_qps),
+                    _ => create_limiter(&endpoint, coordinator, cluster_sz),
+                };
+
+                map.insert(endpoint.stable_id().to_string(), resized);
+            }
+        }
+    }
+
     fn should_block(&self, callout: &BidderCallout) -> bool {
         let span = child_span_info!(
             "qps_limiter_endpoint_should_block",
@@ -149,6 +288,8 @@ impl QpslimiterTask {
         )
         .entered();
 
+        self.coordinator.record_demand(callout.endpoint.stable_id());
+
         let endpoints = self.endpoints.read();
 
         let rl_opt = match endpoints.get(callout.endpoint.stable_id()) {
@@ -163,7 +304,7 @@ impl QpslimiterTask {
         };
 
         let rl = match rl_opt {
-            Some(rl) => rl,
+            Some(endpoint_limiter) => &endpoint_limiter.limiter,
             None => {
                 trace!(
                     "No QPS limit for endpoint {}, allowing request through",
@@ -268,3 +409,44 @@ impl AsyncTask<AuctionContext, Error> for QpslimiterTask {
         self.run0(context).instrument(span).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(local_qps: u32) -> EndpointLimiter {
+        EndpointLimiter {
+            local_qps,
+            limiter: RateLimiter::direct(Quota::per_second(NonZeroU32::new(local_qps).unwrap())),
+        }
+    }
+
+    fn passes(limiter: &EndpointLimiter) -> u32 {
+        (0..limiter.local_qps * 2)
+            .filter(|_| limiter.limiter.check().is_ok())
+            .count() as u32
+    }
+
+    #[test]
+    fn resize_carries_remaining_permits() {
+        let previous = limiter(100);
+        let _ = previous.limiter.check_n(NonZeroU32::new(90).unwrap());
+
+        // larger share, but only the 10 unspent permits carry over
+        let resized = resize_limiter(&previous, 200).unwrap();
+        assert!(passes(&resized) <= 11);
+
+        // smaller share caps what carries over
+        let resized = resize_limiter(&limiter(100), 20).unwrap();
+        assert!(passes(&resized) <= 21);
+    }
+
+    #[test]
+    fn drains_exact_available() {
+        let previous = limiter(100);
+        let _ = previous.limiter.check_n(NonZeroU32::new(37).unwrap());
+
+        let available = drain_available(&previous.limiter, 100);
+        assert!((63..=64).contains(&available));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::app::config::SegmentUserDataConfig;
+use crate::app::pipeline::ortb::AuctionContext;
+use anyhow::Error;
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use rtb::bid_request::Data;
+use rtb::bid_request::data::Segment;
+use rtb::child_span_info;
+use tracing::{Instrument, debug};
+
+/// Shares the user's first party segments with bidders which opt in
+/// via `share_segments`, as a user.data entry under the configured
+/// name and taxonomy id. Runs after IdentityDemandTask, which
+/// guarantees callouts carry a user object for recognized users
+pub struct SegmentDemandTask {
+    config: SegmentUserDataConfig,
+}
+
+impl SegmentDemandTask {
+    pub fn new(config: SegmentUserDataConfig) -> Self {
+        Self { config }
+    }
+
+    fn user_data(&self, segments: &[compact_str::CompactString]) -> Data {
+        Data {
+            id: self.config.taxonomy_id.clone(),
+            name: self.config.name.clone(),
+            segment: segments
+                .iter()
+                .map(|s| Segment {
+                    id: s.to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    async fn run0(&self, context: &AuctionContext) -> Result<(), Error> {
+        let Some(segments) = context.identity.get().and_then(|i| i.segments.get()) else {
+            return Ok(());
+        };
+
+        if segments.is_empty() {
+            return Ok(());
+        }
+
+        let data = self.user_data(segments);
+        let mut bidders = context.bidders.lock().await;
+
+        for bidder_ctx in bidders.iter_mut().filter(|b| b.bidder.share_segments) {
+            debug!(
+                "Sharing {} segments with bidder {}",
+                segments.len(),
+                bidder_ctx.bidder.name
+            );
+
+            for callout in bidder_ctx.callouts.iter_mut() {
+                if let Some(user) = callout.req.user.as_mut() {
+                    user.data.push(data.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncTask<AuctionContext, Error> for SegmentDemandTask {
+    async fn run(&self, context: &AuctionContext) -> Result<(), Error> {
+        let span = child_span_info!("segment_demand_task");
+
+        self.run0(context).instrument(span).await
+    }
+}
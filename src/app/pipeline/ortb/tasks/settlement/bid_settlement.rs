@@ -1,5 +1,8 @@
 use crate::app::pipeline::ortb::AuctionContext;
 use crate::app::pipeline::ortb::context::{BidderContext, BidderResponseState};
+use crate::app::pipeline::ortb::direct::pacing::DealPacer;
+use crate::core::currency::{CurrencyService, FxRates};
+use crate::core::models::deal::Deal;
 use crate::core::models::placement::FillPolicy;
 use crate::core::spec::nobidreasons;
 use anyhow::{Error, bail};
@@ -8,6 +11,7 @@ use pipeline::AsyncTask;
 use rtb::bid_response::{Bid, SeatBid, SeatBidBuilder};
 use rtb::common::bidresponsestate::BidResponseState;
 use rtb::{BidResponseBuilder, child_span_info};
+use std::sync::Arc;
 use tracing::{Instrument, debug, warn};
 
 pub fn sort_bids_by_price(bids: &mut [Bid]) {
@@ -22,15 +26,81 @@ pub fn sort_seats_by_highest_bid(seats: &mut [SeatBid]) {
     });
 }
 
-pub struct BidSettlementTask;
+/// Converts every bid price between currencies. All or nothing,
+/// returns false leaving prices untouched if no rate is available
+pub fn convert_seats_currency(
+    seats: &mut [SeatBid],
+    rates: &FxRates,
+    from: &str,
+    to: &str,
+) -> bool {
+    if rates.convert(1.0, from, to).is_none() {
+        return false;
+    }
+
+    for bid in seats.iter_mut().flat_map(|s| s.bid.iter_mut()) {
+        bid.price = rates.convert(bid.price, from, to).unwrap_or(bid.price);
+    }
+
+    true
+}
+
+/// Settlement precedence of a bid. Only bids of the highest
+/// tier present in an auction compete, on price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SettlementTier {
+    Open,
+    /// Bid through a deal which takes priority
+    Priority,
+    /// Bid through a guaranteed deal behind its delivery schedule
+    MustDeliver,
+}
+
+pub fn settlement_tier(deal: Option<&Deal>, deal_pacer: &dyn DealPacer) -> SettlementTier {
+    match deal {
+        Some(deal) if deal.guaranteed && deal_pacer.behind_pace(deal) => {
+            SettlementTier::MustDeliver
+        }
+        Some(deal) if deal.takes_priority => SettlementTier::Priority,
+        _ => SettlementTier::Open,
+    }
+}
+
+/// Keeps only the seats of the highest [`SettlementTier`] present.
+/// Seats compete as a whole, with every bid they hold
+pub fn top_tier_seats(seat_pairs: Vec<(SeatBid, SettlementTier)>) -> Vec<SeatBid> {
+    let top_tier = seat_pairs
+        .iter()
+        .map(|(_, tier)| *tier)
+        .max()
+        .unwrap_or(SettlementTier::Open);
+
+    seat_pairs
+        .into_iter()
+        .filter(|(_, tier)| *tier == top_tier)
+        .map(|(s, _)| s)
+        .collect()
+}
+
+pub struct BidSettlementTask {
+    currency: Arc<CurrencyService>,
+    deal_pacer: Arc<dyn DealPacer>,
+}
 
 impl BidSettlementTask {
-    /// Returns Vec<(Bid, takes_priority)> for a single bidder context.
+    pub fn new(currency: Arc<CurrencyService>, deal_pacer: Arc<dyn DealPacer>) -> Self {
+        Self {
+            currency,
+            deal_pacer,
+        }
+    }
+
+    /// Returns Vec<(Bid, SettlementTier)> for a single bidder context.
     fn build_bidder_seat_bids(
         &self,
         bidder_context: &BidderContext,
         fill_policy: &FillPolicy,
-    ) -> Vec<(Bid, bool)> {
+    ) -> Vec<(Bid, SettlementTier)> {
         let mut seat_bids = Vec::with_capacity(bidder_context.callouts.len());
 
         for callout in bidder_context.callouts.iter() {
@@ -63,13 +133,12 @@ impl BidSettlementTask {
                         continue;
                     }
 
-                    let takes_priority = bid_context
-                        .deal
-                        .get()
-                        .map(|d| d.takes_priority)
-                        .unwrap_or(false);
+                    let tier = settlement_tier(
+                        bid_context.deal.get().map(Arc::as_ref),
+                        self.deal_pacer.as_ref(),
+                    );
 
-                    seat_bids.push((bid_context.bid.clone(), takes_priority));
+                    seat_bids.push((bid_context.bid.clone(), tier));
                 }
             }
         }
@@ -77,18 +146,23 @@ impl BidSettlementTask {
         seat_bids
     }
 
-    /// Returns Vec<(SeatBid, has_priority_bid)> for all bidders (RTB + merged direct).
+    /// Returns Vec<(SeatBid, seat_tier)> for all bidders (RTB + merged
+    /// direct), the seat tier being the highest tier of any of its bids
     fn build_seats(
         &self,
         bidders: &Vec<BidderContext>,
         fill_policy: &FillPolicy,
-    ) -> Vec<(SeatBid, bool)> {
+    ) -> Vec<(SeatBid, SettlementTier)> {
         let mut seats = Vec::with_capacity(bidders.len());
 
         for bidder in bidders.iter() {
             let bid_pairs = self.build_bidder_seat_bids(bidder, fill_policy);
 
-            let has_priority = bid_pairs.iter().any(|(_, p)| *p);
+            let seat_tier = bid_pairs
+                .iter()
+                .map(|(_, tier)| *tier)
+                .max()
+                .unwrap_or(SettlementTier::Open);
             let mut bids: Vec<Bid> = bid_pairs.into_iter().map(|(b, _)| b).collect();
 
             sort_bids_by_price(&mut bids);
@@ -111,7 +185,7 @@ impl BidSettlementTask {
             };
 
             if !bidder_seat.bid.is_empty() {
-                seats.push((bidder_seat, has_priority));
+                seats.push((bidder_seat, seat_tier));
             }
         }
 
@@ -128,19 +202,7 @@ impl BidSettlementTask {
             .map(|p| &p.fill_policy)
             .unwrap_or(&FillPolicy::HighestPrice);
 
-        let seat_pairs = self.build_seats(&bidders, fill_policy);
-
-        // If any seat has a priority deal bid, keep only priority seats
-        let any_priority = seat_pairs.iter().any(|(_, p)| *p);
-        let mut seats: Vec<SeatBid> = if any_priority {
-            seat_pairs
-                .into_iter()
-                .filter(|(_, p)| *p)
-                .map(|(s, _)| s)
-                .collect()
-        } else {
-            seat_pairs.into_iter().map(|(s, _)| s).collect()
-        };
+        let mut seats = top_tier_seats(self.build_seats(&bidders, fill_policy));
 
         if seats.is_empty() {
             let (nbr, desc) = context
@@ -167,9 +229,34 @@ impl BidSettlementTask {
 
         sort_seats_by_highest_bid(&mut seats);
 
+        // Auction ran in accounting currency, respond
+        // in the currency the publisher requested
+        let accounting = self.currency.accounting();
+        let mut response_currency = context
+            .currency
+            .get()
+            .map(String::as_str)
+            .unwrap_or(accounting);
+
+        if response_currency != accounting
+            && !convert_seats_currency(
+                &mut seats,
+                &self.currency.rates(),
+                accounting,
+                response_currency,
+            )
+        {
+            warn!(
+                "No FX rate to {}, responding in {}",
+                response_currency, accounting
+            );
+            response_currency = accounting;
+        }
+
         let final_bid_response_result = BidResponseBuilder::default()
             .id(context.original_auction_id.clone())
             .seatbid(seats)
+            .cur(response_currency.to_string())
             .build();
 
         if let Err(e) = final_bid_response_result {
@@ -222,8 +309,89 @@ impl AsyncTask<AuctionContext, Error> for BidSettlementTask {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::models::deal::DealPricing;
     use rtb::bid_response::BidBuilder;
 
+    struct StubDealPacer {
+        behind: bool,
+    }
+
+    impl DealPacer for StubDealPacer {
+        fn passes(&self, _deal: &Deal) -> bool {
+            true
+        }
+        fn behind_pace(&self, _deal: &Deal) -> bool {
+            self.behind
+        }
+        fn record_impression(&self, _deal_id: &str) {}
+    }
+
+    fn stub_deal(takes_priority: bool, guaranteed: bool) -> Deal {
+        Deal {
+            pricing: DealPricing::Fixed(5.0),
+            takes_priority,
+            guaranteed,
+            ..Deal::stub("d1")
+        }
+    }
+
+    #[test]
+    fn test_settlement_tier() {
+        let behind = StubDealPacer { behind: true };
+        let on_pace = StubDealPacer { behind: false };
+
+        assert_eq!(settlement_tier(None, &behind), SettlementTier::Open);
+        assert_eq!(
+            settlement_tier(Some(&stub_deal(false, false)), &behind),
+            SettlementTier::Open
+        );
+        assert_eq!(
+            settlement_tier(Some(&stub_deal(true, false)), &behind),
+            SettlementTier::Priority
+        );
+        assert_eq!(
+            settlement_tier(Some(&stub_deal(true, true)), &on_pace),
+            SettlementTier::Priority
+        );
+        assert_eq!(
+            settlement_tier(Some(&stub_deal(false, true)), &behind),
+            SettlementTier::MustDeliver
+        );
+        assert!(SettlementTier::MustDeliver > SettlementTier::Priority);
+    }
+
+    #[test]
+    fn test_top_tier_seats_keep_whole_seats() {
+        let seat = |id: &str, prices: &[f64]| {
+            SeatBidBuilder::default()
+                .seat(id.to_string())
+                .bid(
+                    prices
+                        .iter()
+                        .map(|p| BidBuilder::default().price(*p).build().unwrap())
+                        .collect::<Vec<_>>(),
+                )
+                .build()
+                .unwrap()
+        };
+
+        let seats = top_tier_seats(vec![
+            (seat("open", &[9.0]), SettlementTier::Open),
+            (seat("priority", &[4.0, 2.0]), SettlementTier::Priority),
+        ]);
+
+        assert_eq!(seats.len(), 1);
+        assert_eq!(seats[0].seat, "priority");
+        assert_eq!(seats[0].bid.len(), 2, "seat keeps its open bids too");
+
+        let seats = top_tier_seats(vec![
+            (seat("a", &[1.0]), SettlementTier::Open),
+            (seat("b", &[2.0]), SettlementTier::Open),
+        ]);
+
+        assert_eq!(seats.len(), 2);
+    }
+
     #[test]
     fn test_sort_bids_by_price_descending() {
         let mut bids = vec![
@@ -239,6 +407,29 @@ mod tests {
         assert_eq!(bids[2].price, 1.5);
     }
 
+    #[test]
+    fn test_convert_seats_currency() {
+        let rates = FxRates {
+            base: "USD".to_string(),
+            rates: std::collections::HashMap::from([("EUR".to_string(), 0.5)]),
+            passthrough: false,
+        };
+
+        let mut seats = vec![
+            SeatBidBuilder::default()
+                .seat("seat1".to_string())
+                .bid(vec![BidBuilder::default().price(4.0).build().unwrap()])
+                .build()
+                .unwrap(),
+        ];
+
+        assert!(!convert_seats_currency(&mut seats, &rates, "USD", "JPY"));
+        assert_eq!(seats[0].bid[0].price, 4.0);
+
+        assert!(convert_seats_currency(&mut seats, &rates, "USD", "EUR"));
+        assert_eq!(seats[0].bid[0].price, 2.0);
+    }
+
     #[test]
     fn test_sort_bids_by_price_empty() {
         let mut bids: Vec<Bid> = vec![];
@@ -1,7 +1,8 @@
 use crate::app::pipeline::syncing::r#in::context::SyncInContext;
 use crate::app::pipeline::syncing::utils;
+use crate::core::usersync;
 use crate::core::usersync::model::SyncInEvent;
-use anyhow::{Error, anyhow};
+use anyhow::{Error, anyhow, bail};
 use pipeline::BlockingTask;
 use rtb::child_span_info;
 use tracing::{debug, warn};
@@ -21,6 +22,10 @@ impl BlockingTask<SyncInContext, Error> for ExtractEventTask {
             .get()
             .ok_or_else(|| anyhow!("Data url missing from context!"))?;
 
+        if usersync::utils::is_opted_out(&context.cookies) {
+            bail!("User opted out, not storing their buyeruid");
+        }
+
         let (local_uid, recognized) = utils::extract_or_assign_local_uid(&context.cookies);
 
         if !recognized {
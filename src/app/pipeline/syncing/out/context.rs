@@ -1,4 +1,6 @@
 use crate::core::models::publisher::Publisher;
+use crate::core::usersync::chain::SyncChain;
+use crate::core::usersync::model::SyncEntry;
 use std::collections::HashMap;
 use std::sync::{Arc, OnceLock};
 use strum::{AsRefStr, Display, EnumString};
@@ -16,7 +18,37 @@ pub enum SyncResponse {
     /// If we have no matching partners to initiate sync with,
     /// just return a 204
     NoContent,
-    // Redirect(String) if ever needed.. plain redirect etc
+    /// Redirect to the attached url, the next hop of a sync chain
+    Redirect(String),
+    /// Respond with a transparent pixel, ending a sync chain
+    /// which has nowhere left to redirect to
+    Pixel,
+}
+
+/// How the sync-out call syncs the user's bidders
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SyncMode {
+    /// An iframe firing every bidder's pixel at once
+    #[default]
+    Iframe,
+    /// A 302 chain through one bidder's sync url at a time, each
+    /// returning to /sync/in, which hands back to us for the next.
+    /// Works from a single image pixel, where iframes are blocked
+    Redirect,
+}
+
+impl SyncMode {
+    /// Mode from the sync-out query string, e.g. ?mode=redirect
+    pub fn from_query(query: &str) -> Self {
+        let redirect = url::form_urlencoded::parse(query.as_bytes())
+            .any(|(key, value)| key == "mode" && value == "redirect");
+
+        if redirect {
+            SyncMode::Redirect
+        } else {
+            SyncMode::Iframe
+        }
+    }
 }
 
 /// Context for pipeline which processes the initial call to us which
@@ -29,19 +61,26 @@ pub struct SyncOutContext {
     pub publisher: OnceLock<Arc<Publisher>>,
     /// Cookies extracted from the http request
     pub cookies: HashMap<String, String>,
+    pub mode: SyncMode,
     /// The local user ID a.k.a our exchange ID for this user,
     /// as extracted from the cookies
     pub local_uid: OnceLock<String>,
+    /// The user's stored syncs by partner id, empty if none
+    pub entries: OnceLock<HashMap<String, SyncEntry>>,
+    /// Redirect chain state to carry to the next hop, unset
+    /// when the chain has finished
+    pub chain: OnceLock<SyncChain>,
     /// The final ['SyncResponse'] which we should
     /// send as the http response
     pub response: OnceLock<SyncResponse>,
 }
 
 impl SyncOutContext {
-    pub fn new(pubid: String, cookies: HashMap<String, String>) -> SyncOutContext {
+    pub fn new(pubid: String, cookies: HashMap<String, String>, mode: SyncMode) -> SyncOutContext {
         Self {
             pubid,
             cookies,
+            mode,
             ..Default::default()
         }
     }
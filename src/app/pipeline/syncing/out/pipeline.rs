@@ -1,7 +1,7 @@
 use crate::app::context::StartupContext;
 use crate::app::pipeline::syncing::out::context::SyncOutContext;
 use crate::app::pipeline::syncing::out::tasks;
-use anyhow::{Error, bail};
+use anyhow::{Error, anyhow, bail};
 use pipeline::{Pipeline, PipelineBuilder};
 
 /// Builds the pipeline responsible for handling our user sync pixel calls,
@@ -23,13 +23,33 @@ pub fn build_user_sync_out_pipeline(
         None => bail!("No Bidder Manager?! Cant build rtb pipeline"),
     };
 
+    let sync_store = context
+        .sync_store
+        .get()
+        .ok_or_else(|| anyhow!("No sync store created on context!"))?;
+
+    let sync_revenue = context
+        .sync_revenue
+        .get()
+        .ok_or_else(|| anyhow!("No sync revenue created on context!"))?;
+
+    let sync_config = match context.config.get() {
+        Some(config) => config.usersync.clone(),
+        None => bail!("No config?! Cant build sync out pipeline"),
+    };
+
     let pipeline = PipelineBuilder::new()
         .with_blocking(Box::new(tasks::ExtractLocalUidTask))
         .with_blocking(Box::new(tasks::ExtractPublisherTask::new(
             pub_manager.clone(),
         )))
+        .with_async(Box::new(tasks::LoadSyncEntriesTask::new(
+            sync_store.clone(),
+        )))
         .with_blocking(Box::new(tasks::BuildSyncOutResponseTask::new(
             bidder_manager.clone(),
+            sync_config,
+            sync_revenue.clone(),
         )))
         .build()
         .expect("Sync out pipeline should have tasks");
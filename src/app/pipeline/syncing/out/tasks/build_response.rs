@@ -1,29 +1,63 @@
-use crate::app::pipeline::syncing::out::context::{SyncOutContext, SyncResponse};
+use crate::app::config::UserSyncConfig;
+use crate::app::pipeline::syncing::out::context::{SyncMode, SyncOutContext, SyncResponse};
 use crate::core::managers::DemandManager;
+use crate::core::models::publisher::Publisher;
 use crate::core::models::sync::{SyncConfig, SyncKind};
 use crate::core::usersync;
-use anyhow::{Error, bail};
+use crate::core::usersync::SyncRevenue;
+use crate::core::usersync::chain::{self, SyncChain};
+use crate::core::usersync::selection;
+use anyhow::{Error, anyhow, bail};
 use pipeline::BlockingTask;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::warn;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::{debug, warn};
 
 pub struct BuildSyncOutResponseTask {
     bidders: Arc<DemandManager>,
+    config: UserSyncConfig,
+    /// Recent revenue per bidder, for weighted order
+    revenue: Arc<SyncRevenue>,
+    /// Rotated order start, advanced on every sync call
+    rotation: AtomicUsize,
 }
 
 impl BuildSyncOutResponseTask {
-    pub fn new(bidders: Arc<DemandManager>) -> Self {
-        Self { bidders }
+    pub fn new(
+        bidders: Arc<DemandManager>,
+        config: UserSyncConfig,
+        revenue: Arc<SyncRevenue>,
+    ) -> Self {
+        Self {
+            bidders,
+            config,
+            revenue,
+            rotation: AtomicUsize::new(0),
+        }
     }
-}
 
-impl BlockingTask<SyncOutContext, Error> for BuildSyncOutResponseTask {
-    fn run(&self, context: &SyncOutContext) -> Result<(), Error> {
-        let local_uid = match context.local_uid.get() {
-            Some(local_uid) => local_uid,
-            None => bail!("Local uid is not set! Cannot build sync response"),
-        };
+    fn pub_sync(publisher: &Publisher) -> Option<SyncConfig> {
+        match &publisher.sync_url {
+            Some(sync) => Some(SyncConfig {
+                kind: SyncKind::Image,
+                url: sync.clone(),
+                priority: 0,
+                weight: 1.0,
+                resync_interval: None,
+            }),
+            None => None,
+        }
+    }
 
+    /// Drops pixels for the bidders missing or holding a stale
+    /// buyeruid for the user, up to the per call limit
+    fn build_iframe(
+        &self,
+        context: &SyncOutContext,
+        local_uid: &String,
+        publisher: &Publisher,
+    ) -> Result<SyncResponse, Error> {
         let bidders = self.bidders.bidders();
 
         if bidders.is_empty() {
@@ -39,21 +73,33 @@ impl BlockingTask<SyncOutContext, Error> for BuildSyncOutResponseTask {
             bail!("No bidders matching to sync with");
         }
 
-        let publisher = match context.publisher.get() {
-            Some(publisher) => publisher.clone(),
-            None => bail!("No publisher on context for sync response"),
-        };
+        let empty = HashMap::new();
+        let entries = context.entries.get().unwrap_or(&empty);
 
-        let pub_sync = match &publisher.sync_url {
-            Some(sync) => Some(SyncConfig {
-                kind: SyncKind::Image,
-                url: sync.clone(),
-            }),
-            None => None,
-        };
+        let selected = selection::select_sync_bidders(
+            &bidders,
+            entries,
+            &[],
+            &self.revenue.shares(),
+            &self.config,
+            self.config.max_syncs_per_call,
+            self.rotation.fetch_add(1, Ordering::Relaxed),
+            rtb::common::utils::epoch_timestamp(),
+        );
+
+        debug!(
+            "Syncing {} of {} bidders for {}",
+            selected.len(),
+            bidders.len(),
+            local_uid
+        );
+
+        let response_html = usersync::utils::generate_sync_iframe_html(
+            local_uid,
+            selected,
+            Self::pub_sync(publisher),
+        );
 
-        let response_html =
-            usersync::utils::generate_sync_iframe_html(&local_uid, bidders, pub_sync);
         if response_html.is_empty() {
             context
                 .response
@@ -65,7 +111,94 @@ impl BlockingTask<SyncOutContext, Error> for BuildSyncOutResponseTask {
             bail!("Built sync html content empty, skipping response - no pub or demand syncs?");
         }
 
-        let response = SyncResponse::Content(response_html);
+        Ok(SyncResponse::Content(response_html))
+    }
+
+    /// Redirects to the next bidder in the user's sync chain, carried
+    /// over from the chain cookie if this is a later hop. Once nobody
+    /// is left to sync, hands back to the publisher's sync if any
+    fn build_redirect(
+        &self,
+        context: &SyncOutContext,
+        local_uid: &str,
+        publisher: &Publisher,
+    ) -> Result<SyncResponse, Error> {
+        let mut chain = context
+            .cookies
+            .get(usersync::constants::CONST_REX_SYNC_CHAIN_COOKIE)
+            .and_then(|value| SyncChain::decode(value))
+            .filter(|chain| chain.pubid == publisher.id)
+            .unwrap_or_else(|| SyncChain::new(publisher.id.clone()));
+
+        let empty = HashMap::new();
+        let entries = context.entries.get().unwrap_or(&empty);
+
+        let next = chain::next_chain_bidder(
+            &self.bidders.bidders(),
+            entries,
+            &chain,
+            &self.revenue.shares(),
+            &self.config,
+            self.rotation.fetch_add(1, Ordering::Relaxed),
+            rtb::common::utils::epoch_timestamp(),
+        );
+
+        let (bidder, sync) = match next
+            .as_ref()
+            .and_then(|bidder| bidder.usersync.as_ref().map(|sync| (bidder, sync)))
+        {
+            Some(next) => next,
+            None => {
+                debug!(
+                    "Sync chain done after {} bidders for {}",
+                    chain.attempted.len(),
+                    local_uid
+                );
+
+                return Ok(match Self::pub_sync(publisher) {
+                    Some(sync) if !sync.url.trim().is_empty() => SyncResponse::Redirect(
+                        usersync::utils::build_sync_redirect_url(&sync, local_uid),
+                    ),
+                    _ => SyncResponse::Pixel,
+                });
+            }
+        };
+
+        debug!(
+            "Sync chain hop {} redirecting to bidder {}",
+            chain.attempted.len() + 1,
+            bidder.name
+        );
+
+        chain.attempted.push(bidder.id.clone());
+
+        context
+            .chain
+            .set(chain)
+            .map_err(|_| anyhow!("Sync chain already set on context?!"))?;
+
+        Ok(SyncResponse::Redirect(
+            usersync::utils::build_sync_redirect_url(sync, local_uid),
+        ))
+    }
+}
+
+impl BlockingTask<SyncOutContext, Error> for BuildSyncOutResponseTask {
+    fn run(&self, context: &SyncOutContext) -> Result<(), Error> {
+        let local_uid = match context.local_uid.get() {
+            Some(local_uid) => local_uid,
+            None => bail!("Local uid is not set! Cannot build sync response"),
+        };
+
+        let publisher = match context.publisher.get() {
+            Some(publisher) => publisher.clone(),
+            None => bail!("No publisher on context for sync response"),
+        };
+
+        let response = match context.mode {
+            SyncMode::Iframe => self.build_iframe(context, local_uid, &publisher)?,
+            SyncMode::Redirect => self.build_redirect(context, local_uid, &publisher)?,
+        };
 
         context.response.set(response).unwrap_or_else(|_| {
             warn!("Someone already assigned sync response on empty bidder skip")
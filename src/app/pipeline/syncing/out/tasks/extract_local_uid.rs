@@ -1,9 +1,10 @@
-use crate::app::pipeline::syncing::out::context::SyncOutContext;
+use crate::app::pipeline::syncing::out::context::{SyncMode, SyncOutContext, SyncResponse};
 use crate::app::pipeline::syncing::utils;
-use anyhow::{Error, anyhow};
+use crate::core::usersync;
+use anyhow::{Error, anyhow, bail};
 use pipeline::BlockingTask;
 use rtb::child_span_info;
-use tracing::debug;
+use tracing::{debug, warn};
 
 pub struct ExtractLocalUidTask;
 
@@ -14,6 +15,21 @@ impl BlockingTask<SyncOutContext, Error> for ExtractLocalUidTask {
             "local_uid" = tracing::field::Empty,
         );
 
+        if usersync::utils::is_opted_out(&context.cookies) {
+            // no rxid issued nor syncs dropped, a chain just ends
+            let response = match context.mode {
+                SyncMode::Iframe => SyncResponse::NoContent,
+                SyncMode::Redirect => SyncResponse::Pixel,
+            };
+
+            context
+                .response
+                .set(response)
+                .unwrap_or_else(|_| warn!("Someone already assigned sync response on opt out"));
+
+            bail!("User opted out, skipping sync");
+        }
+
         let (local_uid, cookie_existed) = utils::extract_or_assign_local_uid(&context.cookies);
 
         debug!(
@@ -0,0 +1,46 @@
+use crate::app::pipeline::syncing::out::context::SyncOutContext;
+use crate::core::usersync::SyncStore;
+use anyhow::{Error, anyhow, bail};
+use async_trait::async_trait;
+use pipeline::AsyncTask;
+use rtb::child_span_info;
+use std::sync::Arc;
+use tracing::{Instrument, Span, debug};
+
+/// Loads the user's existing sync entries, so bidders
+/// we already hold a fresh buyeruid for can be skipped
+pub struct LoadSyncEntriesTask {
+    store: Arc<dyn SyncStore>,
+}
+
+impl LoadSyncEntriesTask {
+    pub fn new(store: Arc<dyn SyncStore>) -> Self {
+        Self { store }
+    }
+
+    async fn run0(&self, context: &SyncOutContext) -> Result<(), Error> {
+        let local_uid = match context.local_uid.get() {
+            Some(local_uid) => local_uid,
+            None => bail!("Local uid is not set! Cannot load sync entries"),
+        };
+
+        let entries = self.store.load(local_uid).await.unwrap_or_default();
+
+        Span::current().record("entries", entries.len());
+        debug!("Loaded {} sync entries for {}", entries.len(), local_uid);
+
+        context
+            .entries
+            .set(entries)
+            .map_err(|_| anyhow!("Sync entries already set on context?!"))
+    }
+}
+
+#[async_trait]
+impl AsyncTask<SyncOutContext, Error> for LoadSyncEntriesTask {
+    async fn run(&self, context: &SyncOutContext) -> Result<(), Error> {
+        let span = child_span_info!("load_sync_entries_task", entries = tracing::field::Empty);
+
+        self.run0(context).instrument(span).await
+    }
+}
@@ -1,7 +1,9 @@
 mod build_response;
 mod extract_local_uid;
 mod extract_publisher;
+mod load_entries;
 
 pub use build_response::BuildSyncOutResponseTask;
 pub use extract_local_uid::ExtractLocalUidTask;
 pub use extract_publisher::ExtractPublisherTask;
+pub use load_entries::LoadSyncEntriesTask;
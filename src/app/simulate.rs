@@ -0,0 +1,131 @@
+use crate::core::shaping::simulator::{
+    AuctionLogRecord, SimulationReport, SimulationScenario, simulate,
+};
+use anyhow::{Error, anyhow, bail};
+use config::Config;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+/// Scenario file for the offline shaping simulator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    /// Inbound QPS at which the auction log is replayed
+    pub target_qps: u32,
+    /// Shaping configurations to compare side by side
+    pub scenarios: Vec<SimulationScenario>,
+}
+
+impl SimulationConfig {
+    pub fn load(path: &PathBuf) -> Result<SimulationConfig, Error> {
+        let cfg = Config::builder()
+            .add_source(config::File::from(path.to_path_buf()))
+            .build()?;
+
+        Ok(cfg.try_deserialize()?)
+    }
+}
+
+fn load_records(path: &PathBuf) -> Result<Vec<AuctionLogRecord>, Error> {
+    let file = File::open(path)
+        .map_err(|e| anyhow!("Failed to open auction log {}: {}", path.display(), e))?;
+
+    let mut records = Vec::new();
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = serde_json::from_str::<AuctionLogRecord>(&line)
+            .map_err(|e| anyhow!("Bad auction log record on line {}: {}", idx + 1, e))?;
+
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn run_scenario(
+    records: &[AuctionLogRecord],
+    scenario: &SimulationScenario,
+    target_qps: u32,
+) -> Result<SimulationReport, Error> {
+    // fresh paused runtime per scenario so each shaper
+    // starts from a clean clock and its tasks die with it
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()?;
+
+    runtime.block_on(simulate(records, scenario, target_qps))
+}
+
+fn print_reports(reports: &[SimulationReport]) {
+    println!(
+        "{:<24} {:>10} {:>10} {:>9} {:>10} {:>10} {:>10} {:>10} {:>12} {:>9} {:>10}",
+        "scenario",
+        "requests",
+        "sent",
+        "pass %",
+        "metric",
+        "explore",
+        "boost",
+        "qps_limit",
+        "revenue $",
+        "rev %",
+        "qps saved"
+    );
+
+    for report in reports {
+        println!(
+            "{:<24} {:>10} {:>10} {:>9.2} {:>10} {:>10} {:>10} {:>10} {:>12.2} {:>9.2} {:>10.1}",
+            report.name,
+            report.requests,
+            report.sent,
+            report.pass_rate() * 100.0,
+            report.passed_metric,
+            report.passed_exploratory,
+            report.passed_boost,
+            report.blocked_qps,
+            report.revenue_retained,
+            report.revenue_retained_rate() * 100.0,
+            report.qps_saved()
+        );
+    }
+}
+
+/// Entry point of `rex simulate-shaping <auction_log.jsonl> <scenarios.yaml>`
+///
+/// Replays the auction log once per scenario and prints the resulting
+/// pass rate, revenue retained and QPS saved for each side by side.
+/// Only built with the `simulator` feature, which pauses tokio's clock
+pub fn run(args: &[String]) -> Result<(), Error> {
+    let (log_path, scenarios_path) = match args {
+        [log, scenarios] => (PathBuf::from(log), PathBuf::from(scenarios)),
+        _ => bail!("Usage: rex simulate-shaping <auction_log.jsonl> <scenarios.yaml>"),
+    };
+
+    let sim_config = SimulationConfig::load(&scenarios_path)?;
+    if sim_config.scenarios.is_empty() {
+        bail!("No scenarios configured in {}", scenarios_path.display());
+    }
+
+    let records = load_records(&log_path)?;
+    println!(
+        "Replaying {} auctions at {} qps across {} scenarios",
+        records.len(),
+        sim_config.target_qps,
+        sim_config.scenarios.len()
+    );
+
+    let mut reports = Vec::with_capacity(sim_config.scenarios.len());
+    for scenario in &sim_config.scenarios {
+        reports.push(run_scenario(&records, scenario, sim_config.target_qps)?);
+    }
+
+    print_reports(&reports);
+
+    Ok(())
+}
@@ -18,4 +18,9 @@ pub trait ClusterDiscovery: Send + Sync {
     /// Registered callback receives a param
     /// of the update cluster size
     fn on_change(&self, cb: Box<dyn Fn(usize) + Send + Sync>);
+
+    /// Returns the reachable addresses, e.g. pod IPs,
+    /// of all other healthy peers, excluding self.
+    /// Impls which cannot address their peers return empty
+    fn peers(&self) -> Vec<String>;
 }
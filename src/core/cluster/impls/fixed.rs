@@ -6,13 +6,26 @@ use crate::core::cluster::ClusterDiscovery;
 /// deployments w/o k8s api
 pub struct FixedClusterDiscovery {
     peer_count: usize,
+    peers: Vec<String>,
 }
 
 impl FixedClusterDiscovery {
     pub fn new(peer_count: usize) -> Self {
         assert!(peer_count > 0, "Cluster size must be > 0");
 
-        Self { peer_count }
+        Self {
+            peer_count,
+            peers: Vec::new(),
+        }
+    }
+
+    /// Static cluster of self plus the provided
+    /// peer addresses, which are never re-resolved
+    pub fn with_peers(peers: Vec<String>) -> Self {
+        Self {
+            peer_count: peers.len() + 1,
+            peers,
+        }
     }
 }
 
@@ -25,4 +38,8 @@ impl ClusterDiscovery for FixedClusterDiscovery {
         // Static config with no changes ever,
         // so nothin' to do
     }
+
+    fn peers(&self) -> Vec<String> {
+        self.peers.clone()
+    }
 }
@@ -1,6 +1,7 @@
 use crate::core::cluster::ClusterDiscovery;
 use anyhow::Result;
 use kube_discovery::{KubeDiscovery, PeerEvent};
+use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
 use tracing::{error, info, trace};
 
@@ -9,6 +10,9 @@ use tracing::{error, info, trace};
 pub struct KubeClusterDiscovery {
     kd: KubeDiscovery,
     callbacks: Arc<RwLock<Vec<Box<dyn Fn(usize) + Send + Sync>>>>,
+    /// Pod IPs of peers seen through discovery events, less self.
+    /// Pod hostnames aren't resolvable without a headless service
+    peers: Arc<RwLock<HashSet<String>>>,
 }
 
 impl KubeClusterDiscovery {
@@ -16,8 +20,11 @@ impl KubeClusterDiscovery {
     pub async fn start() -> Result<KubeClusterDiscovery> {
         let callbacks: Arc<RwLock<Vec<Box<dyn Fn(usize) + Send + Sync>>>> =
             Arc::new(RwLock::new(Vec::new()));
+        let peers: Arc<RwLock<HashSet<String>>> = Arc::new(RwLock::new(HashSet::new()));
 
         let callbacks_clone = callbacks.clone();
+        let peers_clone = peers.clone();
+        let own_hostname = std::env::var("HOSTNAME").unwrap_or_default();
 
         let kd = KubeDiscovery::start_auto(
             |e| error!("Error in k8s peer discovery service: {}", e),
@@ -28,10 +35,22 @@ impl KubeClusterDiscovery {
                             "New peer discovered: {}, cluster size: {}",
                             peer.hostname, total
                         );
+
+                        if peer.hostname != own_hostname {
+                            if let Ok(mut peers) = peers_clone.write() {
+                                peers.insert(peer.ip.to_string());
+                            }
+                        }
+
                         *total
                     }
                     PeerEvent::Removed { peer, total } => {
                         info!("Peer removed: {}, cluster size: {}", peer.hostname, total);
+
+                        if let Ok(mut peers) = peers_clone.write() {
+                            peers.remove(&peer.ip.to_string());
+                        }
+
                         *total
                     }
                 };
@@ -47,7 +66,11 @@ impl KubeClusterDiscovery {
         )
         .await?;
 
-        Ok(Self { kd, callbacks })
+        Ok(Self {
+            kd,
+            callbacks,
+            peers,
+        })
     }
 }
 
@@ -61,4 +84,11 @@ impl ClusterDiscovery for KubeClusterDiscovery {
             cbs.push(cb);
         }
     }
+
+    fn peers(&self) -> Vec<String> {
+        match self.peers.read() {
+            Ok(peers) => peers.iter().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
 }
@@ -1,4 +1,6 @@
 mod discovery;
 pub mod impls;
+mod qps;
 
 pub use discovery::*;
+pub use qps::*;
@@ -0,0 +1,366 @@
+use crate::app::config::QpsLimitMode;
+use crate::core::cluster::ClusterDiscovery;
+use crate::core::models::bidder::Endpoint;
+use anyhow::{Error, anyhow};
+use arc_swap::ArcSwap;
+use dashmap::DashMap;
+use futures_util::future::join_all;
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// Path peers serve their ['QpsUsageReport'] on
+pub const QPS_USAGE_PATH: &str = "/cluster/qps";
+
+/// A node never drops below this fraction of its static
+/// share, so an idle node can still observe rising demand
+const MIN_SHARE_DIVISOR: usize = 10;
+
+/// Recent per endpoint demand observed by a single node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QpsUsageReport {
+    /// Endpoint stable id -> requests per second which reached
+    /// the QPS limiter on this node, before any throttling
+    pub endpoints: HashMap<String, f64>,
+}
+
+#[derive(Debug, Default)]
+struct CoordinationState {
+    /// Our own demand rates as of the last cycle
+    local: QpsUsageReport,
+    /// Demand rates summed across every peer, less self
+    peers: HashMap<String, f64>,
+    /// True only if every expected peer reported last cycle
+    coordinated: bool,
+}
+
+/// Derives the node-local share of each endpoint's cluster wide
+/// QPS limit. In static mode this is an even split by cluster size.
+/// In coordinated mode nodes periodically pull each others recent
+/// demand and take a share proportional to their own, falling back
+/// to the static split for any cycle in which a peer is unreachable
+pub struct QpsCoordinator {
+    cluster: Arc<dyn ClusterDiscovery>,
+    /// Demand counters accumulated since the last cycle
+    counts: DashMap<String, AtomicU64>,
+    state: ArcSwap<CoordinationState>,
+    callbacks: RwLock<Vec<Box<dyn Fn() + Send + Sync>>>,
+    task: RwLock<Option<JoinHandle<()>>>,
+}
+
+impl QpsCoordinator {
+    /// Build the coordinator, starting the peer exchange
+    /// in the background if the mode is coordinated
+    ///
+    /// # Arguments
+    /// * 'internal_token' - Bearer token peers require on their usage
+    /// report, required in coordinated mode
+    pub fn start(
+        mode: &QpsLimitMode,
+        cluster: Arc<dyn ClusterDiscovery>,
+        internal_token: Option<String>,
+    ) -> Result<Arc<Self>, Error> {
+        let coordinator = Arc::new(Self {
+            cluster,
+            counts: DashMap::new(),
+            state: ArcSwap::from_pointee(CoordinationState::default()),
+            callbacks: RwLock::new(Vec::new()),
+            task: RwLock::new(None),
+        });
+
+        let (interval, peer_port) = match mode {
+            QpsLimitMode::Static => return Ok(coordinator),
+            QpsLimitMode::Coordinated {
+                interval,
+                peer_port,
+            } => (*interval, *peer_port),
+        };
+
+        let token = internal_token
+            .ok_or_else(|| anyhow!("Coordinated QPS limits require an internal_token"))?;
+
+        let client = reqwest::ClientBuilder::new()
+            .connect_timeout(Duration::from_millis(250))
+            .timeout(interval.min(Duration::from_secs(1)))
+            .no_proxy()
+            .build()
+            .map_err(|e| anyhow!("Failed building qps coordination client: {}", e))?;
+
+        // weak so dropping the coordinator ends the exchange
+        let handle = {
+            let coordinator: Weak<Self> = Arc::downgrade(&coordinator);
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                let mut last_cycle = Instant::now();
+                loop {
+                    ticker.tick().await;
+                    let elapsed = last_cycle.elapsed();
+                    last_cycle = Instant::now();
+
+                    let Some(coordinator) = coordinator.upgrade() else {
+                        debug!("QPS coordinator dropped, ending peer exchange");
+                        break;
+                    };
+
+                    coordinator.cycle(&client, &token, peer_port, elapsed).await;
+                }
+            })
+        };
+
+        *coordinator.task.write() = Some(handle);
+
+        Ok(coordinator)
+    }
+
+    /// Register a callback invoked after every coordination
+    /// cycle, once new shares are available
+    pub fn on_rebalance(&self, cb: Box<dyn Fn() + Send + Sync>) {
+        self.callbacks.write().push(cb);
+    }
+
+    /// Record a callout to the endpoint reaching the QPS limiter,
+    /// regardless of whether it was then throttled
+    pub fn record_demand(&self, endpoint_id: &str) {
+        if let Some(count) = self.counts.get(endpoint_id) {
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        self.counts
+            .entry(endpoint_id.to_string())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Our demand rates as of the last cycle, served to peers
+    pub fn usage_report(&self) -> QpsUsageReport {
+        self.state.load().local.clone()
+    }
+
+    /// The even per node share of the endpoint limit. 0 if unlimited
+    pub fn static_local_qps(endpoint: &Endpoint, cluster_sz: usize) -> u32 {
+        match endpoint.qps {
+            0 => 0,
+            qps => (qps / cluster_sz.max(1)).max(1) as u32,
+        }
+    }
+
+    /// The share of the endpoint limit this node may currently
+    /// send, 0 if the endpoint is unlimited
+    pub fn local_qps(&self, endpoint: &Endpoint, cluster_sz: usize) -> u32 {
+        let static_qps = Self::static_local_qps(endpoint, cluster_sz);
+        if static_qps == 0 {
+            return 0;
+        }
+
+        let state = self.state.load();
+        if !state.coordinated {
+            return static_qps;
+        }
+
+        let id = endpoint.stable_id();
+        let local = state.local.endpoints.get(id).copied().unwrap_or(0.0);
+        let peers = state.peers.get(id).copied().unwrap_or(0.0);
+
+        proportional_share(endpoint.qps, cluster_sz, static_qps, local, peers)
+    }
+
+    fn snapshot_local(&self, elapsed: Duration) -> QpsUsageReport {
+        let secs = elapsed.as_secs_f64().max(0.001);
+        let mut report = QpsUsageReport::default();
+
+        for entry in self.counts.iter() {
+            let count = entry.value().swap(0, Ordering::Relaxed);
+            report
+                .endpoints
+                .insert(entry.key().clone(), count as f64 / secs);
+        }
+
+        report
+    }
+
+    async fn fetch_peer(
+        client: &Client,
+        token: &str,
+        url: String,
+    ) -> Result<QpsUsageReport, Error> {
+        let res = client
+            .get(&url)
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Peer qps report {} failed: {}", url, e))?;
+
+        if !res.status().is_success() {
+            return Err(anyhow!("Peer qps report {} status {}", url, res.status()));
+        }
+
+        let bytes = res
+            .bytes()
+            .await
+            .map_err(|e| anyhow!("Peer qps report {} body failed: {}", url, e))?;
+
+        serde_json::from_slice(&bytes)
+            .map_err(|e| anyhow!("Peer qps report {} invalid: {}", url, e))
+    }
+
+    async fn cycle(&self, client: &Client, token: &str, peer_port: u16, elapsed: Duration) {
+        let local = self.snapshot_local(elapsed);
+        let peer_hosts = self.cluster.peers();
+        let expected_peers = self.cluster.cluster_size().saturating_sub(1);
+
+        let mut peers: HashMap<String, f64> = HashMap::new();
+        let mut coordinated = !peer_hosts.is_empty() && peer_hosts.len() >= expected_peers;
+
+        if coordinated {
+            let futs = peer_hosts
+                .iter()
+                .map(|host| Self::fetch_peer(client, token, peer_url(host, peer_port)));
+
+            for result in join_all(futs).await {
+                match result {
+                    Ok(report) => {
+                        for (endpoint_id, qps) in report.endpoints {
+                            *peers.entry(endpoint_id).or_default() += qps;
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Falling back to static QPS shares: {}", e);
+                        coordinated = false;
+                    }
+                }
+            }
+        } else {
+            debug!(
+                "Have {} of {} expected peers, using static QPS shares",
+                peer_hosts.len(),
+                expected_peers
+            );
+        }
+
+        self.state.store(Arc::new(CoordinationState {
+            local,
+            peers,
+            coordinated,
+        }));
+
+        for cb in self.callbacks.read().iter() {
+            cb();
+        }
+    }
+}
+
+impl Drop for QpsCoordinator {
+    fn drop(&mut self) {
+        if let Some(handle) = self.task.write().take() {
+            handle.abort();
+        }
+    }
+}
+
+/// Usage report url of a peer address, bracketing ipv6
+fn peer_url(address: &str, peer_port: u16) -> String {
+    if address.contains(':') && !address.starts_with('[') {
+        format!("http://[{}]:{}{}", address, peer_port, QPS_USAGE_PATH)
+    } else {
+        format!("http://{}:{}{}", address, peer_port, QPS_USAGE_PATH)
+    }
+}
+
+/// Share of the cluster wide `limit` proportional to local demand,
+/// never below a fraction of the static share. Every node's floor
+/// is reserved out of the limit before the rest is split by demand,
+/// so the shares across the cluster never sum above the limit.
+/// Static share if no demand was observed anywhere
+fn proportional_share(
+    limit: usize,
+    cluster_sz: usize,
+    static_qps: u32,
+    local: f64,
+    peers: f64,
+) -> u32 {
+    let total = local + peers;
+    if total <= 0.0 {
+        return static_qps;
+    }
+
+    let floor = (static_qps as usize / MIN_SHARE_DIVISOR).max(1);
+    let distributable = limit.saturating_sub(floor * cluster_sz.max(1));
+    let share = (distributable as f64 * (local / total)) as usize;
+
+    (floor + share) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_urls_bracket_ipv6() {
+        assert_eq!(peer_url("10.0.0.7", 80), "http://10.0.0.7:80/cluster/qps");
+        assert_eq!(peer_url("fd00::7", 80), "http://[fd00::7]:80/cluster/qps");
+    }
+
+    #[test]
+    fn share_follows_demand() {
+        // 1000 limit over 4 nodes, 100 reserved for floors,
+        // we see 300 of 1000 total demand
+        assert_eq!(proportional_share(1000, 4, 250, 300.0, 700.0), 295);
+    }
+
+    #[test]
+    fn share_floored_when_idle_locally() {
+        assert_eq!(proportional_share(1000, 4, 250, 0.0, 800.0), 25);
+        assert_eq!(proportional_share(10, 5, 2, 0.0, 800.0), 1);
+    }
+
+    #[test]
+    fn share_static_without_demand() {
+        assert_eq!(proportional_share(1000, 4, 250, 0.0, 0.0), 250);
+    }
+
+    #[test]
+    fn share_leaves_idle_peers_their_floor() {
+        assert_eq!(proportional_share(1000, 4, 250, 500.0, 0.0), 925);
+    }
+
+    #[test]
+    fn shares_never_exceed_limit() {
+        let endpoint = Endpoint {
+            qps: 1000,
+            ..Default::default()
+        };
+
+        for demands in [
+            vec![1000.0, 0.0, 0.0, 0.0],
+            vec![300.0, 700.0, 0.0, 0.0],
+            vec![1.0, 1.0, 1.0, 997.0],
+            vec![250.0, 250.0, 250.0, 250.0],
+        ] {
+            let cluster_sz = demands.len();
+            let static_qps = QpsCoordinator::static_local_qps(&endpoint, cluster_sz);
+            let total: f64 = demands.iter().sum();
+
+            let sum: u32 = demands
+                .iter()
+                .map(|&local| {
+                    proportional_share(endpoint.qps, cluster_sz, static_qps, local, total - local)
+                })
+                .sum();
+
+            assert!(
+                sum as usize <= endpoint.qps,
+                "{:?} sums to {}",
+                demands,
+                sum
+            );
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use crate::app::config::{CurrencyConfig, FxRatesSource};
+use crate::core::models::campaign::{Campaign, PricingStrategy};
+use crate::core::models::deal::{Deal, DealPricing};
+use anyhow::{Error, anyhow};
+use arc_swap::ArcSwap;
+use firestore::FirestoreDb;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// OpenRTB default when no currency is specified
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Normalizes an ISO-4217 code, treating empty as the OpenRTB default
+pub fn normalize_code(code: &str) -> String {
+    let code = code.trim();
+    if code.is_empty() {
+        return DEFAULT_CURRENCY.to_string();
+    }
+
+    code.to_ascii_uppercase()
+}
+
+/// Snapshot of exchange rates relative to a base currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FxRates {
+    pub base: String,
+    /// Currency code -> units of that currency per one unit of base
+    pub rates: HashMap<String, f64>,
+    /// No rates are configured, amounts are passed through unconverted
+    #[serde(skip)]
+    pub passthrough: bool,
+}
+
+impl FxRates {
+    /// Rates which can only convert the base currency to itself
+    pub fn identity(base: &str) -> Self {
+        Self {
+            base: normalize_code(base),
+            rates: HashMap::new(),
+            passthrough: false,
+        }
+    }
+
+    /// Rates which leave every amount as is, whatever its currency
+    pub fn passthrough(base: &str) -> Self {
+        Self {
+            passthrough: true,
+            ..Self::identity(base)
+        }
+    }
+
+    fn per_base(&self, code: &str) -> Option<f64> {
+        if code == self.base {
+            return Some(1.0);
+        }
+
+        self.rates.get(code).copied().filter(|r| *r > 0.0)
+    }
+
+    /// Convert the amount between currencies, None if either is unknown
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        if self.passthrough {
+            return Some(amount);
+        }
+
+        let from = normalize_code(from);
+        let to = normalize_code(to);
+
+        if from == to {
+            return Some(amount);
+        }
+
+        Some(amount / self.per_base(&from)? * self.per_base(&to)?)
+    }
+}
+
+/// Converts prices between currencies and the single accounting currency
+/// every floor, bid, deal price and budget is normalized into. Rates are
+/// loaded from a file or Firestore document and periodically refreshed,
+/// keeping the last good rates if a refresh fails. Without a rates
+/// source prices are passed through unconverted, as they were sent
+pub struct CurrencyService {
+    accounting: String,
+    rates: ArcSwap<FxRates>,
+    task: RwLock<Option<JoinHandle<()>>>,
+    refresh_cbs: RwLock<Vec<Box<dyn Fn() + Send + Sync>>>,
+}
+
+impl CurrencyService {
+    pub async fn start(
+        config: &CurrencyConfig,
+        firestore: Option<Arc<FirestoreDb>>,
+    ) -> Result<Arc<Self>, Error> {
+        let accounting = normalize_code(&config.accounting);
+
+        let rates = match &config.rates {
+            FxRatesSource::None => FxRates::passthrough(&accounting),
+            _ => FxRates::identity(&accounting),
+        };
+
+        let service = Arc::new(Self {
+            rates: ArcSwap::from_pointee(rates),
+            accounting,
+            task: RwLock::new(None),
+            refresh_cbs: RwLock::new(Vec::new()),
+        });
+
+        let (loader, refresh) = match &config.rates {
+            FxRatesSource::None => {
+                info!("No FX rates configured, prices are passed through unconverted");
+                return Ok(service);
+            }
+            FxRatesSource::File { path, refresh } => (RatesLoader::File(path.clone()), *refresh),
+            FxRatesSource::Firestore {
+                collection,
+                document,
+                refresh,
+            } => {
+                let db = firestore.ok_or_else(|| {
+                    anyhow!("Firestore FX rates configured but Firestore is not enabled")
+                })?;
+
+                let loader = RatesLoader::Firestore {
+                    db,
+                    collection: collection.clone(),
+                    document: document.clone(),
+                };
+
+                (loader, *refresh)
+            }
+        };
+
+        // first load must succeed so we never run on stale defaults
+        service.rates.store(Arc::new(loader.load().await?));
+
+        // weak so dropping the service ends the refresh
+        let handle = {
+            let service = Arc::downgrade(&service);
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(refresh);
+                ticker.tick().await;
+
+                loop {
+                    ticker.tick().await;
+
+                    let rates = loader.load().await;
+
+                    let Some(service) = Weak::upgrade(&service) else {
+                        break;
+                    };
+
+                    match rates {
+                        Ok(rates) => {
+                            debug!("Refreshed {} FX rates", rates.rates.len());
+                            service.rates.store(Arc::new(rates));
+
+                            for cb in service.refresh_cbs.read().iter() {
+                                cb();
+                            }
+                        }
+                        Err(e) => warn!("FX rate refresh failed, keeping last rates: {}", e),
+                    }
+                }
+            })
+        };
+
+        *service.task.write() = Some(handle);
+
+        Ok(service)
+    }
+
+    /// Register a callback fired after every successful rate refresh,
+    /// used to re-convert prices which were normalized at load
+    pub fn on_refresh(&self, cb: Box<dyn Fn() + Send + Sync>) {
+        self.refresh_cbs.write().push(cb);
+    }
+
+    /// The currency all internal prices and budgets are held in
+    pub fn accounting(&self) -> &str {
+        &self.accounting
+    }
+
+    /// False when no rates are configured and prices pass through
+    pub fn converts(&self) -> bool {
+        !self.rates.load().passthrough
+    }
+
+    /// The current rates snapshot
+    pub fn rates(&self) -> Arc<FxRates> {
+        self.rates.load_full()
+    }
+
+    /// Convert an amount in `from` into the accounting currency
+    pub fn to_accounting(&self, amount: f64, from: &str) -> Option<f64> {
+        self.rates.load().convert(amount, from, &self.accounting)
+    }
+
+    /// Convert an accounting currency amount into `to`
+    pub fn from_accounting(&self, amount: f64, to: &str) -> Option<f64> {
+        self.rates.load().convert(amount, &self.accounting, to)
+    }
+
+    /// Convert an amount in an optional model currency, e.g. a deal price
+    /// or campaign budget, None meaning it is already in accounting currency
+    pub fn model_to_accounting(&self, amount: f64, currency: Option<&str>) -> Option<f64> {
+        match currency {
+            None => Some(amount),
+            Some(currency) => self.to_accounting(amount, currency),
+        }
+    }
+
+    /// Rewrite the deal pricing into accounting currency,
+    /// None if the deal currency cannot be converted
+    pub fn normalize_deal(&self, mut deal: Deal) -> Option<Deal> {
+        let currency = deal.currency.take();
+        let currency = currency.as_deref();
+
+        deal.pricing = match deal.pricing {
+            DealPricing::Inherit => DealPricing::Inherit,
+            DealPricing::Floor(p) => DealPricing::Floor(self.model_to_accounting(p, currency)?),
+            DealPricing::Fixed(p) => DealPricing::Fixed(self.model_to_accounting(p, currency)?),
+        };
+
+        Some(deal)
+    }
+
+    /// Rewrite the campaign budget and strategy price into
+    /// accounting currency, None if the campaign currency
+    /// cannot be converted
+    pub fn normalize_campaign(&self, mut campaign: Campaign) -> Option<Campaign> {
+        let currency = campaign.currency.take();
+        let currency = currency.as_deref();
+
+        campaign.budget = self.model_to_accounting(campaign.budget, currency)?;
+        campaign.strategy = match campaign.strategy {
+            PricingStrategy::FixedPrice(p) => {
+                PricingStrategy::FixedPrice(self.model_to_accounting(p, currency)?)
+            }
+        };
+
+        Some(campaign)
+    }
+}
+
+impl Drop for CurrencyService {
+    fn drop(&mut self) {
+        if let Some(handle) = self.task.write().take() {
+            handle.abort();
+        }
+    }
+}
+
+enum RatesLoader {
+    File(PathBuf),
+    Firestore {
+        db: Arc<FirestoreDb>,
+        collection: String,
+        document: String,
+    },
+}
+
+impl RatesLoader {
+    async fn load(&self) -> Result<FxRates, Error> {
+        let mut rates = match self {
+            RatesLoader::File(path) => {
+                let data = tokio::fs::read(path)
+                    .await
+                    .map_err(|e| anyhow!("Failed reading FX rates {}: {}", path.display(), e))?;
+
+                serde_json::from_slice::<FxRates>(&data)
+                    .map_err(|e| anyhow!("Invalid FX rates file {}: {}", path.display(), e))?
+            }
+            RatesLoader::Firestore {
+                db,
+                collection,
+                document,
+            } => db
+                .fluent()
+                .select()
+                .by_id_in(collection.as_str())
+                .obj::<FxRates>()
+                .one(document.as_str())
+                .await
+                .map_err(|e| anyhow!("Failed reading FX rates document: {}", e))?
+                .ok_or_else(|| anyhow!("FX rates document {}/{} missing", collection, document))?,
+        };
+
+        rates.base = normalize_code(&rates.base);
+        rates.rates = rates
+            .rates
+            .into_iter()
+            .map(|(code, rate)| (normalize_code(&code), rate))
+            .collect();
+
+        Ok(rates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates() -> FxRates {
+        FxRates {
+            base: "USD".to_string(),
+            rates: HashMap::from([("EUR".to_string(), 0.5), ("GBP".to_string(), 0.25)]),
+            passthrough: false,
+        }
+    }
+
+    #[test]
+    fn converts_through_base() {
+        let rates = rates();
+
+        assert_eq!(rates.convert(10.0, "EUR", "USD"), Some(20.0));
+        assert_eq!(rates.convert(10.0, "USD", "EUR"), Some(5.0));
+        assert_eq!(rates.convert(10.0, "EUR", "GBP"), Some(5.0));
+    }
+
+    #[test]
+    fn empty_code_is_usd() {
+        assert_eq!(rates().convert(3.0, "", "usd"), Some(3.0));
+    }
+
+    #[test]
+    fn unknown_currency_fails() {
+        assert_eq!(rates().convert(3.0, "JPY", "USD"), None);
+        assert_eq!(FxRates::identity("USD").convert(3.0, "EUR", "USD"), None);
+    }
+
+    #[test]
+    fn passthrough_leaves_amounts() {
+        let rates = FxRates::passthrough("USD");
+
+        assert_eq!(rates.convert(3.0, "JPY", "USD"), Some(3.0));
+        assert_eq!(rates.convert(3.0, "USD", "EUR"), Some(3.0));
+    }
+}
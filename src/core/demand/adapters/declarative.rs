@@ -0,0 +1,227 @@
+use crate::core::demand::adapters::BidderAdapter;
+use crate::core::models::bidder::{Bidder, Endpoint};
+use anyhow::Error;
+use rtb::BidRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Request fields a declarative adapter can strip
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StripField {
+    DeviceIfa,
+    DeviceGeo,
+    UserData,
+    ImpPmp,
+}
+
+/// Value of a custom request ext key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExtValue {
+    Bool(bool),
+    String(String),
+}
+
+/// A single declarative request transform, applied in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RequestTransform {
+    /// Move the schain into source.ext.schain
+    SchainToExt,
+    /// Move the schain into source.schain
+    SchainToSource,
+    /// Clear the listed fields
+    Strip { fields: Vec<StripField> },
+    /// Set a custom key on the top level request ext
+    SetExt { key: String, value: ExtValue },
+    /// Replace imp.tagid values found in the map, others untouched
+    RemapTagid { tagids: HashMap<String, String> },
+}
+
+/// Config for an adapter built entirely from request transforms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeclarativeAdapterConfig {
+    /// Name bidders select the adapter by
+    pub name: String,
+    pub transforms: Vec<RequestTransform>,
+}
+
+/// Adapter applying a config driven list of ['RequestTransform']
+pub struct DeclarativeAdapter {
+    config: DeclarativeAdapterConfig,
+}
+
+impl DeclarativeAdapter {
+    pub fn new(config: DeclarativeAdapterConfig) -> Self {
+        Self { config }
+    }
+
+    fn apply(transform: &RequestTransform, req: &mut BidRequest) {
+        match transform {
+            RequestTransform::SchainToExt => {
+                let Some(source) = req.source.as_mut() else {
+                    return;
+                };
+
+                if let Some(schain) = source.schain.take() {
+                    #[allow(deprecated)]
+                    {
+                        source.ext.get_or_insert_with(Default::default).schain = Some(schain);
+                    }
+                }
+            }
+            RequestTransform::SchainToSource => {
+                let Some(source) = req.source.as_mut() else {
+                    return;
+                };
+
+                #[allow(deprecated)]
+                let ext_schain = source.ext.as_mut().and_then(|ext| ext.schain.take());
+
+                if let Some(schain) = ext_schain {
+                    source.schain = Some(schain);
+                }
+            }
+            RequestTransform::Strip { fields } => {
+                for field in fields {
+                    match field {
+                        StripField::DeviceIfa => {
+                            if let Some(device) = req.device.as_mut() {
+                                device.ifa.clear();
+                            }
+                        }
+                        StripField::DeviceGeo => {
+                            if let Some(device) = req.device.as_mut() {
+                                device.geo = None;
+                            }
+                        }
+                        StripField::UserData => {
+                            if let Some(user) = req.user.as_mut() {
+                                user.data.clear();
+                            }
+                        }
+                        StripField::ImpPmp => {
+                            for imp in req.imp.iter_mut() {
+                                imp.pmp = None;
+                            }
+                        }
+                    }
+                }
+            }
+            RequestTransform::SetExt { key, value } => {
+                let ext = req.ext.get_or_insert_with(Default::default);
+
+                match value {
+                    ExtValue::Bool(b) => ext.custom_mut().insert_bool(key.clone(), *b),
+                    ExtValue::String(s) => ext.custom_mut().insert_string(key.clone(), s.clone()),
+                };
+            }
+            RequestTransform::RemapTagid { tagids } => {
+                for imp in req.imp.iter_mut() {
+                    if let Some(tagid) = tagids.get(&imp.tagid) {
+                        imp.tagid = tagid.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl BidderAdapter for DeclarativeAdapter {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn adapt_request(
+        &self,
+        _bidder: &Bidder,
+        _endpoint: &Endpoint,
+        req: &mut BidRequest,
+    ) -> Result<(), Error> {
+        for transform in &self.config.transforms {
+            Self::apply(transform, req);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rtb::BidRequestBuilder;
+    use rtb::bid_request::{ImpBuilder, SourceBuilder, SupplyChainBuilder};
+
+    fn adapt(transforms: Vec<RequestTransform>, req: &mut BidRequest) {
+        let adapter = DeclarativeAdapter::new(DeclarativeAdapterConfig {
+            name: "test".to_string(),
+            transforms,
+        });
+
+        adapter
+            .adapt_request(&Bidder::default(), &Endpoint::default(), req)
+            .unwrap();
+    }
+
+    #[test]
+    fn schain_moves_to_ext_and_back() {
+        let schain = SupplyChainBuilder::default()
+            .complete(true)
+            .ver("1.0")
+            .build()
+            .unwrap();
+
+        let mut req = BidRequestBuilder::default()
+            .source(SourceBuilder::default().schain(schain).build().unwrap())
+            .build()
+            .unwrap();
+
+        adapt(vec![RequestTransform::SchainToExt], &mut req);
+
+        let source = req.source.as_ref().unwrap();
+        assert!(source.schain.is_none());
+        #[allow(deprecated)]
+        let moved = source.ext.as_ref().and_then(|ext| ext.schain.as_ref());
+        assert!(moved.is_some());
+
+        adapt(vec![RequestTransform::SchainToSource], &mut req);
+
+        assert!(req.source.as_ref().unwrap().schain.is_some());
+    }
+
+    #[test]
+    fn tagid_remapped_and_pmp_stripped() {
+        let mut req = BidRequestBuilder::default()
+            .imp(vec![
+                ImpBuilder::default()
+                    .id("1".to_string())
+                    .tagid("ours".to_string())
+                    .build()
+                    .unwrap(),
+                ImpBuilder::default()
+                    .id("2".to_string())
+                    .tagid("other".to_string())
+                    .build()
+                    .unwrap(),
+            ])
+            .build()
+            .unwrap();
+
+        adapt(
+            vec![
+                RequestTransform::RemapTagid {
+                    tagids: HashMap::from([("ours".to_string(), "theirs".to_string())]),
+                },
+                RequestTransform::Strip {
+                    fields: vec![StripField::ImpPmp],
+                },
+            ],
+            &mut req,
+        );
+
+        assert_eq!(req.imp[0].tagid, "theirs");
+        assert_eq!(req.imp[1].tagid, "other");
+        assert!(req.imp.iter().all(|imp| imp.pmp.is_none()));
+    }
+}
@@ -0,0 +1,107 @@
+mod declarative;
+
+pub use declarative::*;
+
+use crate::core::models::bidder::{Bidder, Endpoint};
+use anyhow::{Error, bail};
+use rtb::{BidRequest, BidResponse};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Name of the adapter used by bidders which don't select one
+pub const PASSTHROUGH_ADAPTER: &str = "passthrough";
+
+/// Partner specific request and response shaping, selected per
+/// ['Bidder'] by name. Request hooks run against the callout owned
+/// request right before it is sent, response hooks run against the
+/// decoded response before it is evaluated. Both default to no-ops
+pub trait BidderAdapter: Send + Sync {
+    /// The name bidders select this adapter by
+    fn name(&self) -> &str;
+
+    fn adapt_request(
+        &self,
+        _bidder: &Bidder,
+        _endpoint: &Endpoint,
+        _req: &mut BidRequest,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn adapt_response(
+        &self,
+        _bidder: &Bidder,
+        _endpoint: &Endpoint,
+        _res: &mut BidResponse,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Sends and receives everything as is
+pub struct PassthroughAdapter;
+
+impl BidderAdapter for PassthroughAdapter {
+    fn name(&self) -> &str {
+        PASSTHROUGH_ADAPTER
+    }
+}
+
+/// Holds every adapter registered at startup, built in code
+/// adapters as well as declarative adapters from config
+pub struct AdapterRegistry {
+    adapters: HashMap<String, Arc<dyn BidderAdapter>>,
+    passthrough: Arc<dyn BidderAdapter>,
+}
+
+impl AdapterRegistry {
+    pub fn new() -> Self {
+        let passthrough: Arc<dyn BidderAdapter> = Arc::new(PassthroughAdapter);
+
+        let mut adapters = HashMap::new();
+        adapters.insert(PASSTHROUGH_ADAPTER.to_string(), passthrough.clone());
+
+        Self {
+            adapters,
+            passthrough,
+        }
+    }
+
+    /// Register an adapter, failing if the name is already taken
+    pub fn register(&mut self, adapter: Arc<dyn BidderAdapter>) -> Result<(), Error> {
+        let name = adapter.name().to_string();
+        if self.adapters.contains_key(&name) {
+            bail!("Bidder adapter {} registered more than once", name);
+        }
+
+        self.adapters.insert(name, adapter);
+
+        Ok(())
+    }
+
+    /// Fails if the bidder selects an adapter which isn't registered
+    pub fn validate(&self, bidder: &Bidder) -> Result<(), Error> {
+        match bidder.adapter.as_deref() {
+            None | Some("") => Ok(()),
+            Some(name) if self.adapters.contains_key(name) => Ok(()),
+            Some(name) => bail!(
+                "Bidder {} selects unknown adapter {}, using passthrough",
+                bidder.name,
+                name
+            ),
+        }
+    }
+
+    /// The adapter selected by the bidder, passthrough if none is
+    /// selected or the selected adapter isn't registered. Unknown
+    /// names are reported by ['AdapterRegistry::validate'] when
+    /// bidders load rather than here per request
+    pub fn adapter_for(&self, bidder: &Bidder) -> Arc<dyn BidderAdapter> {
+        bidder
+            .adapter
+            .as_deref()
+            .and_then(|name| self.adapters.get(name))
+            .unwrap_or(&self.passthrough)
+            .clone()
+    }
+}
@@ -1,11 +1,13 @@
 use crate::core::demand::encoding::{RequestEncoder, ResponseDecoder};
-use crate::core::models::bidder::{Bidder, Endpoint, HttpProto};
+use crate::core::managers::{DemandChange, DemandManager};
+use crate::core::models::bidder::{Bidder, Endpoint, EndpointHeader, EndpointTls, HttpProto};
 use anyhow::anyhow;
+use dashmap::DashMap;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use reqwest::{Client, StatusCode, redirect, retry};
+use reqwest::{Client, Identity, StatusCode, redirect, retry};
 use rtb::{BidRequest, BidResponse};
-use std::sync::{Arc, OnceLock};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::debug;
 
 pub struct DemandResponse {
@@ -14,16 +16,52 @@ pub struct DemandResponse {
     pub response: Option<BidResponse>,
 }
 
+/// Resolved endpoint headers are re-read after this long,
+/// so rotated secrets are picked up without a restart
+const HEADER_REFRESH: Duration = Duration::from_secs(5 * 60);
+
+/// Failed client builds, e.g. an unreadable mTLS identity, are
+/// retried after a backoff doubling from the first up to the max
+const CLIENT_RETRY_FIRST: Duration = Duration::from_secs(1);
+const CLIENT_RETRY_MAX: Duration = Duration::from_secs(5 * 60);
+
+/// Each distinct protocol and TLS configuration gets its own client
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    proto: HttpProto,
+    tls: EndpointTls,
+}
+
+struct ResolvedHeaders {
+    source: Vec<EndpointHeader>,
+    headers: HeaderMap,
+    resolved_at: Instant,
+}
+
+struct FailedClient {
+    error: String,
+    attempts: u32,
+    retry_at: Instant,
+}
+
+impl FailedClient {
+    fn backoff(attempts: u32) -> Duration {
+        CLIENT_RETRY_FIRST
+            .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+            .min(CLIENT_RETRY_MAX)
+    }
+}
+
 pub struct DemandClient {
-    h1_client: OnceLock<Client>,
-    h2c_client: OnceLock<Client>,
-    h2_client: OnceLock<Client>,
+    clients: DashMap<ClientKey, Client>,
+    failures: DashMap<ClientKey, FailedClient>,
+    headers: Arc<DashMap<String, ResolvedHeaders>>,
 }
 
 impl DemandClient {
-    fn init_client(proto: HttpProto) -> Result<Client, anyhow::Error> {
+    fn init_client(key: &ClientKey) -> Result<Client, anyhow::Error> {
         let mut client_builder = reqwest::ClientBuilder::new()
-            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_certs(key.tls.insecure_skip_verify)
             .user_agent("ad-client")
             .connect_timeout(Duration::from_millis(500))
             .pool_max_idle_per_host(128)
@@ -33,13 +71,23 @@ impl DemandClient {
             .referer(false)
             .redirect(redirect::Policy::none())
             .no_proxy()
-            .timeout(Duration::from_secs(1))
             .tcp_nodelay(true)
             .deflate(true)
             .gzip(true)
             .hickory_dns(true);
 
-        client_builder = match proto {
+        if let Some(identity) = &key.tls.identity {
+            let mut pem = identity.cert.resolve()?.into_bytes();
+            pem.push(b'\n');
+            pem.extend_from_slice(identity.key.resolve()?.as_bytes());
+
+            let identity = Identity::from_pem(&pem)
+                .map_err(|e| anyhow!("Invalid mTLS client identity: {}", e))?;
+
+            client_builder = client_builder.identity(identity);
+        }
+
+        client_builder = match key.proto {
             HttpProto::Http1 => client_builder
                 .http1_only()
                 .http1_ignore_invalid_headers_in_responses(true),
@@ -52,16 +100,149 @@ impl DemandClient {
         client_builder.build().map_err(anyhow::Error::from)
     }
 
-    /// Create a new demand cliet which will eagerly create underlying
-    /// http clients to afford graceful failure on startup
+    /// Create a new demand cliet which will eagerly create the default
+    /// strict TLS http clients to afford graceful failure on startup.
+    /// Clients for endpoint specific TLS identities are created lazily
     pub fn new() -> Result<Self, anyhow::Error> {
+        let clients = DashMap::new();
+
+        for proto in [HttpProto::Http1, HttpProto::H2c, HttpProto::Http2] {
+            let key = ClientKey {
+                proto,
+                tls: EndpointTls::default(),
+            };
+
+            let client = Self::init_client(&key)?;
+            clients.insert(key, client);
+        }
+
         Ok(DemandClient {
-            h1_client: OnceLock::from(Self::init_client(HttpProto::Http1)?),
-            h2c_client: OnceLock::from(Self::init_client(HttpProto::H2c)?),
-            h2_client: OnceLock::from(Self::init_client(HttpProto::Http2)?),
+            clients,
+            failures: DashMap::new(),
+            headers: Arc::new(DashMap::new()),
         })
     }
 
+    /// Drops cached headers of endpoints which are changed or
+    /// removed, so the cache only holds endpoints still loaded
+    pub fn watch_endpoints(&self, demand_manager: &DemandManager) {
+        let headers = self.headers.clone();
+
+        demand_manager.on_change(Box::new(move |change| {
+            let prev_endpoints = match change {
+                DemandChange::Added { .. } => return,
+                DemandChange::Modified { prev_endpoints, .. }
+                | DemandChange::Removed { prev_endpoints, .. } => prev_endpoints,
+            };
+
+            for endpoint in prev_endpoints {
+                headers.remove(endpoint.stable_id());
+            }
+        }));
+    }
+
+    fn client_for(&self, endpoint: &Endpoint) -> Result<Client, anyhow::Error> {
+        let key = ClientKey {
+            proto: endpoint.protocol,
+            tls: endpoint.tls.clone(),
+        };
+
+        if let Some(client) = self.clients.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let attempts = match self.failures.get(&key) {
+            Some(failed) if failed.retry_at > Instant::now() => {
+                return Err(anyhow!(
+                    "Failed creating client for {}, retrying in {:?}: {}",
+                    endpoint.name,
+                    failed.retry_at.saturating_duration_since(Instant::now()),
+                    failed.error
+                ));
+            }
+            Some(failed) => failed.attempts,
+            None => 0,
+        };
+
+        debug!(
+            "Creating demand client for endpoint {} tls config",
+            endpoint.name
+        );
+
+        match Self::init_client(&key) {
+            Ok(client) => {
+                self.failures.remove(&key);
+                Ok(self.clients.entry(key).or_insert(client).clone())
+            }
+            Err(e) => {
+                let attempts = attempts + 1;
+                let error = e.to_string();
+
+                self.failures.insert(
+                    key,
+                    FailedClient {
+                        error: error.clone(),
+                        attempts,
+                        retry_at: Instant::now() + FailedClient::backoff(attempts),
+                    },
+                );
+
+                Err(anyhow!(
+                    "Failed creating client for {}: {}",
+                    endpoint.name,
+                    error
+                ))
+            }
+        }
+    }
+
+    fn resolve_headers(endpoint: &Endpoint) -> Result<HeaderMap, anyhow::Error> {
+        let mut headers = HeaderMap::new();
+
+        for header in &endpoint.headers {
+            let key = HeaderName::from_bytes(header.name.as_bytes())
+                .map_err(|e| anyhow!("Invalid header name {}: {}", header.name, e))?;
+
+            let value = format!("{}{}", header.prefix, header.value.resolve()?);
+            let mut value = HeaderValue::from_str(&value)
+                .map_err(|e| anyhow!("Invalid header value for {}: {}", header.name, e))?;
+
+            value.set_sensitive(true);
+            headers.insert(key, value);
+        }
+
+        Ok(headers)
+    }
+
+    /// The endpoint static headers with secrets resolved, cached
+    /// until the endpoint headers change or the cache goes stale
+    fn endpoint_headers(&self, endpoint: &Endpoint) -> Result<HeaderMap, anyhow::Error> {
+        if endpoint.headers.is_empty() {
+            return Ok(HeaderMap::new());
+        }
+
+        let id = endpoint.stable_id();
+
+        if let Some(cached) = self.headers.get(id) {
+            if cached.source == endpoint.headers && cached.resolved_at.elapsed() < HEADER_REFRESH {
+                return Ok(cached.headers.clone());
+            }
+        }
+
+        let headers = Self::resolve_headers(endpoint)?;
+
+        self.headers.insert(
+            id.to_string(),
+            ResolvedHeaders {
+                source: endpoint.headers.clone(),
+                headers: headers.clone(),
+                resolved_at: Instant::now(),
+            },
+        );
+
+        Ok(headers)
+    }
+
     /// Send a demand bid request. If a non 200 status code is
     /// returned, the client will immediately return and skip
     /// reading the body (if any present)
@@ -77,20 +258,17 @@ impl DemandClient {
         endpoint: Arc<Endpoint>,
         req: &BidRequest,
     ) -> Result<DemandResponse, anyhow::Error> {
-        let client = match endpoint.protocol {
-            HttpProto::Http1 => self.h1_client.get(),
-            HttpProto::H2c => self.h2c_client.get(),
-            HttpProto::Http2 => self.h2_client.get(),
-        }
-        .expect("Client should never be missing");
+        let client = self.client_for(&endpoint)?;
 
         if tracing::event_enabled!(tracing::Level::TRACE) {
             tracing::trace!("{}", serde_json::to_string(&req)?);
         }
 
-        let request_encoding = RequestEncoder::encode(req, &endpoint.encoding, bidder.gzip)?;
+        let request_encoding =
+            RequestEncoder::encode(req, &endpoint.encoding, &endpoint.ortb_version, bidder.gzip)?;
 
-        let mut headers = HeaderMap::new();
+        // encoding headers go last so endpoint headers can't clobber them
+        let mut headers = self.endpoint_headers(&endpoint)?;
         for header in request_encoding.headers {
             let key = HeaderName::from_static(header.key);
             let value = HeaderValue::from_str(&header.value)
@@ -138,7 +316,8 @@ impl DemandClient {
             .await
             .map_err(|e| anyhow!("Failed to read http response for {}: {}", endpoint.name, e))?;
 
-        let bid_response = ResponseDecoder::decode(&endpoint.encoding, &bytes)?;
+        let bid_response =
+            ResponseDecoder::decode(&endpoint.encoding, &endpoint.ortb_version, &bytes)?;
 
         Ok(DemandResponse {
             status_code,
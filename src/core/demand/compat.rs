@@ -0,0 +1,156 @@
+//! OpenRTB 2.5 wire compatibility. Requests are modelled as 2.6, so
+//! for legacy endpoints the encoded json is downgraded by moving
+//! fields which only gained a top level home in 2.6 back under
+//! their 2.5 `ext` locations, and 2.5 style responses are upgraded
+use serde_json::{Map, Value};
+
+/// (object path, field) pairs moved under `<object>.ext.<field>`
+const DOWNGRADE_TO_EXT: &[(&str, &str)] = &[
+    ("regs", "gdpr"),
+    ("regs", "us_privacy"),
+    ("user", "consent"),
+    ("user", "eids"),
+    ("source", "schain"),
+];
+
+fn move_into_ext(obj: &mut Map<String, Value>, field: &str) {
+    let Some(value) = obj.remove(field) else {
+        return;
+    };
+
+    // 2.5 consumers expect gdpr as an int flag
+    let value = match value {
+        Value::Bool(b) => Value::from(b as u8),
+        other => other,
+    };
+
+    let ext = obj
+        .entry("ext")
+        .or_insert_with(|| Value::Object(Map::new()));
+
+    match ext {
+        Value::Object(ext) => {
+            ext.entry(field).or_insert(value);
+        }
+        _ => *ext = Value::Object(Map::from_iter([(field.to_string(), value)])),
+    }
+}
+
+/// Rewrite a 2.6 shaped json request into 2.5 ext locations
+pub fn downgrade_request(req: &mut Value) {
+    let Some(req) = req.as_object_mut() else {
+        return;
+    };
+
+    for (object, field) in DOWNGRADE_TO_EXT {
+        if let Some(Value::Object(obj)) = req.get_mut(*object) {
+            move_into_ext(obj, field);
+        }
+    }
+}
+
+/// 2.5 `ext.prebid.type` media type to 2.6 `mtype`
+fn mtype_from_ext(bid: &Map<String, Value>) -> Option<u8> {
+    let kind = bid.get("ext")?.get("prebid")?.get("type")?.as_str()?;
+
+    match kind {
+        "banner" => Some(1),
+        "video" => Some(2),
+        "audio" => Some(3),
+        "native" => Some(4),
+        _ => None,
+    }
+}
+
+fn upgrade_bid(bid: &mut Map<String, Value>) {
+    if !bid.contains_key("mtype") {
+        if let Some(mtype) = mtype_from_ext(bid) {
+            bid.insert("mtype".to_string(), Value::from(mtype));
+        }
+    }
+
+    // 2.5 single `api` became the `apis` list
+    if !bid.contains_key("apis") {
+        if let Some(api) = bid.remove("api") {
+            if api.is_number() {
+                bid.insert("apis".to_string(), Value::Array(vec![api]));
+            }
+        }
+    }
+}
+
+/// Rewrite a 2.5 shaped json response into 2.6 locations
+pub fn upgrade_response(res: &mut Value) {
+    let Some(seatbids) = res.get_mut("seatbid").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for seatbid in seatbids {
+        let Some(bids) = seatbid.get_mut("bid").and_then(Value::as_array_mut) else {
+            continue;
+        };
+
+        for bid in bids.iter_mut().filter_map(Value::as_object_mut) {
+            upgrade_bid(bid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn request_fields_move_to_ext() {
+        let mut req = json!({
+            "id": "1",
+            "regs": {"gdpr": true, "ext": {"other": 1}},
+            "user": {"id": "u", "consent": "CO123", "eids": [{"source": "x.com"}]},
+            "source": {"tid": "t", "schain": {"complete": 1}},
+        });
+
+        downgrade_request(&mut req);
+
+        assert_eq!(
+            req,
+            json!({
+                "id": "1",
+                "regs": {"ext": {"other": 1, "gdpr": 1}},
+                "user": {"id": "u", "ext": {"consent": "CO123", "eids": [{"source": "x.com"}]}},
+                "source": {"tid": "t", "ext": {"schain": {"complete": 1}}},
+            })
+        );
+    }
+
+    #[test]
+    fn existing_ext_value_wins() {
+        let mut req = json!({"user": {"consent": "new", "ext": {"consent": "old"}}});
+
+        downgrade_request(&mut req);
+
+        assert_eq!(req, json!({"user": {"ext": {"consent": "old"}}}));
+    }
+
+    #[test]
+    fn response_bids_upgraded() {
+        let mut res = json!({
+            "seatbid": [{"bid": [
+                {"id": "1", "api": 7, "ext": {"prebid": {"type": "video"}}},
+                {"id": "2", "mtype": 1, "apis": [3]},
+            ]}]
+        });
+
+        upgrade_response(&mut res);
+
+        assert_eq!(
+            res,
+            json!({
+                "seatbid": [{"bid": [
+                    {"id": "1", "mtype": 2, "apis": [7], "ext": {"prebid": {"type": "video"}}},
+                    {"id": "2", "mtype": 1, "apis": [3]},
+                ]}]
+            })
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use crate::core::models::bidder::Encoding;
+use crate::core::demand::compat;
+use crate::core::models::bidder::{Encoding, OrtbVersion};
 use anyhow::anyhow;
 use bytes::Bytes;
 use rtb::{BidRequest, BidResponse};
@@ -26,8 +27,16 @@ pub struct RequestEncoder {
 }
 
 impl RequestEncoder {
-    fn encode_json(req: &BidRequest) -> Result<Vec<u8>, anyhow::Error> {
-        serde_json::to_vec(req).map_err(|e| anyhow::Error::from(e))
+    fn encode_json(req: &BidRequest, version: &OrtbVersion) -> Result<Vec<u8>, anyhow::Error> {
+        match version {
+            OrtbVersion::V2_6 => serde_json::to_vec(req).map_err(|e| anyhow::Error::from(e)),
+            OrtbVersion::V2_5 => {
+                let mut value = serde_json::to_value(req)?;
+                compat::downgrade_request(&mut value);
+
+                serde_json::to_vec(&value).map_err(|e| anyhow::Error::from(e))
+            }
+        }
     }
 
     fn encode_protobuf(req: &BidRequest) -> Result<Vec<u8>, anyhow::Error> {
@@ -52,9 +61,12 @@ impl RequestEncoder {
 
     /// Encodes the given request to a byte array and associated any required headers
     /// such as content type
+    /// Protobuf is always sent as modelled, the ortb version only
+    /// affects the json wire shape
     pub fn encode(
         req: &BidRequest,
         encoding: &Encoding,
+        version: &OrtbVersion,
         gzip: bool,
     ) -> Result<Self, anyhow::Error> {
         let mut headers = Vec::new();
@@ -66,7 +78,7 @@ impl RequestEncoder {
                     "application/json".into(),
                 ));
 
-                Self::encode_json(req)
+                Self::encode_json(req, version)
             }
             Encoding::Protobuf => {
                 headers.push(Header::new(
@@ -96,14 +108,28 @@ impl ResponseDecoder {
             .map_err(|e| anyhow!("Failed to decode protobuf response: {}", e))
     }
 
-    fn decode_json(bytes: &Bytes) -> Result<BidResponse, anyhow::Error> {
-        serde_json::from_slice(bytes.as_ref())
-            .map_err(|e| anyhow!("Failed decoding json response: {}", e))
+    fn decode_json(bytes: &Bytes, version: &OrtbVersion) -> Result<BidResponse, anyhow::Error> {
+        match version {
+            OrtbVersion::V2_6 => serde_json::from_slice(bytes.as_ref())
+                .map_err(|e| anyhow!("Failed decoding json response: {}", e)),
+            OrtbVersion::V2_5 => {
+                let mut value: serde_json::Value = serde_json::from_slice(bytes.as_ref())
+                    .map_err(|e| anyhow!("Failed decoding json response: {}", e))?;
+                compat::upgrade_response(&mut value);
+
+                serde_json::from_value(value)
+                    .map_err(|e| anyhow!("Failed decoding 2.5 json response: {}", e))
+            }
+        }
     }
 
-    pub fn decode(encoding: &Encoding, data: &Bytes) -> Result<BidResponse, anyhow::Error> {
+    pub fn decode(
+        encoding: &Encoding,
+        version: &OrtbVersion,
+        data: &Bytes,
+    ) -> Result<BidResponse, anyhow::Error> {
         match encoding {
-            Encoding::Json => Self::decode_json(data),
+            Encoding::Json => Self::decode_json(data, version),
             Encoding::Protobuf => Self::decode_protobuf(data),
         }
     }
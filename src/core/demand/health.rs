@@ -0,0 +1,303 @@
+use crate::app::config::CircuitBreakerConfig;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Health relevant classification of a callout result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalloutOutcome {
+    /// Any response from the endpoint, bid or no bid
+    Healthy,
+    /// Connection failure or 5xx response
+    Error,
+    Timeout,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct OutcomeCounts {
+    total: u32,
+    errors: u32,
+    timeouts: u32,
+}
+
+impl OutcomeCounts {
+    fn add(&mut self, outcome: CalloutOutcome) {
+        self.total += 1;
+        match outcome {
+            CalloutOutcome::Healthy => {}
+            CalloutOutcome::Error => self.errors += 1,
+            CalloutOutcome::Timeout => self.timeouts += 1,
+        }
+    }
+
+    fn unhealthy(&self, config: &CircuitBreakerConfig) -> bool {
+        if self.total == 0 {
+            return false;
+        }
+
+        let total = self.total as f32;
+
+        self.errors as f32 / total >= config.error_rate
+            || self.timeouts as f32 / total >= config.timeout_rate
+    }
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    /// All traffic flows, outcomes tallied over the current window
+    Closed {
+        window_start: Instant,
+        counts: OutcomeCounts,
+    },
+    /// Only probe traffic flows, probe outcomes tallied
+    Open {
+        since: Instant,
+        probes: OutcomeCounts,
+    },
+}
+
+impl BreakerState {
+    fn closed(now: Instant) -> Self {
+        BreakerState::Closed {
+            window_start: now,
+            counts: OutcomeCounts::default(),
+        }
+    }
+
+    fn open(now: Instant) -> Self {
+        BreakerState::Open {
+            since: now,
+            probes: OutcomeCounts::default(),
+        }
+    }
+}
+
+/// Tracks callout outcomes per endpoint and trips a circuit breaker
+/// when an endpoint's error or timeout rate crosses the configured
+/// threshold. While open only a small fraction of probe callouts are
+/// allowed, and once enough healthy probes are seen after the open
+/// period, the breaker closes and full traffic resumes
+pub struct EndpointHealthTracker {
+    config: CircuitBreakerConfig,
+    endpoints: DashMap<String, Mutex<BreakerState>>,
+}
+
+impl EndpointHealthTracker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            endpoints: DashMap::new(),
+        }
+    }
+
+    /// Whether a callout to the endpoint should be sent. Always
+    /// true if disabled, or if the endpoint has no history yet
+    pub fn allow(&self, endpoint_id: &str) -> bool {
+        if !self.config.enabled {
+            return true;
+        }
+
+        match self.endpoints.get(endpoint_id) {
+            Some(state) => match &*state.lock() {
+                BreakerState::Closed { .. } => true,
+                BreakerState::Open { .. } => fastrand::f32() < self.config.probe_rate,
+            },
+            None => true,
+        }
+    }
+
+    /// True if the endpoint breaker is currently open
+    pub fn is_open(&self, endpoint_id: &str) -> bool {
+        self.endpoints
+            .get(endpoint_id)
+            .is_some_and(|state| matches!(&*state.lock(), BreakerState::Open { .. }))
+    }
+
+    /// Feed a completed callout outcome for the endpoint
+    pub fn record(&self, endpoint_id: &str, outcome: CalloutOutcome) {
+        if !self.config.enabled {
+            return;
+        }
+
+        self.record_at(endpoint_id, outcome, Instant::now());
+    }
+
+    fn record_at(&self, endpoint_id: &str, outcome: CalloutOutcome, now: Instant) {
+        if let Some(state) = self.endpoints.get(endpoint_id) {
+            return self.transition(endpoint_id, &mut state.lock(), outcome, now);
+        }
+
+        let state = self
+            .endpoints
+            .entry(endpoint_id.to_string())
+            .or_insert_with(|| Mutex::new(BreakerState::closed(now)));
+
+        self.transition(endpoint_id, &mut state.lock(), outcome, now);
+    }
+
+    fn transition(
+        &self,
+        endpoint_id: &str,
+        state: &mut BreakerState,
+        outcome: CalloutOutcome,
+        now: Instant,
+    ) {
+        let config = &self.config;
+
+        match state {
+            BreakerState::Closed {
+                window_start,
+                counts,
+            } => {
+                if now.duration_since(*window_start) >= config.window {
+                    *window_start = now;
+                    *counts = OutcomeCounts::default();
+                }
+
+                counts.add(outcome);
+
+                if counts.total >= config.min_samples && counts.unhealthy(config) {
+                    warn!(
+                        "Circuit breaker opened for endpoint {}: {} errors, {} timeouts of {}",
+                        endpoint_id, counts.errors, counts.timeouts, counts.total
+                    );
+
+                    *state = BreakerState::open(now);
+                }
+            }
+            BreakerState::Open { since, probes } => {
+                probes.add(outcome);
+
+                if now.duration_since(*since) < config.open_for
+                    || probes.total < config.recovery_probes
+                {
+                    return;
+                }
+
+                if probes.unhealthy(config) {
+                    warn!(
+                        "Endpoint {} probes still unhealthy, circuit breaker stays open",
+                        endpoint_id
+                    );
+
+                    *state = BreakerState::open(now);
+                } else {
+                    info!("Circuit breaker closed for endpoint {}", endpoint_id);
+
+                    *state = BreakerState::closed(now);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn tracker() -> EndpointHealthTracker {
+        EndpointHealthTracker::new(CircuitBreakerConfig {
+            enabled: true,
+            min_samples: 10,
+            error_rate: 0.5,
+            timeout_rate: 0.8,
+            window: Duration::from_secs(10),
+            open_for: Duration::from_secs(30),
+            probe_rate: 0.0,
+            recovery_probes: 3,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn trips_on_error_rate() {
+        let tracker = tracker();
+        let now = Instant::now();
+
+        for i in 0..10 {
+            let outcome = match i % 2 {
+                0 => CalloutOutcome::Error,
+                _ => CalloutOutcome::Healthy,
+            };
+            tracker.record_at("ep", outcome, now);
+        }
+
+        assert!(tracker.is_open("ep"));
+        assert!(!tracker.allow("ep"));
+        assert!(tracker.allow("other"));
+    }
+
+    #[test]
+    fn needs_min_samples() {
+        let tracker = tracker();
+        let now = Instant::now();
+
+        for _ in 0..9 {
+            tracker.record_at("ep", CalloutOutcome::Timeout, now);
+        }
+
+        assert!(!tracker.is_open("ep"));
+    }
+
+    #[test]
+    fn window_resets_counts() {
+        let tracker = tracker();
+        let now = Instant::now();
+
+        for _ in 0..9 {
+            tracker.record_at("ep", CalloutOutcome::Timeout, now);
+        }
+
+        let later = now + Duration::from_secs(11);
+        tracker.record_at("ep", CalloutOutcome::Timeout, later);
+
+        assert!(!tracker.is_open("ep"));
+    }
+
+    #[test]
+    fn recovers_after_healthy_probes() {
+        let tracker = tracker();
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            tracker.record_at("ep", CalloutOutcome::Timeout, now);
+        }
+        assert!(tracker.is_open("ep"));
+
+        // probes before open_for elapsed never close it
+        for _ in 0..5 {
+            tracker.record_at("ep", CalloutOutcome::Healthy, now);
+        }
+        assert!(tracker.is_open("ep"));
+
+        let later = now + Duration::from_secs(31);
+        tracker.record_at("ep", CalloutOutcome::Healthy, later);
+
+        assert!(!tracker.is_open("ep"));
+        assert!(tracker.allow("ep"));
+    }
+
+    #[test]
+    fn reopens_on_unhealthy_probes() {
+        let tracker = tracker();
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            tracker.record_at("ep", CalloutOutcome::Error, now);
+        }
+
+        let later = now + Duration::from_secs(31);
+        for _ in 0..3 {
+            tracker.record_at("ep", CalloutOutcome::Error, later);
+        }
+        assert!(tracker.is_open("ep"));
+
+        // open period restarted, so healthy probes right after don't close it
+        for _ in 0..3 {
+            tracker.record_at("ep", CalloutOutcome::Healthy, later);
+        }
+        assert!(tracker.is_open("ep"));
+    }
+}
@@ -0,0 +1,165 @@
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Width of each latency bucket
+const BUCKET_WIDTH_MS: u32 = 5;
+/// Bucket count, latencies beyond 2s land in the last bucket
+const BUCKETS: usize = 400;
+/// Samples required before a percentile is trusted
+const MIN_SAMPLES: u64 = 200;
+/// How often (in samples) the cached percentile is recalculated
+const RECALC_EVERY: u64 = 64;
+/// Sample count at which all buckets are halved, biasing
+/// the histogram toward recent latency
+const DECAY_AT: u64 = 20_000;
+
+/// Rolling, lock free latency histogram for a single endpoint
+struct LatencyHistogram {
+    buckets: Vec<AtomicU64>,
+    samples: AtomicU64,
+    /// Cached p95 in ms, 0 until first calculated
+    p95_ms: AtomicU32,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            samples: AtomicU64::new(0),
+            p95_ms: AtomicU32::new(0),
+        }
+    }
+
+    fn record(&self, latency: Duration) {
+        let idx = (latency.as_millis() as usize / BUCKET_WIDTH_MS as usize).min(BUCKETS - 1);
+        self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+
+        let samples = self.samples.fetch_add(1, Ordering::Relaxed) + 1;
+        if samples % RECALC_EVERY == 0 {
+            self.recalc(samples);
+        }
+    }
+
+    fn recalc(&self, samples: u64) {
+        let mut total = samples;
+
+        if samples >= DECAY_AT {
+            total = 0;
+            for bucket in &self.buckets {
+                let halved = bucket.load(Ordering::Relaxed) / 2;
+                bucket.store(halved, Ordering::Relaxed);
+                total += halved;
+            }
+            self.samples.store(total, Ordering::Relaxed);
+        }
+
+        let target = (total as f64 * 0.95).ceil() as u64;
+        let mut seen = 0;
+
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= target {
+                // upper edge of the bucket, so the p95 is never under reported
+                self.p95_ms
+                    .store((idx as u32 + 1) * BUCKET_WIDTH_MS, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    fn p95_ms(&self) -> Option<u32> {
+        if self.samples.load(Ordering::Relaxed) < MIN_SAMPLES {
+            return None;
+        }
+
+        match self.p95_ms.load(Ordering::Relaxed) {
+            0 => None,
+            p95_ms => Some(p95_ms),
+        }
+    }
+}
+
+/// Tracks observed callout latency per endpoint, so callout
+/// deadlines can adapt to how quickly each endpoint responds
+#[derive(Default)]
+pub struct LatencyTracker {
+    endpoints: DashMap<String, LatencyHistogram>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a completed callout latency. Timeouts should be recorded
+    /// at their deadline so slow endpoints push their own deadline up
+    pub fn record(&self, endpoint_id: &str, latency: Duration) {
+        if let Some(histogram) = self.endpoints.get(endpoint_id) {
+            return histogram.record(latency);
+        }
+
+        self.endpoints
+            .entry(endpoint_id.to_string())
+            .or_insert_with(LatencyHistogram::new)
+            .record(latency);
+    }
+
+    /// The recent p95 latency of the endpoint in ms,
+    /// None until enough callouts have been observed
+    pub fn p95_ms(&self, endpoint_id: &str) -> Option<u32> {
+        self.endpoints.get(endpoint_id).and_then(|h| h.p95_ms())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_percentile_until_min_samples() {
+        let tracker = LatencyTracker::new();
+
+        for _ in 0..(MIN_SAMPLES - 1) {
+            tracker.record("ep", Duration::from_millis(40));
+        }
+
+        assert_eq!(tracker.p95_ms("ep"), None);
+        assert_eq!(tracker.p95_ms("unknown"), None);
+    }
+
+    #[test]
+    fn p95_reflects_tail() {
+        let tracker = LatencyTracker::new();
+
+        // 90% fast, 10% slow -> p95 sits in the slow tail
+        for i in 0..1_024 {
+            let ms = if i % 10 == 0 { 180 } else { 30 };
+            tracker.record("ep", Duration::from_millis(ms));
+        }
+
+        assert_eq!(tracker.p95_ms("ep"), Some(185));
+    }
+
+    #[test]
+    fn p95_all_fast() {
+        let tracker = LatencyTracker::new();
+
+        for _ in 0..1_024 {
+            tracker.record("ep", Duration::from_millis(12));
+        }
+
+        assert_eq!(tracker.p95_ms("ep"), Some(15));
+    }
+
+    #[test]
+    fn decay_keeps_percentile() {
+        let tracker = LatencyTracker::new();
+
+        for _ in 0..(DECAY_AT + RECALC_EVERY) {
+            tracker.record("ep", Duration::from_millis(60));
+        }
+
+        assert_eq!(tracker.p95_ms("ep"), Some(65));
+    }
+}
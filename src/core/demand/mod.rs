@@ -1,4 +1,8 @@
+pub mod adapters;
 pub mod client;
+mod compat;
 mod encoding;
+pub mod health;
+pub mod latency;
 pub mod notifications;
 pub mod takerate;
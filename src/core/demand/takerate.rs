@@ -1,3 +1,30 @@
+use crate::core::models::bidder::Bidder;
+use crate::core::models::deal::Deal;
+use crate::core::models::publisher::Publisher;
+use anyhow::{Error, bail};
+
+/// Highest margin percentage. At 100 the publisher would receive
+/// nothing and ['markup_floor()'] would divide by zero
+pub const MAX_MARGIN: u32 = 99;
+
+/// Margins are rejected at load if they are 100% or more
+pub fn validate_margin(margin: Option<u32>) -> Result<(), Error> {
+    match margin {
+        Some(margin) if margin > MAX_MARGIN => bail!("margin {}% must be below 100%", margin),
+        _ => Ok(()),
+    }
+}
+
+/// Resolve the margin for a bid, layered as the publisher
+/// default, then any bidder override, then any deal override.
+/// Clamped to ['MAX_MARGIN'] should an invalid margin slip through
+pub fn effective_margin(publisher: &Publisher, bidder: &Bidder, deal: Option<&Deal>) -> u32 {
+    deal.and_then(|d| d.margin)
+        .or(bidder.margin)
+        .unwrap_or(publisher.margin)
+        .min(MAX_MARGIN)
+}
+
 /// Apply publisher margin to the gross bid price received from the
 /// demand partner.
 ///
@@ -9,7 +36,7 @@ pub fn markdown_bid(original_bid_price: f64, take_rate: u32) -> f64 {
         return original_bid_price;
     }
 
-    let margin_factor = take_rate as f64 / 100.0;
+    let margin_factor = take_rate.min(MAX_MARGIN) as f64 / 100.0;
 
     original_bid_price - original_bid_price * margin_factor
 }
@@ -26,12 +53,110 @@ pub fn markdown_bid(original_bid_price: f64, take_rate: u32) -> f64 {
 /// - Markup floor: $10.0 / (1 - 10/100) = $10.0 / 0.90 = $11.11
 /// - If bid comes in at $11.11, markdown: $11.11 * 0.10 = $1.11
 /// - Publisher receives: $11.11 - $1.11 = $10.00
+/// Take rates of 100% or more are clamped to ['MAX_MARGIN']
 pub fn markup_floor(original_bid_floor: f64, take_rate: u32) -> f64 {
     if take_rate == 0 {
         return original_bid_floor;
     }
 
-    let margin_factor = take_rate as f64 / 100.0;
+    let margin_factor = take_rate.min(MAX_MARGIN) as f64 / 100.0;
 
     original_bid_floor / (1.0 - margin_factor)
 }
+
+/// The gross floor sent to a bidder, so that a bid exactly at it
+/// still meets `floor` after the bidder's adjustment factor and
+/// ['markdown_bid()'] are applied
+pub fn bidder_floor(floor: f64, take_rate: u32, adjustment: f64) -> f64 {
+    markup_floor(floor, take_rate) / adjustment
+}
+
+/// Net bid price after the bidder's adjustment factor and margin
+pub fn net_bid(original_bid_price: f64, take_rate: u32, adjustment: f64) -> f64 {
+    markdown_bid(original_bid_price * adjustment, take_rate)
+}
+
+/// The curation fee CPM for a deal impression. The fee is a share of
+/// the gross price carved out of the exchange margin, so it is capped
+/// at the margin and never reduces the publisher's cost share
+pub fn curation_fee(cpm_gross: f64, cpm_cost: f64, fee: u32) -> f64 {
+    let margin = (cpm_gross - cpm_cost).max(0.0);
+
+    (cpm_gross * fee as f64 / 100.0).min(margin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deal(margin: Option<u32>) -> Deal {
+        Deal {
+            margin,
+            ..Deal::stub("d1")
+        }
+    }
+
+    #[test]
+    fn margin_layering() {
+        let publisher = Publisher {
+            margin: 20,
+            ..Default::default()
+        };
+        let mut bidder = Bidder::default();
+
+        assert_eq!(effective_margin(&publisher, &bidder, None), 20);
+
+        bidder.margin = Some(15);
+        assert_eq!(effective_margin(&publisher, &bidder, None), 15);
+        assert_eq!(effective_margin(&publisher, &bidder, Some(&deal(None))), 15);
+        assert_eq!(
+            effective_margin(&publisher, &bidder, Some(&deal(Some(5)))),
+            5
+        );
+    }
+
+    #[test]
+    fn margins_of_100_or_more_are_invalid() {
+        assert!(validate_margin(None).is_ok());
+        assert!(validate_margin(Some(99)).is_ok());
+        assert!(validate_margin(Some(100)).is_err());
+        assert!(validate_margin(Some(150)).is_err());
+    }
+
+    #[test]
+    fn margins_of_100_or_more_are_clamped() {
+        let publisher = Publisher {
+            margin: 100,
+            ..Default::default()
+        };
+        let bidder = Bidder::default();
+
+        assert_eq!(effective_margin(&publisher, &bidder, None), MAX_MARGIN);
+        assert_eq!(
+            effective_margin(&publisher, &bidder, Some(&deal(Some(120)))),
+            MAX_MARGIN
+        );
+
+        for take_rate in [100, 120] {
+            let floor = markup_floor(1.0, take_rate);
+            assert!(floor.is_finite());
+            assert!((floor - markup_floor(1.0, MAX_MARGIN)).abs() < 1e-9);
+            assert!(markdown_bid(1.0, take_rate) > 0.0);
+        }
+    }
+
+    #[test]
+    fn bid_at_bidder_floor_meets_floor() {
+        let floor = bidder_floor(2.0, 20, 0.8);
+        let net = net_bid(floor, 20, 0.8);
+
+        assert!((net - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn curation_fee_comes_out_of_margin() {
+        assert!((curation_fee(10.0, 8.0, 5) - 0.5).abs() < 1e-9);
+        assert!((curation_fee(10.0, 8.0, 30) - 2.0).abs() < 1e-9);
+        assert_eq!(curation_fee(10.0, 10.0, 5), 0.0);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::core::models::supply_rule::{SupplyPattern, SupplyRule, SupplyRuleAction};
+use anyhow::{Error, bail};
+use ip_network::IpNetwork;
+use ip_network_table::IpNetworkTable;
+use regex::{Regex, RegexBuilder};
+use std::net::IpAddr;
+use url::Url;
+
+/// The request values supply rules match on, lowercased
+#[derive(Debug, Clone, Default)]
+pub struct SupplyFacts {
+    pub pub_id: String,
+    pub bundle: String,
+    pub domain: String,
+    /// Id parsed from the app store url, if any
+    pub store_id: Option<String>,
+    pub ip: Option<IpAddr>,
+    pub user_agent: String,
+}
+
+/// Extracts the app id from a store url, e.g. the id query param
+/// of google play, or the trailing id123 path segment of apple
+pub fn store_id_from_url(store_url: &str) -> Option<String> {
+    let url = Url::parse(store_url.trim()).ok()?;
+
+    if let Some((_, id)) = url.query_pairs().find(|(k, _)| k == "id") {
+        return (!id.is_empty()).then(|| id.to_lowercase());
+    }
+
+    url.path_segments()?
+        .filter_map(|segment| segment.strip_prefix("id"))
+        .find(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+enum CompiledPattern {
+    Exact(String),
+    Prefix(String),
+    Contains(String),
+    Regex(Regex),
+}
+
+impl CompiledPattern {
+    fn compile(pattern: &SupplyPattern) -> Result<Self, Error> {
+        Ok(match pattern {
+            SupplyPattern::Exact(v) => CompiledPattern::Exact(v.to_lowercase()),
+            SupplyPattern::Prefix(v) => CompiledPattern::Prefix(v.to_lowercase()),
+            SupplyPattern::Contains(v) => CompiledPattern::Contains(v.to_lowercase()),
+            SupplyPattern::Regex(v) => {
+                CompiledPattern::Regex(RegexBuilder::new(v).case_insensitive(true).build()?)
+            }
+        })
+    }
+
+    /// Value is expected lowercased
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            CompiledPattern::Exact(v) => value == v,
+            CompiledPattern::Prefix(v) => value.starts_with(v.as_str()),
+            CompiledPattern::Contains(v) => value.contains(v.as_str()),
+            CompiledPattern::Regex(r) => r.is_match(value),
+        }
+    }
+}
+
+fn compile_patterns(patterns: &[SupplyPattern]) -> Result<Vec<CompiledPattern>, Error> {
+    patterns.iter().map(CompiledPattern::compile).collect()
+}
+
+/// Empty patterns match anything, an empty value matches nothing
+fn any_pattern_matches(patterns: &[CompiledPattern], value: &str) -> bool {
+    patterns.is_empty() || (!value.is_empty() && patterns.iter().any(|p| p.matches(value)))
+}
+
+fn parse_ip_range(range: &str) -> Result<IpNetwork, Error> {
+    let range = range.trim();
+
+    if let Ok(network) = range.parse::<IpNetwork>() {
+        return Ok(network);
+    }
+
+    let ip: IpAddr = range.parse()?;
+    let netmask = if ip.is_ipv4() { 32 } else { 128 };
+
+    Ok(format!("{ip}/{netmask}").parse()?)
+}
+
+/// A supply rule compiled for matching requests
+pub struct SupplyRuleMatcher {
+    pub id: String,
+    pub action: SupplyRuleAction,
+    pub_ids: Vec<String>,
+    bundles: Vec<CompiledPattern>,
+    domains: Vec<CompiledPattern>,
+    store_ids: Vec<String>,
+    ip_ranges: Option<IpNetworkTable<()>>,
+    user_agents: Vec<CompiledPattern>,
+}
+
+impl SupplyRuleMatcher {
+    pub fn compile(rule: &SupplyRule) -> Result<Self, Error> {
+        if rule.id.trim().is_empty() {
+            bail!("Supply rule missing id");
+        }
+
+        if rule.bundles.is_empty()
+            && rule.domains.is_empty()
+            && rule.store_ids.is_empty()
+            && rule.ip_ranges.is_empty()
+            && rule.user_agents.is_empty()
+        {
+            bail!("Supply rule {} has no criteria besides publishers", rule.id);
+        }
+
+        let ip_ranges = if rule.ip_ranges.is_empty() {
+            None
+        } else {
+            let mut table = IpNetworkTable::new();
+
+            for range in &rule.ip_ranges {
+                table.insert(parse_ip_range(range)?, ());
+            }
+
+            Some(table)
+        };
+
+        Ok(Self {
+            id: rule.id.clone(),
+            action: rule.action,
+            pub_ids: rule.pub_ids.clone(),
+            bundles: compile_patterns(&rule.bundles)?,
+            domains: compile_patterns(&rule.domains)?,
+            store_ids: rule.store_ids.iter().map(|s| s.to_lowercase()).collect(),
+            ip_ranges,
+            user_agents: compile_patterns(&rule.user_agents)?,
+        })
+    }
+
+    pub fn matches(&self, facts: &SupplyFacts) -> bool {
+        if !self.pub_ids.is_empty() && !self.pub_ids.contains(&facts.pub_id) {
+            return false;
+        }
+
+        if !self.store_ids.is_empty()
+            && !self
+                .store_ids
+                .iter()
+                .any(|id| *id == facts.bundle || facts.store_id.as_ref().is_some_and(|s| s == id))
+        {
+            return false;
+        }
+
+        if let Some(table) = &self.ip_ranges {
+            if !facts.ip.is_some_and(|ip| table.longest_match(ip).is_some()) {
+                return false;
+            }
+        }
+
+        any_pattern_matches(&self.bundles, &facts.bundle)
+            && any_pattern_matches(&self.domains, &facts.domain)
+            && any_pattern_matches(&self.user_agents, &facts.user_agent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str) -> SupplyRule {
+        SupplyRule {
+            id: id.into(),
+            action: SupplyRuleAction::Block,
+            pub_ids: vec![],
+            bundles: vec![],
+            domains: vec![],
+            store_ids: vec![],
+            ip_ranges: vec![],
+            user_agents: vec![],
+        }
+    }
+
+    fn app(pub_id: &str, bundle: &str) -> SupplyFacts {
+        SupplyFacts {
+            pub_id: pub_id.into(),
+            bundle: bundle.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn matches_bundle_patterns_case_insensitive() {
+        let matcher = SupplyRuleMatcher::compile(&SupplyRule {
+            bundles: vec![
+                SupplyPattern::Exact("com.Example.App".into()),
+                SupplyPattern::Prefix("com.hbo.".into()),
+                SupplyPattern::Regex(r"^tv\.\w+\.premium$".into()),
+            ],
+            ..rule("premium")
+        })
+        .unwrap();
+
+        assert!(matcher.matches(&app("pub", "com.example.app")));
+        assert!(matcher.matches(&app("pub", "com.hbo.max")));
+        assert!(matcher.matches(&app("pub", "tv.acme.premium")));
+        assert!(!matcher.matches(&app("pub", "com.example.app2")));
+        assert!(!matcher.matches(&app("pub", "")));
+    }
+
+    #[test]
+    fn requires_every_criteria_to_match() {
+        let matcher = SupplyRuleMatcher::compile(&SupplyRule {
+            pub_ids: vec!["pub-1".into()],
+            bundles: vec![SupplyPattern::Contains("netflix".into())],
+            ..rule("pub-bundle")
+        })
+        .unwrap();
+
+        assert!(matcher.matches(&app("pub-1", "com.netflix.mediaclient")));
+        assert!(!matcher.matches(&app("pub-2", "com.netflix.mediaclient")));
+        assert!(!matcher.matches(&app("pub-1", "com.example.app")));
+    }
+
+    #[test]
+    fn matches_ip_ranges_store_ids_and_user_agents() {
+        let matcher = SupplyRuleMatcher::compile(&SupplyRule {
+            ip_ranges: vec!["10.1.0.0/16".into(), "192.0.2.7".into()],
+            ..rule("hosting")
+        })
+        .unwrap();
+
+        let facts = |ip: &str| SupplyFacts {
+            ip: ip.parse().ok(),
+            ..Default::default()
+        };
+
+        assert!(matcher.matches(&facts("10.1.2.3")));
+        assert!(matcher.matches(&facts("192.0.2.7")));
+        assert!(!matcher.matches(&facts("192.0.2.8")));
+        assert!(!matcher.matches(&facts("")));
+
+        let matcher = SupplyRuleMatcher::compile(&SupplyRule {
+            store_ids: vec!["284882215".into()],
+            user_agents: vec![SupplyPattern::Contains("iphone".into())],
+            ..rule("store")
+        })
+        .unwrap();
+
+        let facts = SupplyFacts {
+            store_id: store_id_from_url("https://apps.apple.com/us/app/facebook/id284882215"),
+            user_agent: "mozilla/5.0 (iphone; cpu iphone os 17_0)".into(),
+            ..Default::default()
+        };
+
+        assert!(matcher.matches(&facts));
+        assert!(!matcher.matches(&SupplyFacts {
+            user_agent: "roku/dvp-13.0".into(),
+            ..facts
+        }));
+    }
+
+    #[test]
+    fn parses_store_ids_from_urls() {
+        assert_eq!(
+            store_id_from_url("https://play.google.com/store/apps/details?id=Com.Example.App"),
+            Some("com.example.app".into())
+        );
+        assert_eq!(
+            store_id_from_url("https://apps.apple.com/us/app/some-app/id123456?mt=8"),
+            Some("123456".into())
+        );
+        assert_eq!(store_id_from_url("not a url"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert!(SupplyRuleMatcher::compile(&rule("empty")).is_err());
+
+        assert!(
+            SupplyRuleMatcher::compile(&SupplyRule {
+                bundles: vec![SupplyPattern::Regex("(".into())],
+                ..rule("bad-regex")
+            })
+            .is_err()
+        );
+
+        assert!(
+            SupplyRuleMatcher::compile(&SupplyRule {
+                ip_ranges: vec!["10.1.0.0/99".into()],
+                ..rule("bad-ip")
+            })
+            .is_err()
+        );
+    }
+}
@@ -1,6 +1,13 @@
+use crate::core::firestore::counters::funnel::{
+    DEAL_FUNNEL_COLLECTION, DealFunnelCounters, DealFunnelReport, query_deal_funnel,
+};
 use crate::core::firestore::counters::store::CounterStore;
 use crate::core::firestore::counters::{CounterBuffer, CounterValue};
+use anyhow::Error;
+use chrono::{DateTime, Utc};
 use firestore::FirestoreDb;
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -8,12 +15,21 @@ use std::time::Duration;
 pub struct DealCounters {
     /// Bid responses from DSPs (or direct bids staged) that claimed this deal
     bids: u64,
+    /// Deal bids rejected for not meeting the deal price
+    price_rejections: u64,
+    /// Bidder callouts which offered this deal while it was guaranteed
+    pg_requests: u64,
+    /// Guaranteed callouts where the bidder returned no bid through
+    /// this deal. The PG bid-rate shortfall is pg_missed_bids / pg_requests
+    pg_missed_bids: u64,
     /// Won impressions (recorded via billing event)
     impressions: u64,
     /// Gross CPM sum charged to buyers (/ 1000 = dollars). Admin-visible only.
     revenue_cpm_sum: f64,
     /// Net CPM sum paid to publishers (/ 1000 = dollars). Publisher + admin visible.
     cost_cpm_sum: f64,
+    /// Curation fee CPM sum paid to the curator out of margin (/ 1000 = dollars).
+    curation_fee_cpm_sum: f64,
 }
 
 impl DealCounters {
@@ -21,6 +37,17 @@ impl DealCounters {
         self.bids += 1;
     }
 
+    pub fn price_rejection(&mut self) {
+        self.price_rejections += 1;
+    }
+
+    pub fn pg_request(&mut self, bid: bool) {
+        self.pg_requests += 1;
+        if !bid {
+            self.pg_missed_bids += 1;
+        }
+    }
+
     pub fn impression(&mut self) {
         self.impressions += 1;
     }
@@ -29,22 +56,37 @@ impl DealCounters {
         self.revenue_cpm_sum += cpm_gross;
         self.cost_cpm_sum += cpm_cost;
     }
+
+    pub fn record_curation_fee(&mut self, cpm_fee: f64) {
+        self.curation_fee_cpm_sum += cpm_fee;
+    }
 }
 
 impl CounterBuffer for DealCounters {
     fn merge(&mut self, other: &Self) {
         self.bids += other.bids;
+        self.price_rejections += other.price_rejections;
+        self.pg_requests += other.pg_requests;
+        self.pg_missed_bids += other.pg_missed_bids;
         self.impressions += other.impressions;
         self.revenue_cpm_sum += other.revenue_cpm_sum;
         self.cost_cpm_sum += other.cost_cpm_sum;
+        self.curation_fee_cpm_sum += other.curation_fee_cpm_sum;
     }
 
     fn counter_pairs(&self) -> Vec<(&'static str, CounterValue)> {
         vec![
             ("bids", CounterValue::Int(self.bids)),
+            ("price_rejections", CounterValue::Int(self.price_rejections)),
+            ("pg_requests", CounterValue::Int(self.pg_requests)),
+            ("pg_missed_bids", CounterValue::Int(self.pg_missed_bids)),
             ("impressions", CounterValue::Int(self.impressions)),
             ("revenue_cpm_sum", CounterValue::Float(self.revenue_cpm_sum)),
             ("cost_cpm_sum", CounterValue::Float(self.cost_cpm_sum)),
+            (
+                "curation_fee_cpm_sum",
+                CounterValue::Float(self.curation_fee_cpm_sum),
+            ),
         ]
     }
 }
@@ -62,8 +104,18 @@ pub struct DealCounterStore {
     /// Daily-bucketed — one doc per deal per day.
     /// Used for Daily delivery goals.
     by_deal_daily: Arc<CounterStore<DealCounters>>,
+    /// Hourly-bucketed — one doc per deal per bidder per hour.
+    /// Used for deal troubleshooting reports.
+    funnel: Arc<CounterStore<DealFunnelCounters>>,
+    /// Funnel reports query up to a month of hourly docs,
+    /// so they're rate limited per node
+    report_limiter: DefaultDirectRateLimiter,
+    db: Arc<FirestoreDb>,
 }
 
+/// Funnel reports queried per minute on each node
+const MAX_REPORTS_PER_MINUTE: NonZeroU32 = NonZeroU32::new(30).unwrap();
+
 impl DealCounterStore {
     pub fn new(db: Arc<FirestoreDb>, update_interval: Duration) -> Self {
         Self {
@@ -75,12 +127,21 @@ impl DealCounterStore {
                 update_interval,
             ),
             by_deal_daily: CounterStore::new(
-                db,
+                db.clone(),
                 DEAL_PACING_DAILY_COLLECTION.to_string(),
                 vec!["deal_id"],
                 Some(Duration::from_hours(24)),
                 update_interval,
             ),
+            funnel: CounterStore::new(
+                db.clone(),
+                DEAL_FUNNEL_COLLECTION.to_string(),
+                vec!["deal_id", "bidder_id"],
+                Some(Duration::from_hours(1)),
+                update_interval,
+            ),
+            report_limiter: RateLimiter::direct(Quota::per_minute(MAX_REPORTS_PER_MINUTE)),
+            db,
         }
     }
 
@@ -89,8 +150,28 @@ impl DealCounterStore {
         self.by_deal_daily.merge(&[deal_id], buffer);
     }
 
+    pub fn merge_funnel(&self, deal_id: &str, bidder_id: &str, buffer: &DealFunnelCounters) {
+        self.funnel.merge(&[deal_id, bidder_id], buffer);
+    }
+
+    /// Whether another funnel report may be queried now
+    pub fn try_acquire_report(&self) -> bool {
+        self.report_limiter.check().is_ok()
+    }
+
+    /// Per bidder funnel of a deal over the time range
+    pub async fn funnel_report(
+        &self,
+        deal_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<DealFunnelReport, Error> {
+        query_deal_funnel(&self.db, deal_id, from, to).await
+    }
+
     pub async fn shutdown(&self) {
         self.by_deal.shutdown().await;
         self.by_deal_daily.shutdown().await;
+        self.funnel.shutdown().await;
     }
 }
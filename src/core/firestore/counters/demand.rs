@@ -12,6 +12,8 @@ pub struct DemandCounters {
     pub requests_matched: u64,
     pub requests_qps_limited: u64,
     pub requests_shaping_blocked: u64,
+    /// Callouts skipped while the endpoint's circuit breaker was open
+    pub requests_circuit_open: u64,
     pub auctions: u64,
     pub bids: u64,
     pub bids_filtered: u64,
@@ -35,6 +37,10 @@ impl DemandCounters {
         self.requests_shaping_blocked += 1;
     }
 
+    pub fn request_circuit_open(&mut self) {
+        self.requests_circuit_open += 1;
+    }
+
     pub fn auction(&mut self) {
         self.auctions += 1;
     }
@@ -67,6 +73,7 @@ impl CounterBuffer for DemandCounters {
         self.requests_matched += other.requests_matched;
         self.requests_qps_limited += other.requests_qps_limited;
         self.requests_shaping_blocked += other.requests_shaping_blocked;
+        self.requests_circuit_open += other.requests_circuit_open;
         self.auctions += other.auctions;
         self.bids += other.bids;
         self.bids_filtered += other.bids_filtered;
@@ -88,6 +95,10 @@ impl CounterBuffer for DemandCounters {
                 "requests_shaping_blocked",
                 CounterValue::Int(self.requests_shaping_blocked),
             ),
+            (
+                "requests_circuit_open",
+                CounterValue::Int(self.requests_circuit_open),
+            ),
             ("auctions", CounterValue::Int(self.auctions)),
             ("bids", CounterValue::Int(self.bids)),
             ("bids_filtered", CounterValue::Int(self.bids_filtered)),
@@ -0,0 +1,198 @@
+use crate::core::firestore::counters::{CounterBuffer, CounterValue};
+use crate::core::spec::lossreasons;
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use firestore::{FirestoreDb, FirestoreTimestamp};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Collection name for the hourly deal funnel store.
+pub const DEAL_FUNNEL_COLLECTION: &str = "stats_deal_funnel";
+
+/// Delivery funnel of a deal through a single bidder, in stage order.
+/// Doubles as the stats shape read back from Firestore for reports.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DealFunnelCounters {
+    /// Imps where the deal targeting matched
+    pub eligible: u64,
+    /// Eligible imps where deal pacing held the deal back
+    pub paced_out: u64,
+    /// Bid requests sent with the deal in pmp
+    pub requests: u64,
+    /// Bids received through the deal
+    pub bids: u64,
+    /// Bids filtered for not meeting the deal price
+    pub filtered_deal_price: u64,
+    /// Bids filtered as invalid, e.g. an unsupported currency
+    pub filtered_invalid: u64,
+    /// Bids filtered on an internal error
+    pub filtered_error: u64,
+    /// Bids filtered for any other reason
+    pub filtered_other: u64,
+    /// Bids which won the exchange auction for their imp
+    pub won: u64,
+    /// Billed impressions
+    pub billed: u64,
+}
+
+impl DealFunnelCounters {
+    pub fn eligible(&mut self) {
+        self.eligible += 1;
+    }
+
+    pub fn paced_out(&mut self) {
+        self.paced_out += 1;
+    }
+
+    pub fn request(&mut self) {
+        self.requests += 1;
+    }
+
+    pub fn bid(&mut self) {
+        self.bids += 1;
+    }
+
+    /// Counts a filtered bid under the stage for its loss reason code
+    pub fn filtered(&mut self, code: u32) {
+        if lossreasons::is_deal_price_mismatch(code) {
+            self.filtered_deal_price += 1;
+        } else if code == rtb::spec::openrtb::lossreason::INVALID_BID_RESPONSE {
+            self.filtered_invalid += 1;
+        } else if code == rtb::spec::openrtb::lossreason::INTERNAL_ERROR {
+            self.filtered_error += 1;
+        } else {
+            self.filtered_other += 1;
+        }
+    }
+
+    pub fn won(&mut self) {
+        self.won += 1;
+    }
+
+    pub fn billed(&mut self) {
+        self.billed += 1;
+    }
+}
+
+impl CounterBuffer for DealFunnelCounters {
+    fn merge(&mut self, other: &Self) {
+        self.eligible += other.eligible;
+        self.paced_out += other.paced_out;
+        self.requests += other.requests;
+        self.bids += other.bids;
+        self.filtered_deal_price += other.filtered_deal_price;
+        self.filtered_invalid += other.filtered_invalid;
+        self.filtered_error += other.filtered_error;
+        self.filtered_other += other.filtered_other;
+        self.won += other.won;
+        self.billed += other.billed;
+    }
+
+    fn counter_pairs(&self) -> Vec<(&'static str, CounterValue)> {
+        vec![
+            ("eligible", CounterValue::Int(self.eligible)),
+            ("paced_out", CounterValue::Int(self.paced_out)),
+            ("requests", CounterValue::Int(self.requests)),
+            ("bids", CounterValue::Int(self.bids)),
+            (
+                "filtered_deal_price",
+                CounterValue::Int(self.filtered_deal_price),
+            ),
+            ("filtered_invalid", CounterValue::Int(self.filtered_invalid)),
+            ("filtered_error", CounterValue::Int(self.filtered_error)),
+            ("filtered_other", CounterValue::Int(self.filtered_other)),
+            ("won", CounterValue::Int(self.won)),
+            ("billed", CounterValue::Int(self.billed)),
+        ]
+    }
+}
+
+/// Shape of the funnel docs written by the deal funnel counter store
+#[derive(Deserialize)]
+struct DealFunnelDoc {
+    fields: HashMap<String, String>,
+    #[serde(default)]
+    stats: Option<DealFunnelCounters>,
+}
+
+/// Funnel of one deal per bidder over a time range
+#[derive(Debug, Serialize)]
+pub struct DealFunnelReport {
+    pub deal_id: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total: DealFunnelCounters,
+    pub bidders: BTreeMap<String, DealFunnelCounters>,
+}
+
+/// Sums the hourly funnel buckets of a deal with bucket start in
+/// [from, to). Needs a composite index on fields.deal_id + bucket
+pub async fn query_deal_funnel(
+    db: &FirestoreDb,
+    deal_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<DealFunnelReport, Error> {
+    let docs: Vec<DealFunnelDoc> = db
+        .fluent()
+        .select()
+        .from(DEAL_FUNNEL_COLLECTION)
+        .filter(|q| {
+            q.for_all([
+                q.field("fields.deal_id").eq(deal_id),
+                q.field("bucket")
+                    .greater_than_or_equal(FirestoreTimestamp(from)),
+                q.field("bucket").less_than(FirestoreTimestamp(to)),
+            ])
+        })
+        .obj()
+        .query()
+        .await?;
+
+    let mut report = DealFunnelReport {
+        deal_id: deal_id.to_string(),
+        from,
+        to,
+        total: DealFunnelCounters::default(),
+        bidders: BTreeMap::new(),
+    };
+
+    for doc in docs {
+        let (Some(bidder_id), Some(stats)) = (doc.fields.get("bidder_id"), doc.stats) else {
+            continue;
+        };
+
+        report.total.merge(&stats);
+        report
+            .bidders
+            .entry(bidder_id.clone())
+            .or_default()
+            .merge(&stats);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filtered_by_reason_and_merge() {
+        let mut a = DealFunnelCounters::default();
+        a.filtered(lossreasons::BID_BELOW_DEAL_FLOOR);
+        a.filtered(rtb::spec::openrtb::lossreason::INVALID_BID_RESPONSE);
+        a.filtered(9999);
+
+        let mut b = DealFunnelCounters::default();
+        b.bid();
+        b.merge(&a);
+
+        assert_eq!(b.bids, 1);
+        assert_eq!(b.filtered_deal_price, 1);
+        assert_eq!(b.filtered_invalid, 1);
+        assert_eq!(b.filtered_other, 1);
+        assert_eq!(b.filtered_error, 0);
+    }
+}
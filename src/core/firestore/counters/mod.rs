@@ -1,6 +1,7 @@
 pub mod campaign;
 pub mod deal;
 pub mod demand;
+pub mod funnel;
 pub mod publisher;
 pub mod store;
 mod traits;
@@ -48,8 +48,8 @@ impl PublisherCounters {
     }
 
     /// Mark this request as having a recognized
-    /// buyeruid value (cookie for web) or
-    /// a validated device id present
+    /// buyeruid value (cookie for web), a
+    /// validated device id or any EIDs present
     pub fn had_cookie_or_maid(&mut self) {
         self.had_cookie_or_maid += 1;
     }
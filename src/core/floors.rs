@@ -0,0 +1,331 @@
+use crate::app::config::FloorsConfig;
+use crate::core::enrichment::device::DeviceType;
+use crate::core::models::publisher::{FloorRule, ImpFormat};
+use dashmap::DashMap;
+use rtb::bid_request::Imp;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Lower edge of the first price bucket, bids below it land there
+const MIN_PRICE: f64 = 0.01;
+/// Each bucket's lower edge is this factor above the previous
+const BUCKET_GROWTH: f64 = 1.1;
+/// Bucket count, 0.01 * 1.1^100 is ~$137 beyond which
+/// bids land in the last bucket
+const BUCKETS: usize = 100;
+/// How often (in samples) the cached quantile is recalculated
+const RECALC_EVERY: u64 = 32;
+/// Sample count at which all buckets are halved, biasing
+/// the histogram toward recent bids
+const DECAY_AT: u64 = 10_000;
+/// Cached quantile bucket value before one is calculated
+const NO_BUCKET: u32 = u32::MAX;
+
+/// The media type a floor applies to. Multi format imps are keyed
+/// by their richest format, since it usually attracts the highest bids
+pub fn imp_format(imp: &Imp) -> Option<ImpFormat> {
+    if imp.video.is_some() {
+        Some(ImpFormat::Video)
+    } else if imp.audio.is_some() {
+        Some(ImpFormat::Audio)
+    } else if imp.native.is_some() {
+        Some(ImpFormat::Native)
+    } else if imp.banner.is_some() {
+        Some(ImpFormat::Banner)
+    } else {
+        None
+    }
+}
+
+/// Dimensions floors are set and learned over
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FloorKey {
+    pub pub_id: String,
+    /// Placement id if known, else the imp tagid
+    pub placement_id: String,
+    pub country: String,
+    pub devtype: DeviceType,
+    pub format: Option<ImpFormat>,
+}
+
+fn rule_matches(rule: &FloorRule, key: &FloorKey) -> bool {
+    rule.placement_id
+        .as_ref()
+        .is_none_or(|id| *id == key.placement_id)
+        && (rule.countries.is_empty()
+            || rule
+                .countries
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(&key.country)))
+        && (rule.device_types.is_empty() || rule.device_types.contains(&key.devtype))
+        && (rule.formats.is_empty() || key.format.is_some_and(|f| rule.formats.contains(&f)))
+}
+
+fn rule_specificity(rule: &FloorRule) -> usize {
+    [
+        rule.placement_id.is_some(),
+        !rule.countries.is_empty(),
+        !rule.device_types.is_empty(),
+        !rule.formats.is_empty(),
+    ]
+    .into_iter()
+    .filter(|constrained| *constrained)
+    .count()
+}
+
+/// The publisher floor rule which applies to the key, if any. The
+/// most specific matching rule wins, ties going to the highest floor
+pub fn static_floor<'a>(rules: &'a [FloorRule], key: &FloorKey) -> Option<&'a FloorRule> {
+    rules
+        .iter()
+        .filter(|rule| rule_matches(rule, key))
+        .max_by(|a, b| {
+            rule_specificity(a)
+                .cmp(&rule_specificity(b))
+                .then(a.floor.total_cmp(&b.floor))
+        })
+}
+
+fn bucket_for(price: f64) -> usize {
+    if price <= MIN_PRICE {
+        return 0;
+    }
+
+    let idx = ((price / MIN_PRICE).ln() / BUCKET_GROWTH.ln()).floor() as usize;
+
+    idx.min(BUCKETS - 1)
+}
+
+fn bucket_lower_edge(idx: usize) -> f64 {
+    MIN_PRICE * BUCKET_GROWTH.powi(idx as i32)
+}
+
+/// Rolling, lock free histogram of bid prices on log scaled buckets
+struct PriceHistogram {
+    buckets: Vec<AtomicU64>,
+    samples: AtomicU64,
+    /// Cached bucket of the configured quantile, NO_BUCKET until calculated
+    quantile_bucket: AtomicU32,
+}
+
+impl PriceHistogram {
+    fn new() -> Self {
+        Self {
+            buckets: (0..BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            samples: AtomicU64::new(0),
+            quantile_bucket: AtomicU32::new(NO_BUCKET),
+        }
+    }
+
+    fn record(&self, price: f64, quantile: f64) {
+        self.buckets[bucket_for(price)].fetch_add(1, Ordering::Relaxed);
+
+        let samples = self.samples.fetch_add(1, Ordering::Relaxed) + 1;
+        if samples % RECALC_EVERY == 0 {
+            self.recalc(samples, quantile);
+        }
+    }
+
+    fn recalc(&self, samples: u64, quantile: f64) {
+        let mut total = samples;
+
+        if samples >= DECAY_AT {
+            total = 0;
+            for bucket in &self.buckets {
+                let halved = bucket.load(Ordering::Relaxed) / 2;
+                bucket.store(halved, Ordering::Relaxed);
+                total += halved;
+            }
+            self.samples.store(total, Ordering::Relaxed);
+        }
+
+        let target = ((total as f64 * quantile).ceil() as u64).max(1);
+        let mut seen = 0;
+
+        for (idx, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= target {
+                self.quantile_bucket.store(idx as u32, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+
+    /// Lower edge of the quantile bucket, so the learned
+    /// floor never sits above the true quantile
+    fn floor(&self, min_samples: u64) -> Option<f64> {
+        if self.samples.load(Ordering::Relaxed) < min_samples {
+            return None;
+        }
+
+        match self.quantile_bucket.load(Ordering::Relaxed) {
+            NO_BUCKET => None,
+            idx => Some(bucket_lower_edge(idx as usize)),
+        }
+    }
+}
+
+/// Learns floors per [`FloorKey`] from the bid landscape. Only auctions
+/// picked for exploration are sent without a learned floor and fed back,
+/// since bids seen under a learned floor are censored by it and would
+/// otherwise push the floor up on every cycle
+pub struct FloorEngine {
+    config: FloorsConfig,
+    keys: DashMap<FloorKey, PriceHistogram>,
+}
+
+impl FloorEngine {
+    pub fn new(config: FloorsConfig) -> Self {
+        Self {
+            config,
+            keys: DashMap::new(),
+        }
+    }
+
+    /// Whether this auction should skip learned floors and be learned from
+    pub fn explore(&self) -> bool {
+        self.config.dynamic && fastrand::f32() < self.config.explore_rate
+    }
+
+    /// The learned floor for the key, None until enough bids are observed
+    pub fn learned_floor(&self, key: &FloorKey) -> Option<f64> {
+        if !self.config.dynamic {
+            return None;
+        }
+
+        self.keys
+            .get(key)
+            .and_then(|h| h.floor(self.config.min_samples))
+            .map(|floor| floor.min(self.config.max_floor))
+    }
+
+    /// Record a bid price observed on an exploration auction
+    pub fn record(&self, key: &FloorKey, price: f64) {
+        if !self.config.dynamic || price <= 0.0 {
+            return;
+        }
+
+        if let Some(histogram) = self.keys.get(key) {
+            return histogram.record(price, self.config.quantile);
+        }
+
+        if self.keys.len() >= self.config.max_keys {
+            return;
+        }
+
+        self.keys
+            .entry(key.clone())
+            .or_insert_with(PriceHistogram::new)
+            .record(price, self.config.quantile);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(placement_id: &str) -> FloorKey {
+        FloorKey {
+            pub_id: "pub1".to_string(),
+            placement_id: placement_id.to_string(),
+            country: "USA".to_string(),
+            devtype: DeviceType::Phone,
+            format: Some(ImpFormat::Banner),
+        }
+    }
+
+    fn rule(floor: f64) -> FloorRule {
+        FloorRule {
+            placement_id: None,
+            countries: vec![],
+            device_types: vec![],
+            formats: vec![],
+            floor,
+            currency: None,
+        }
+    }
+
+    fn engine() -> FloorEngine {
+        FloorEngine::new(FloorsConfig {
+            dynamic: true,
+            quantile: 0.2,
+            min_samples: 100,
+            max_floor: 5.0,
+            max_keys: 2,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn most_specific_rule_wins() {
+        let rules = vec![
+            rule(3.0),
+            FloorRule {
+                countries: vec!["usa".to_string()],
+                formats: vec![ImpFormat::Banner],
+                ..rule(1.0)
+            },
+            FloorRule {
+                placement_id: Some("other".to_string()),
+                countries: vec!["USA".to_string()],
+                formats: vec![ImpFormat::Banner],
+                ..rule(9.0)
+            },
+        ];
+
+        assert_eq!(
+            static_floor(&rules, &key("plc1")).map(|r| r.floor),
+            Some(1.0)
+        );
+        assert_eq!(
+            static_floor(&rules, &key("other")).map(|r| r.floor),
+            Some(9.0)
+        );
+        assert!(static_floor(&[], &key("plc1")).is_none());
+    }
+
+    #[test]
+    fn learns_low_quantile() {
+        let engine = engine();
+        let key = key("plc1");
+
+        // 20% of bids around $1, the rest around $4
+        for i in 0..160 {
+            let price = if i % 5 == 0 { 1.0 } else { 4.0 };
+            engine.record(&key, price);
+        }
+
+        let floor = engine.learned_floor(&key).unwrap();
+        assert!(floor > 0.9 && floor <= 1.0, "floor {}", floor);
+    }
+
+    #[test]
+    fn needs_min_samples_and_caps() {
+        let engine = engine();
+        let key = key("plc1");
+
+        for _ in 0..96 {
+            engine.record(&key, 50.0);
+        }
+        assert_eq!(engine.learned_floor(&key), None);
+
+        for _ in 0..32 {
+            engine.record(&key, 50.0);
+        }
+        assert_eq!(engine.learned_floor(&key), Some(5.0));
+    }
+
+    #[test]
+    fn key_cap_and_disabled() {
+        let engine = engine();
+
+        engine.record(&key("a"), 1.0);
+        engine.record(&key("b"), 1.0);
+        engine.record(&key("c"), 1.0);
+        assert_eq!(engine.keys.len(), 2);
+
+        let disabled = FloorEngine::new(FloorsConfig::default());
+        disabled.record(&key("a"), 1.0);
+        assert!(disabled.keys.is_empty());
+        assert!(!disabled.explore());
+    }
+}
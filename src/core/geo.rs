@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Mean earth radius used for great circle distances
+const EARTH_RADIUS_KM: f64 = 6371.0;
+/// Kilometres per degree of latitude
+const KM_PER_DEG: f64 = 111.32;
+/// Smallest grid cell, so tiny radii don't blow up the cell count
+const MIN_CELL_DEG: f64 = 0.05;
+/// Cosine floor near the poles, where a degree of longitude shrinks to nothing
+const MIN_COS_LAT: f64 = 0.01;
+
+/// A circle around a point, e.g. a store location
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeoRadius {
+    pub lat: f64,
+    pub lon: f64,
+    pub radius_km: f64,
+}
+
+/// Great circle distance between two points in km
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (dlat, dlon) = ((lat2 - lat1).to_radians(), (lon2 - lon1).to_radians());
+
+    let a = (dlat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (dlon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Lat/lon grid over the circles of one radius band, each circle
+/// bucketed into every cell its bounding box overlaps
+#[derive(Debug, Clone)]
+struct RadiusGrid {
+    cell_deg: f64,
+    lon_cells: i64,
+    cells: HashMap<(i64, i64), Vec<u32>>,
+}
+
+impl RadiusGrid {
+    fn new(target_deg: f64) -> Self {
+        // cells evenly divide the longitudes so wrapping lines up
+        let lon_cells = (360.0 / target_deg).ceil() as i64;
+
+        RadiusGrid {
+            cell_deg: 360.0 / lon_cells as f64,
+            lon_cells,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn lat_cell(&self, lat: f64) -> i64 {
+        ((lat + 90.0) / self.cell_deg).floor() as i64
+    }
+
+    fn lon_cell(&self, lon: f64) -> i64 {
+        (((lon + 180.0) / self.cell_deg).floor() as i64).rem_euclid(self.lon_cells)
+    }
+
+    fn insert(&mut self, i: u32, circle: &GeoRadius) {
+        let dlat = circle.radius_km / KM_PER_DEG;
+        let dlon = (circle.radius_km
+            / (KM_PER_DEG * circle.lat.to_radians().cos().max(MIN_COS_LAT)))
+        .min(180.0);
+
+        let lat_lo = self.lat_cell((circle.lat - dlat).max(-90.0));
+        let lat_hi = self.lat_cell((circle.lat + dlat).min(90.0));
+
+        let lon_lo = ((circle.lon + 180.0 - dlon) / self.cell_deg).floor() as i64;
+        let lon_hi = ((circle.lon + 180.0 + dlon) / self.cell_deg).floor() as i64;
+
+        // circles wider than the globe cover every longitude once
+        let lon_range: Vec<i64> = if lon_hi - lon_lo + 1 >= self.lon_cells {
+            (0..self.lon_cells).collect()
+        } else {
+            (lon_lo..=lon_hi)
+                .map(|x| x.rem_euclid(self.lon_cells))
+                .collect()
+        };
+
+        for lat_cell in lat_lo..=lat_hi {
+            for &lon_cell in &lon_range {
+                self.cells.entry((lat_cell, lon_cell)).or_default().push(i);
+            }
+        }
+    }
+
+    fn candidates(&self, lat: f64, lon: f64) -> &[u32] {
+        self.cells
+            .get(&(self.lat_cell(lat), self.lon_cell(lon)))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Grid index over a set of radius circles. Circles are banded by
+/// radius, doubling the cell size per band, and each band gets its
+/// own grid sized to its largest radius. Circles so span few cells,
+/// and one large radius doesn't coarsen the grid for many small
+/// ones. A lookup only distance checks the circles bucketed in its
+/// own cell of each band.
+/// Serializes as the plain list of circles, and is built on load
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<GeoRadius>", into = "Vec<GeoRadius>")]
+pub struct GeoRadiusIndex {
+    circles: Vec<GeoRadius>,
+    grids: Vec<RadiusGrid>,
+}
+
+impl GeoRadiusIndex {
+    /// Whether the point falls within any of the circles
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        self.grids.iter().any(|grid| {
+            grid.candidates(lat, lon).iter().any(|&i| {
+                let c = &self.circles[i as usize];
+                haversine_km(lat, lon, c.lat, c.lon) <= c.radius_km
+            })
+        })
+    }
+}
+
+impl From<Vec<GeoRadius>> for GeoRadiusIndex {
+    fn from(circles: Vec<GeoRadius>) -> Self {
+        let mut bands: BTreeMap<i32, RadiusGrid> = BTreeMap::new();
+
+        for (i, circle) in circles.iter().enumerate() {
+            let deg = (circle.radius_km / KM_PER_DEG).clamp(MIN_CELL_DEG, 180.0);
+            let band = (deg / MIN_CELL_DEG).log2().ceil() as i32;
+
+            bands
+                .entry(band)
+                .or_insert_with(|| RadiusGrid::new((MIN_CELL_DEG * 2f64.powi(band)).min(180.0)))
+                .insert(i as u32, circle);
+        }
+
+        GeoRadiusIndex {
+            circles,
+            grids: bands.into_values().collect(),
+        }
+    }
+}
+
+impl From<GeoRadiusIndex> for Vec<GeoRadius> {
+    fn from(index: GeoRadiusIndex) -> Self {
+        index.circles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(lat: f64, lon: f64, radius_km: f64) -> GeoRadius {
+        GeoRadius {
+            lat,
+            lon,
+            radius_km,
+        }
+    }
+
+    #[test]
+    fn contains_points_within_any_radius() {
+        // Times Square 5km and Santa Monica 20km
+        let index = GeoRadiusIndex::from(vec![
+            circle(40.758, -73.9855, 5.0),
+            circle(34.0195, -118.4912, 20.0),
+        ]);
+
+        // Empire State Building, ~1km from Times Square
+        assert!(index.contains(40.7484, -73.9857));
+        // Downtown LA, ~22km from Santa Monica
+        assert!(!index.contains(34.0522, -118.2437));
+        // Beverly Hills, ~10km from Santa Monica
+        assert!(index.contains(34.0736, -118.4004));
+        // Chicago
+        assert!(!index.contains(41.8781, -87.6298));
+    }
+
+    #[test]
+    fn wraps_across_antimeridian() {
+        let index = GeoRadiusIndex::from(vec![circle(-16.5, 179.95, 30.0)]);
+
+        assert!(index.contains(-16.5, -179.95));
+        assert!(!index.contains(-16.5, 170.0));
+    }
+
+    #[test]
+    fn large_radius_keeps_small_radii_in_fine_cells() {
+        let index = GeoRadiusIndex::from(vec![
+            circle(40.758, -73.9855, 1.0),
+            circle(34.0195, -118.4912, 1.0),
+            circle(51.5, -0.12, 2000.0),
+        ]);
+
+        assert_eq!(index.grids.len(), 2);
+        assert!(index.grids[0].cell_deg < 0.1);
+
+        // Times Square is only a candidate around its own cell
+        let small = &index.grids[0];
+        assert!(small.candidates(40.758, -73.9855).contains(&0));
+        assert!(small.candidates(41.0, -73.9855).is_empty());
+
+        assert!(index.contains(40.7484, -73.9857));
+        assert!(!index.contains(40.77, -73.9855));
+        // Paris, ~340km from London
+        assert!(index.contains(48.8566, 2.3522));
+    }
+
+    #[test]
+    fn round_trips_as_circle_list() {
+        let json = r#"[{"lat":51.5,"lon":-0.12,"radius_km":10.0}]"#;
+        let index: GeoRadiusIndex = serde_json::from_str(json).unwrap();
+
+        assert!(index.contains(51.51, -0.1));
+        assert_eq!(serde_json::to_string(&index).unwrap(), json);
+    }
+}
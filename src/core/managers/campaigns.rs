@@ -1,4 +1,5 @@
 use super::delivery::write_delivery_states;
+use crate::core::currency::CurrencyService;
 use crate::core::models::campaign::Campaign;
 use crate::core::models::common::{DeliveryState, Status};
 use crate::core::providers::{Provider, ProviderEvent};
@@ -9,7 +10,7 @@ use firestore::FirestoreDb;
 use parking_lot::RwLock;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 struct CampaignCache {
     by_id: HashMap<String, Arc<Campaign>>,
@@ -114,12 +115,17 @@ pub struct CampaignManager {
     /// after a rebuild (expired, deactivated, or removed).
     on_expired_cbs: RwLock<Vec<Box<dyn Fn(&str) + Send + Sync>>>,
     firestore_db: Option<Arc<FirestoreDb>>,
+    currency: Arc<CurrencyService>,
+    /// Campaigns priced in a currency of their own as loaded, so they
+    /// can be re-converted whenever the FX rates refresh
+    priced: RwLock<HashMap<String, Campaign>>,
 }
 
 impl CampaignManager {
     pub async fn start(
         provider: Arc<dyn Provider<Campaign>>,
         db: Option<Arc<FirestoreDb>>,
+        currency: Arc<CurrencyService>,
     ) -> Result<Arc<Self>, Error> {
         let manager = Arc::new(Self {
             cache: ArcSwap::from_pointee(CampaignCache {
@@ -129,6 +135,8 @@ impl CampaignManager {
             }),
             on_expired_cbs: RwLock::new(Vec::new()),
             firestore_db: db,
+            currency: currency.clone(),
+            priced: RwLock::new(HashMap::new()),
         });
 
         let mgr = manager.clone();
@@ -138,6 +146,13 @@ impl CampaignManager {
 
         manager.load(initial);
 
+        let mgr = Arc::downgrade(&manager);
+        currency.on_refresh(Box::new(move || {
+            if let Some(mgr) = mgr.upgrade() {
+                mgr.reprice();
+            }
+        }));
+
         Ok(manager)
     }
 
@@ -149,7 +164,14 @@ impl CampaignManager {
 
     fn load(&self, campaigns: Vec<Campaign>) {
         let total = campaigns.len();
-        let (cache, _) = CampaignCache::build(campaigns.into_iter().map(Arc::new), None);
+        *self.priced.write() = campaigns
+            .iter()
+            .filter(|c| c.currency.is_some())
+            .map(|c| (c.id.clone(), c.clone()))
+            .collect();
+
+        let campaigns = campaigns.into_iter().filter_map(|c| self.normalize(c));
+        let (cache, _) = CampaignCache::build(campaigns.map(Arc::new), None);
         info!(
             "Loaded {} active campaigns across {} buyers (total: {})",
             cache.all.len(),
@@ -192,6 +214,90 @@ impl CampaignManager {
         }
     }
 
+    /// Campaign budget and price converted into accounting
+    /// currency. Campaigns in an unknown currency are dropped
+    /// rather than risk overspending the budget, as are
+    /// campaigns with invalid targeting
+    fn normalize(&self, mut campaign: Campaign) -> Option<Campaign> {
+        let id = campaign.id.clone();
+        let currency = campaign.currency.clone();
+
+        if let Err(e) = campaign.targeting.common.validate() {
+            warn!("Dropping campaign {}, invalid targeting: {}", id, e);
+            return None;
+        }
+        campaign.targeting.common.normalize();
+
+        let normalized = self.currency.normalize_campaign(campaign);
+        if normalized.is_none() {
+            warn!(
+                "Dropping campaign {}, no FX rate for currency {:?}",
+                id, currency
+            );
+        }
+
+        normalized
+    }
+
+    fn upsert(&self, campaign: Campaign) {
+        let id = campaign.id.clone();
+
+        if campaign.currency.is_some() {
+            self.priced.write().insert(id.clone(), campaign.clone());
+        } else {
+            self.priced.write().remove(&id);
+        }
+
+        let campaign = self.normalize(campaign);
+
+        self.rebuild(
+            |m| match campaign {
+                Some(campaign) => {
+                    m.insert(id, Arc::new(campaign));
+                }
+                None => {
+                    m.remove(&id);
+                }
+            },
+            None,
+        );
+    }
+
+    /// Re-convert every campaign priced in a currency of its own with
+    /// the current rates, keeping the delivery state tracked since
+    fn reprice(&self) {
+        let priced: Vec<Campaign> = self.priced.read().values().cloned().collect();
+        if priced.is_empty() {
+            return;
+        }
+
+        debug!("Repricing {} campaigns after FX refresh", priced.len());
+
+        let normalized: Vec<(String, Option<Campaign>)> = priced
+            .into_iter()
+            .map(|c| (c.id.clone(), self.normalize(c)))
+            .collect();
+
+        self.rebuild(
+            |m| {
+                for (id, campaign) in normalized {
+                    match campaign {
+                        Some(mut campaign) => {
+                            if let Some(prev) = m.get(&id) {
+                                campaign.delivery_state = prev.delivery_state.clone();
+                            }
+                            m.insert(id, Arc::new(campaign));
+                        }
+                        None => {
+                            m.remove(&id);
+                        }
+                    }
+                }
+            },
+            None,
+        );
+    }
+
     fn handle_event(&self, event: ProviderEvent<Campaign>) {
         match event {
             ProviderEvent::Added(c) => {
@@ -199,27 +305,18 @@ impl CampaignManager {
                     "Campaign added: {} ({}) buyer={} budget={:.2}",
                     c.name, c.id, c.buyer_id, c.budget
                 );
-                self.rebuild(
-                    |m| {
-                        m.insert(c.id.clone(), Arc::new(c));
-                    },
-                    None,
-                );
+                self.upsert(c);
             }
             ProviderEvent::Modified(c) => {
                 debug!(
                     "Campaign modified: {} ({}) status={:?}",
                     c.name, c.id, c.status
                 );
-                self.rebuild(
-                    |m| {
-                        m.insert(c.id.clone(), Arc::new(c));
-                    },
-                    None,
-                );
+                self.upsert(c);
             }
             ProviderEvent::Removed(id) => {
                 debug!("Campaign removed: {}", id);
+                self.priced.write().remove(&id);
                 self.rebuild(
                     |m| {
                         m.remove(&id);
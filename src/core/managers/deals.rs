@@ -1,4 +1,6 @@
 use super::delivery::write_delivery_states;
+use crate::core::currency::CurrencyService;
+use crate::core::demand::takerate;
 use crate::core::models::common::{DeliveryState, Status};
 use crate::core::models::deal::{Deal, DemandPolicy};
 use crate::core::providers::{Provider, ProviderEvent};
@@ -9,7 +11,7 @@ use firestore::FirestoreDb;
 use parking_lot::RwLock;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{debug, info, trace};
+use tracing::{debug, info, trace, warn};
 
 /// Per-bidder RTB deals pre-split by private/open at load time
 /// so match-time never filters on the private flag
@@ -141,12 +143,17 @@ pub struct DealManager {
     cache: ArcSwap<DealCache>,
     on_expired_cbs: RwLock<Vec<Box<dyn Fn(&str) + Send + Sync>>>,
     firestore_db: Option<Arc<FirestoreDb>>,
+    currency: Arc<CurrencyService>,
+    /// Deals priced in a currency of their own as loaded, so they
+    /// can be re-converted whenever the FX rates refresh
+    priced: RwLock<HashMap<String, Deal>>,
 }
 
 impl DealManager {
     pub async fn start(
         provider: Arc<dyn Provider<Deal>>,
         db: Option<Arc<FirestoreDb>>,
+        currency: Arc<CurrencyService>,
     ) -> Result<Arc<Self>, Error> {
         let manager = Arc::new(Self {
             cache: ArcSwap::from_pointee(DealCache {
@@ -156,6 +163,8 @@ impl DealManager {
             }),
             on_expired_cbs: RwLock::new(Vec::new()),
             firestore_db: db,
+            currency: currency.clone(),
+            priced: RwLock::new(HashMap::new()),
         });
 
         let mgr = manager.clone();
@@ -165,12 +174,26 @@ impl DealManager {
 
         manager.load(initial);
 
+        let mgr = Arc::downgrade(&manager);
+        currency.on_refresh(Box::new(move || {
+            if let Some(mgr) = mgr.upgrade() {
+                mgr.reprice();
+            }
+        }));
+
         Ok(manager)
     }
 
     fn load(&self, deals: Vec<Deal>) {
         let total = deals.len();
-        let (cache, _) = DealCache::build(deals.into_iter().map(Arc::new), None);
+        *self.priced.write() = deals
+            .iter()
+            .filter(|d| d.currency.is_some())
+            .map(|d| (d.id.clone(), d.clone()))
+            .collect();
+
+        let deals = deals.into_iter().filter_map(|d| self.normalize(d));
+        let (cache, _) = DealCache::build(deals.map(Arc::new), None);
         let rtb_count: usize = cache
             .rtb_by_bidder
             .values()
@@ -227,28 +250,114 @@ impl DealManager {
         }
     }
 
+    /// Deal pricing converted into accounting currency. Deals
+    /// priced in an unknown currency are dropped rather than
+    /// risk bidding at the wrong price, as are buyer deals
+    /// which fail owner validation, deals with a margin of
+    /// 100% or more and deals with invalid targeting
+    fn normalize(&self, mut deal: Deal) -> Option<Deal> {
+        let id = deal.id.clone();
+        let currency = deal.currency.clone();
+
+        if let Err(e) = deal.validate_owner() {
+            warn!("Dropping deal {}: {}", id, e);
+            return None;
+        }
+
+        if let Err(e) = takerate::validate_margin(deal.margin) {
+            warn!("Dropping deal {}: {}", id, e);
+            return None;
+        }
+
+        if let Err(e) = deal.targeting.common.validate() {
+            warn!("Dropping deal {}, invalid targeting: {}", id, e);
+            return None;
+        }
+        deal.targeting.common.normalize();
+
+        let normalized = self.currency.normalize_deal(deal);
+        if normalized.is_none() {
+            warn!(
+                "Dropping deal {}, no FX rate for currency {:?}",
+                id, currency
+            );
+        }
+
+        normalized
+    }
+
+    fn upsert(&self, deal: Deal) {
+        let id = deal.id.clone();
+
+        if deal.currency.is_some() {
+            self.priced.write().insert(id.clone(), deal.clone());
+        } else {
+            self.priced.write().remove(&id);
+        }
+
+        let deal = self.normalize(deal);
+
+        self.rebuild(
+            |m| match deal {
+                Some(deal) => {
+                    m.insert(id, Arc::new(deal));
+                }
+                None => {
+                    m.remove(&id);
+                }
+            },
+            None,
+        );
+    }
+
+    /// Re-convert every deal priced in a currency of its own with
+    /// the current rates, keeping the delivery state tracked since
+    fn reprice(&self) {
+        let priced: Vec<Deal> = self.priced.read().values().cloned().collect();
+        if priced.is_empty() {
+            return;
+        }
+
+        debug!("Repricing {} deals after FX refresh", priced.len());
+
+        let normalized: Vec<(String, Option<Deal>)> = priced
+            .into_iter()
+            .map(|d| (d.id.clone(), self.normalize(d)))
+            .collect();
+
+        self.rebuild(
+            |m| {
+                for (id, deal) in normalized {
+                    match deal {
+                        Some(mut deal) => {
+                            if let Some(prev) = m.get(&id) {
+                                deal.delivery_state = prev.delivery_state.clone();
+                            }
+                            m.insert(id, Arc::new(deal));
+                        }
+                        None => {
+                            m.remove(&id);
+                        }
+                    }
+                }
+            },
+            None,
+        );
+    }
+
     fn handle_event(&self, event: ProviderEvent<Deal>) {
         match event {
             ProviderEvent::Added(d) => {
                 debug!("Deal added: {} ({}) policy={:?}", d.name, d.id, d.policy);
-                self.rebuild(
-                    |m| {
-                        m.insert(d.id.clone(), Arc::new(d));
-                    },
-                    None,
-                );
+                self.upsert(d);
             }
             ProviderEvent::Modified(d) => {
                 debug!("Deal modified: {} ({}) status={:?}", d.name, d.id, d.status);
-                self.rebuild(
-                    |m| {
-                        m.insert(d.id.clone(), Arc::new(d));
-                    },
-                    None,
-                );
+                self.upsert(d);
             }
             ProviderEvent::Removed(id) => {
                 debug!("Deal removed: {}", id);
+                self.priced.write().remove(&id);
                 self.rebuild(
                     |m| {
                         m.remove(&id);
@@ -270,6 +379,16 @@ impl DealManager {
         self.cache.load().by_id.get(id).cloned()
     }
 
+    /// Every deal in the active set, direct and RTB
+    pub fn active_deals(&self) -> Vec<Arc<Deal>> {
+        let cache = self.cache.load();
+        cache
+            .active_ids()
+            .iter()
+            .filter_map(|id| cache.by_id.get(id).cloned())
+            .collect()
+    }
+
     pub fn direct_deals(&self) -> Arc<Vec<Arc<Deal>>> {
         Arc::clone(&self.cache.load().direct)
     }
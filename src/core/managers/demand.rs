@@ -1,11 +1,12 @@
 use crate::app::config::BidderConfig;
+use crate::core::demand::takerate;
 use crate::core::models::bidder::{Bidder, Endpoint};
 use crate::core::providers::{Provider, ProviderEvent};
-use anyhow::Error;
+use anyhow::{Error, anyhow};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, warn};
 
 pub enum DemandChange {
     Added {
@@ -34,14 +35,18 @@ pub struct DemandManager {
 }
 
 impl DemandManager {
-    pub async fn start(provider: Arc<dyn Provider<BidderConfig>>) -> Result<Arc<Self>, Error> {
-        let manager = Arc::new(Self {
+    fn new() -> Self {
+        Self {
             data: RwLock::new(BidderData {
                 list: Vec::new(),
                 index: HashMap::new(),
             }),
             callbacks: RwLock::new(Vec::new()),
-        });
+        }
+    }
+
+    pub async fn start(provider: Arc<dyn Provider<BidderConfig>>) -> Result<Arc<Self>, Error> {
+        let manager = Arc::new(Self::new());
 
         let mgr = manager.clone();
         let initial = provider
@@ -56,15 +61,45 @@ impl DemandManager {
         self.callbacks.write().push(cb);
     }
 
+    /// Bidders with a margin of 100% or more or an endpoint with an
+    /// invalid timeout policy are rejected, a rejected change keeps
+    /// the last valid bidder loaded
+    fn is_valid(bc: &BidderConfig) -> bool {
+        let valid = takerate::validate_margin(bc.bidder.margin).and_then(|_| {
+            bc.endpoints.iter().try_for_each(|endpoint| {
+                endpoint
+                    .timeout
+                    .validate()
+                    .map_err(|e| anyhow!("endpoint {}: {}", endpoint.stable_id(), e))
+            })
+        });
+
+        match valid {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Rejecting bidder {}: {}", bc.bidder.id, e);
+                false
+            }
+        }
+    }
+
+    /// Normalizes each endpoint's configured geo values to match
+    /// how request values are compared
+    fn endpoints(endpoints: Vec<Endpoint>) -> Vec<Arc<Endpoint>> {
+        endpoints
+            .into_iter()
+            .map(|mut endpoint| {
+                endpoint.targeting.geo.normalize();
+                Arc::new(endpoint)
+            })
+            .collect()
+    }
+
     fn load(&self, configs: Vec<BidderConfig>) {
         let list: Vec<(Arc<Bidder>, Vec<Arc<Endpoint>>)> = configs
             .into_iter()
-            .map(|bc| {
-                (
-                    Arc::new(bc.bidder),
-                    bc.endpoints.into_iter().map(Arc::new).collect(),
-                )
-            })
+            .filter(Self::is_valid)
+            .map(|bc| (Arc::new(bc.bidder), Self::endpoints(bc.endpoints)))
             .collect();
 
         let index: HashMap<String, Arc<Bidder>> = list
@@ -76,6 +111,12 @@ impl DemandManager {
     }
 
     fn handle_event(&self, event: ProviderEvent<BidderConfig>) {
+        if let ProviderEvent::Added(bc) | ProviderEvent::Modified(bc) = &event {
+            if !Self::is_valid(bc) {
+                return;
+            }
+        }
+
         let change = {
             let mut data = self.data.write();
 
@@ -83,8 +124,7 @@ impl DemandManager {
                 ProviderEvent::Added(bc) => {
                     debug!("Bidder added: {}", bc.bidder.id);
                     let bidder = Arc::new(bc.bidder);
-                    let endpoints: Vec<Arc<Endpoint>> =
-                        bc.endpoints.into_iter().map(Arc::new).collect();
+                    let endpoints = Self::endpoints(bc.endpoints);
 
                     data.index.insert(bidder.id.clone(), bidder.clone());
                     data.list.push((bidder.clone(), endpoints.clone()));
@@ -94,8 +134,7 @@ impl DemandManager {
                 ProviderEvent::Modified(bc) => {
                     debug!("Bidder modified: {}", bc.bidder.id);
                     let bidder = Arc::new(bc.bidder);
-                    let endpoints: Vec<Arc<Endpoint>> =
-                        bc.endpoints.into_iter().map(Arc::new).collect();
+                    let endpoints = Self::endpoints(bc.endpoints);
 
                     let prev_endpoints = data
                         .list
@@ -156,3 +195,51 @@ impl DemandManager {
         self.data.read().index.get(bidder_id).cloned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::targeting::ListFilter;
+    use compact_str::CompactString;
+    use rtb::bid_request::Geo;
+
+    #[test]
+    fn endpoint_geo_values_normalized() {
+        let mut endpoint = Endpoint {
+            id: "e1".into(),
+            ..Default::default()
+        };
+        endpoint.targeting.geo.regions_filter =
+            ListFilter::Allow([CompactString::from("ca")].into_iter().collect());
+        endpoint.targeting.geo.cities_filter =
+            ListFilter::Allow([CompactString::from("New York")].into_iter().collect());
+
+        let manager = DemandManager::new();
+        manager.load(vec![BidderConfig {
+            bidder: Bidder {
+                id: "b1".into(),
+                ..Default::default()
+            },
+            endpoints: vec![endpoint.clone()],
+        }]);
+
+        let geo = Geo {
+            region: "CA".into(),
+            city: "NEW YORK".into(),
+            ..Default::default()
+        };
+        let (_, endpoints) = &manager.bidders_endpoints()[0];
+        assert!(endpoints[0].targeting.geo.check(Some(&geo)));
+
+        manager.handle_event(ProviderEvent::Modified(BidderConfig {
+            bidder: Bidder {
+                id: "b1".into(),
+                ..Default::default()
+            },
+            endpoints: vec![endpoint],
+        }));
+
+        let (_, endpoints) = &manager.bidders_endpoints()[0];
+        assert!(endpoints[0].targeting.geo.check(Some(&geo)));
+    }
+}
@@ -9,6 +9,7 @@ mod placements;
 mod properties;
 mod publishers;
 mod shapers;
+mod supply_rules;
 
 pub use advertisers::*;
 pub use buyers::*;
@@ -20,3 +21,4 @@ pub use placements::*;
 pub use properties::*;
 pub use publishers::*;
 pub use shapers::*;
+pub use supply_rules::*;
@@ -1,10 +1,11 @@
+use crate::core::demand::takerate;
 use crate::core::models::publisher::Publisher;
 use crate::core::providers::{Provider, ProviderEvent};
 use anyhow::Error;
 use arc_swap::ArcSwap;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, warn};
 
 pub struct PublisherManager {
     pubs: ArcSwap<HashMap<String, Arc<Publisher>>>,
@@ -25,9 +26,22 @@ impl PublisherManager {
         Ok(manager)
     }
 
+    /// Publishers with a margin of 100% or more are rejected,
+    /// a rejected change keeps the last valid publisher loaded
+    fn is_valid(p: &Publisher) -> bool {
+        match takerate::validate_margin(Some(p.margin)) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Rejecting publisher {}: {}", p.id, e);
+                false
+            }
+        }
+    }
+
     fn load(&self, publishers: Vec<Publisher>) {
         let map: HashMap<String, Arc<Publisher>> = publishers
             .into_iter()
+            .filter(Self::is_valid)
             .map(|p| (p.id.clone(), Arc::new(p)))
             .collect();
 
@@ -35,6 +49,12 @@ impl PublisherManager {
     }
 
     fn handle_event(&self, event: ProviderEvent<Publisher>) {
+        if let ProviderEvent::Added(p) | ProviderEvent::Modified(p) = &event {
+            if !Self::is_valid(p) {
+                return;
+            }
+        }
+
         match event {
             ProviderEvent::Added(p) => {
                 debug!("Publisher added: {}", p.id);
@@ -2,12 +2,11 @@ use crate::core::cluster::ClusterDiscovery;
 use crate::core::managers::{DemandChange, DemandManager};
 use crate::core::models::bidder::{Bidder, Endpoint};
 use crate::core::models::shaping::{ShapingFeature, TrafficShaping};
-use crate::core::shaping::tree::TreeShaper;
+use crate::core::shaping::tree::{DEFAULT_MIN_DECISION_AUCTIONS, DEFAULT_SEGMENT_TTL, TreeShaper};
 use anyhow::{Error, bail};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
 use tracing::{debug, info, warn};
 
 fn validate_tree_params(control_percent: u32, features: &Vec<ShapingFeature>) -> Result<(), Error> {
@@ -57,8 +56,8 @@ fn create_shaper_for_endpoint(
             Some(Arc::new(TreeShaper::new(
                 &features,
                 &metric,
-                500,
-                &Duration::from_secs(10 * 60),
+                DEFAULT_MIN_DECISION_AUCTIONS,
+                &DEFAULT_SEGMENT_TTL,
                 control_percent.clone(),
                 endpoint.qps as u32,
                 min_target_metric.clone(),
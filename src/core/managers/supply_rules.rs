@@ -0,0 +1,192 @@
+use crate::core::filters::supply::{SupplyFacts, SupplyRuleMatcher};
+use crate::core::models::supply_rule::{SupplyPattern, SupplyRule, SupplyRuleAction};
+use crate::core::providers::{Provider, ProviderEvent};
+use anyhow::Error;
+use arc_swap::ArcSwap;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Rules applied whichever provider supplies the rest, spoofed bundles
+/// of premium apps that shouldn't be available on the open market.
+/// A provider rule with the same id replaces the built in one
+fn builtin_supply_rules() -> Vec<SupplyRule> {
+    let contains = ["netflix", "hulu", "disney", "zhiliaoapp"]
+        .into_iter()
+        .map(|b| SupplyPattern::Contains(b.into()));
+
+    let prefixes = [
+        "com.amazon.avod",
+        "com.amazon.firetv",
+        "com.peacocktv",
+        "com.hbo.",
+        "com.instagram",
+        "com.whatsapp",
+    ]
+    .into_iter()
+    .map(|b| SupplyPattern::Prefix(b.into()));
+
+    vec![SupplyRule {
+        id: "spoofed-premium-bundles".into(),
+        action: SupplyRuleAction::Block,
+        pub_ids: vec![],
+        bundles: contains.chain(prefixes).collect(),
+        domains: vec![],
+        store_ids: vec![],
+        ip_ranges: vec![],
+        user_agents: vec![],
+    }]
+}
+
+/// Maintains the compiled supply quality rules. Rules which fail to
+/// compile, e.g. an invalid regex, are skipped so a bad edit can't
+/// take down the rule set. The built in rules always apply unless
+/// replaced by a provider rule of the same id
+pub struct SupplyRuleManager {
+    /// Built in rule id -> matcher, restored when an overriding rule is removed
+    builtin: BTreeMap<String, Arc<SupplyRuleMatcher>>,
+    /// Rule id -> matcher, ordered so the first blocking match is stable
+    rules: ArcSwap<BTreeMap<String, Arc<SupplyRuleMatcher>>>,
+}
+
+impl SupplyRuleManager {
+    fn new() -> Self {
+        let builtin: BTreeMap<String, Arc<SupplyRuleMatcher>> = builtin_supply_rules()
+            .iter()
+            .filter_map(|r| Some((r.id.clone(), Self::compile(r)?)))
+            .collect();
+
+        Self {
+            rules: ArcSwap::from_pointee(builtin.clone()),
+            builtin,
+        }
+    }
+
+    pub async fn start(provider: Arc<dyn Provider<SupplyRule>>) -> Result<Arc<Self>, Error> {
+        let manager = Arc::new(Self::new());
+
+        let mgr = manager.clone();
+        let initial = provider
+            .start(Box::new(move |event| mgr.handle_event(event)))
+            .await?;
+
+        manager.load(initial);
+        Ok(manager)
+    }
+
+    fn compile(rule: &SupplyRule) -> Option<Arc<SupplyRuleMatcher>> {
+        match SupplyRuleMatcher::compile(rule) {
+            Ok(matcher) => Some(Arc::new(matcher)),
+            Err(e) => {
+                warn!("Skipping invalid supply rule {}: {}", rule.id, e);
+                None
+            }
+        }
+    }
+
+    fn load(&self, rules: Vec<SupplyRule>) {
+        let mut map = self.builtin.clone();
+        let mut loaded = 0;
+
+        for rule in &rules {
+            if let Some(matcher) = Self::compile(rule) {
+                map.insert(rule.id.clone(), matcher);
+                loaded += 1;
+            }
+        }
+
+        info!(
+            "Loaded {} of {} supply rules, {} rules active with built ins",
+            loaded,
+            rules.len(),
+            map.len()
+        );
+        self.rules.store(Arc::new(map));
+    }
+
+    /// Drops the rule, restoring the built in rule of the same id if any
+    fn remove(&self, map: &mut BTreeMap<String, Arc<SupplyRuleMatcher>>, id: &str) {
+        match self.builtin.get(id) {
+            Some(builtin) => map.insert(id.to_string(), builtin.clone()),
+            None => map.remove(id),
+        };
+    }
+
+    fn handle_event(&self, event: ProviderEvent<SupplyRule>) {
+        let mut map = (*self.rules.load_full()).clone();
+
+        match event {
+            ProviderEvent::Added(r) | ProviderEvent::Modified(r) => {
+                debug!("Supply rule updated: {}", r.id);
+                // an invalid edit drops the rule rather than keeping a stale one
+                match Self::compile(&r) {
+                    Some(matcher) => {
+                        map.insert(r.id.clone(), matcher);
+                    }
+                    None => self.remove(&mut map, &r.id),
+                };
+            }
+            ProviderEvent::Removed(id) => {
+                debug!("Supply rule removed: {}", id);
+                self.remove(&mut map, &id);
+            }
+        }
+
+        self.rules.store(Arc::new(map));
+    }
+
+    /// Rules matching the request, in rule id order
+    pub fn matching(&self, facts: &SupplyFacts) -> Vec<Arc<SupplyRuleMatcher>> {
+        self.rules
+            .load()
+            .values()
+            .filter(|rule| rule.matches(facts))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts(bundle: &str) -> SupplyFacts {
+        SupplyFacts {
+            bundle: bundle.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builtin_rules_apply_without_provider_rules() {
+        let manager = SupplyRuleManager::new();
+        manager.load(vec![]);
+
+        assert_eq!(manager.matching(&facts("com.netflix.mediaclient")).len(), 1);
+        assert!(manager.matching(&facts("com.example.game")).is_empty());
+    }
+
+    #[test]
+    fn provider_rule_overrides_builtin_until_removed() {
+        let manager = SupplyRuleManager::new();
+        let flag_only = SupplyRule {
+            id: "spoofed-premium-bundles".into(),
+            action: SupplyRuleAction::Flag,
+            pub_ids: vec![],
+            bundles: vec![SupplyPattern::Contains("netflix".into())],
+            domains: vec![],
+            store_ids: vec![],
+            ip_ranges: vec![],
+            user_agents: vec![],
+        };
+
+        manager.load(vec![flag_only]);
+        let matched = manager.matching(&facts("com.netflix.mediaclient"));
+        assert_eq!(matched[0].action, SupplyRuleAction::Flag);
+        assert!(manager.matching(&facts("com.hulu.plus")).is_empty());
+
+        manager.handle_event(ProviderEvent::Removed("spoofed-premium-bundles".into()));
+        let matched = manager.matching(&facts("com.hulu.plus"));
+        assert_eq!(matched[0].action, SupplyRuleAction::Block);
+    }
+}
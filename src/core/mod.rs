@@ -1,14 +1,18 @@
 pub mod cluster;
 pub mod config_manager;
+pub mod currency;
 pub mod demand;
 pub mod enrichment;
 pub mod events;
 pub mod filters;
 pub mod firestore;
+pub mod floors;
+pub mod geo;
 pub mod managers;
 pub mod models;
 pub mod observability;
 pub mod providers;
+pub mod segments;
 pub mod shaping;
 pub mod spec;
 pub mod usersync;
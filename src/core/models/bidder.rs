@@ -1,6 +1,9 @@
 use crate::core::enrichment::device::Os;
+use crate::core::models::secret::SecretRef;
 use crate::core::models::shaping::TrafficShaping;
 use crate::core::models::sync::SyncConfig;
+use crate::core::models::targeting::GeoTargeting;
+use anyhow::{Error, bail};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString};
@@ -69,6 +72,8 @@ impl Default for TargetingDeviceTypes {
 pub struct Targeting {
     /// Specific geos or all if empty
     pub geos: Vec<String>,
+    /// Region, metro, city, zip and radius filters within the geos
+    pub geo: GeoTargeting,
     /// Specific publishers only or all if empty
     pub pubs: Vec<String>,
     /// Specific OS types or all if empty
@@ -80,7 +85,9 @@ pub struct Targeting {
     pub devices: TargetingDeviceTypes,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, EnumString, Display)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default, EnumString, Display,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum HttpProto {
     /// Force http1.1 only
@@ -100,6 +107,134 @@ pub enum Encoding {
     Protobuf,
 }
 
+/// OpenRTB version of the json wire shape an endpoint speaks
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default, EnumString, Display)]
+pub enum OrtbVersion {
+    /// Legacy 2.5, fields such as regs.gdpr, user.consent, user.eids
+    /// and source.schain are sent under their 2.5 ext locations
+    #[serde(rename = "2.5")]
+    #[strum(serialize = "2.5")]
+    V2_5,
+    #[default]
+    #[serde(rename = "2.6")]
+    #[strum(serialize = "2.6")]
+    V2_6,
+}
+
+/// Deadline policy for callouts to an endpoint, which is both
+/// how long we wait and the tmax we send in the request
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EndpointTimeout {
+    /// Wait for the full remaining auction tmax
+    #[default]
+    Auction,
+    /// Fixed cap in ms, never beyond the auction tmax
+    Fixed { tmax_ms: u32 },
+    /// Deadline follows the observed p95 response latency
+    /// of the endpoint plus headroom, bounded by min and max.
+    /// Uses max (or the auction tmax) until enough samples exist
+    Adaptive {
+        #[serde(default = "default_adaptive_headroom_ms")]
+        headroom_ms: u32,
+        #[serde(default = "default_adaptive_min_ms")]
+        min_ms: u32,
+        /// Upper cap in ms, 0 for the auction tmax
+        #[serde(default)]
+        max_ms: u32,
+    },
+}
+
+fn default_adaptive_headroom_ms() -> u32 {
+    20
+}
+
+fn default_adaptive_min_ms() -> u32 {
+    50
+}
+
+impl EndpointTimeout {
+    /// Rejects policies which would time out every callout
+    /// or whose adaptive bounds are inverted
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            EndpointTimeout::Auction => {}
+            EndpointTimeout::Fixed { tmax_ms } => {
+                if *tmax_ms == 0 {
+                    bail!("fixed timeout tmax_ms must be above 0");
+                }
+            }
+            EndpointTimeout::Adaptive { min_ms, max_ms, .. } => {
+                if *max_ms != 0 && min_ms > max_ms {
+                    bail!(
+                        "adaptive timeout min_ms {} is above max_ms {}",
+                        min_ms,
+                        max_ms
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the callout deadline in ms for an auction with the
+    /// provided (already offset) tmax and the endpoint's observed
+    /// p95 latency, if enough has been observed
+    pub fn resolve(&self, auction_tmax: u32, p95_ms: Option<u32>) -> u32 {
+        match self {
+            EndpointTimeout::Auction => auction_tmax,
+            EndpointTimeout::Fixed { tmax_ms } => (*tmax_ms).min(auction_tmax),
+            EndpointTimeout::Adaptive {
+                headroom_ms,
+                min_ms,
+                max_ms,
+            } => {
+                let cap = match *max_ms {
+                    0 => auction_tmax,
+                    max_ms => max_ms.min(auction_tmax),
+                };
+
+                match p95_ms {
+                    Some(p95_ms) => (p95_ms + headroom_ms).clamp((*min_ms).min(cap), cap),
+                    None => cap,
+                }
+            }
+        }
+    }
+}
+
+/// Static header sent on every callout to an endpoint,
+/// e.g. an auth token or `x-openrtb-version`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHeader {
+    pub name: String,
+    /// Prepended to the resolved value, e.g. "Bearer "
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(flatten)]
+    pub value: SecretRef,
+}
+
+/// Client certificate and key presented to endpoints requiring mTLS
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TlsIdentity {
+    /// PEM encoded certificate chain
+    pub cert: SecretRef,
+    /// PEM encoded private key
+    pub key: SecretRef,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointTls {
+    /// Skip server certificate verification. Only for
+    /// partners with broken certificate chains
+    pub insecure_skip_verify: bool,
+    /// Optional client identity for mTLS
+    pub identity: Option<TlsIdentity>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Builder)]
 #[serde(default)]
 pub struct Endpoint {
@@ -115,7 +250,19 @@ pub struct Endpoint {
     pub protocol: HttpProto,
     #[serde(default)]
     pub encoding: Encoding,
+    /// Json wire version, 2.5 endpoints get downgraded requests
+    #[serde(default)]
+    pub ortb_version: OrtbVersion,
     pub targeting: Targeting,
+    /// Callout deadline and outbound tmax policy
+    #[serde(default)]
+    pub timeout: EndpointTimeout,
+    /// Extra static headers sent on each callout
+    #[serde(default)]
+    pub headers: Vec<EndpointHeader>,
+    /// Server verification and client identity, strict by default
+    #[serde(default)]
+    pub tls: EndpointTls,
 }
 
 impl Endpoint {
@@ -140,4 +287,105 @@ pub struct Bidder {
     #[builder(default = "true")]
     pub multi_imp: bool,
     pub usersync: Option<SyncConfig>,
+    /// Name of the registered ['BidderAdapter'] shaping requests and
+    /// responses for this bidder, passthrough if not set
+    pub adapter: Option<String>,
+    /// Margin percentage for this bidder's bids,
+    /// overriding the publisher margin
+    pub margin: Option<u32>,
+    /// Multiplier applied to this bidder's bid prices, e.g. 0.95
+    /// to discount a known billing discrepancy. None = 1.0
+    pub bid_adjustment: Option<f64>,
+    /// Send our first party segments to this bidder in user.data
+    pub share_segments: bool,
+    /// Restricts the EID sources passed to this bidder in user.eids,
+    /// e.g. uidapi.com, or * for all. All are passed if unset, and
+    /// none if empty
+    pub eid_sources: Option<Vec<String>>,
+}
+
+impl Bidder {
+    /// The bid adjustment multiplier, 1.0 if unset or not positive
+    pub fn adjustment_factor(&self) -> f64 {
+        self.bid_adjustment.filter(|f| *f > 0.0).unwrap_or(1.0)
+    }
+
+    /// Whether EIDs of the source may be passed to this bidder
+    pub fn allows_eid_source(&self, source: &str) -> bool {
+        match &self.eid_sources {
+            None => true,
+            Some(sources) => sources
+                .iter()
+                .any(|s| s == "*" || s.eq_ignore_ascii_case(source)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auction_timeout_uses_auction_tmax() {
+        assert_eq!(EndpointTimeout::Auction.resolve(250, Some(40)), 250);
+    }
+
+    #[test]
+    fn invalid_timeouts_rejected() {
+        assert!(EndpointTimeout::Fixed { tmax_ms: 0 }.validate().is_err());
+        assert!(EndpointTimeout::Fixed { tmax_ms: 150 }.validate().is_ok());
+
+        let adaptive = |min_ms, max_ms| EndpointTimeout::Adaptive {
+            headroom_ms: 20,
+            min_ms,
+            max_ms,
+        };
+        assert!(adaptive(300, 200).validate().is_err());
+        assert!(adaptive(50, 200).validate().is_ok());
+        assert!(adaptive(300, 0).validate().is_ok());
+    }
+
+    #[test]
+    fn fixed_timeout_capped_by_auction() {
+        let timeout = EndpointTimeout::Fixed { tmax_ms: 150 };
+
+        assert_eq!(timeout.resolve(250, None), 150);
+        assert_eq!(timeout.resolve(100, None), 100);
+    }
+
+    #[test]
+    fn adaptive_timeout_follows_p95() {
+        let timeout = EndpointTimeout::Adaptive {
+            headroom_ms: 20,
+            min_ms: 50,
+            max_ms: 200,
+        };
+
+        // no samples yet -> full cap
+        assert_eq!(timeout.resolve(300, None), 200);
+        assert_eq!(timeout.resolve(120, None), 120);
+        // p95 + headroom within bounds
+        assert_eq!(timeout.resolve(300, Some(80)), 100);
+        // floored at min, capped at max
+        assert_eq!(timeout.resolve(300, Some(5)), 50);
+        assert_eq!(timeout.resolve(300, Some(500)), 200);
+        // min never exceeds the auction tmax
+        assert_eq!(timeout.resolve(40, Some(5)), 40);
+    }
+
+    #[test]
+    fn eid_sources_default_to_all() {
+        let mut bidder = Bidder::default();
+        assert!(bidder.allows_eid_source("uidapi.com"));
+
+        bidder.eid_sources = Some(vec!["UIDapi.com".into()]);
+        assert!(bidder.allows_eid_source("uidapi.com"));
+        assert!(!bidder.allows_eid_source("liveramp.com"));
+
+        bidder.eid_sources = Some(vec!["*".into()]);
+        assert!(bidder.allows_eid_source("liveramp.com"));
+
+        bidder.eid_sources = Some(vec![]);
+        assert!(!bidder.allows_eid_source("uidapi.com"));
+    }
 }
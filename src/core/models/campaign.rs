@@ -73,6 +73,10 @@ pub struct Campaign {
     /// Replaces the old campaign_id-based creative lookup.
     #[serde(default)]
     pub creatives: Vec<CampaignCreative>,
+    /// ISO-4217 currency of the budget and strategy price.
+    /// None = the exchange accounting currency
+    #[serde(default)]
+    pub currency: Option<String>,
     #[serde(default)]
     pub delivery_state: DeliveryState,
 }
@@ -80,3 +84,30 @@ pub struct Campaign {
 fn default_budget_type() -> BudgetType {
     BudgetType::Total
 }
+
+#[cfg(test)]
+impl Campaign {
+    /// An active, fast paced campaign for buyer co1 with a $1000 total
+    /// budget at a $5 fixed price, in flight an hour either side of now,
+    /// for tests to update from
+    pub fn stub(id: &str) -> Self {
+        Self {
+            status: Status::Active,
+            buyer_id: "co1".into(),
+            id: id.into(),
+            start_date: Utc::now() - chrono::Duration::hours(1),
+            end_date: Utc::now() + chrono::Duration::hours(1),
+            name: "test".into(),
+            pacing: CampaignPacing::Fast,
+            budget: 1000.0,
+            budget_type: BudgetType::Total,
+            strategy: PricingStrategy::FixedPrice(5.0),
+            advertiser_id: "adv1".into(),
+            targeting: CampaignTargeting::default(),
+            click_url: None,
+            creatives: vec![],
+            currency: None,
+            delivery_state: Default::default(),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use crate::core::models::common::{DeliveryState, Status};
+use crate::core::models::publisher::Publisher;
 use crate::core::models::targeting::CommonTargeting;
+use anyhow::{Error, bail};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -34,7 +36,10 @@ pub enum DealOwner {
     /// Inventory included should only belong to
     /// associated pub id
     Publisher { id: String },
-    // Buyer(id String) if we want buyer self-curated deals?
+    /// Buyer self-curated deal, packaging inventory of
+    /// publishers opted in to curation. Only usable by
+    /// the buyer's own campaigns via their deal_ids
+    Buyer { id: String },
     /// A platform wide (admin) generated deal
     /// (no attached id)
     Platform,
@@ -48,6 +53,19 @@ pub enum DealPricing {
     Fixed(f64),
 }
 
+/// How RTB bids through a fixed price deal which come in above the
+/// agreed price are handled. Bids below a fixed price or deal floor
+/// are always rejected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DealPriceEnforcement {
+    /// Reject the bid
+    #[default]
+    Reject,
+    /// Lower the bid to the fixed price
+    Clamp,
+}
+
 /// Deal targeting object which houses
 /// the ['CommonTargeting'] as well as
 /// deal specific targeting fields
@@ -128,7 +146,91 @@ pub struct Deal {
     /// bids self-compete on price.
     #[serde(default)]
     pub takes_priority: bool,
+    /// Programmatic guaranteed. While the deal pacer finds the deal
+    /// behind its delivery schedule, bids through it win settlement
+    /// over all others, including other priority bids
+    #[serde(default)]
+    pub guaranteed: bool,
+    /// ISO-4217 currency of the pricing values.
+    /// None = the exchange accounting currency
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Margin percentage for bids through this deal,
+    /// overriding the bidder and publisher margins
+    #[serde(default)]
+    pub margin: Option<u32>,
+    /// Handling of RTB bids priced above a fixed deal price
+    #[serde(default)]
+    pub price_enforcement: DealPriceEnforcement,
+    /// Curation fee percentage of the gross price, paid to
+    /// the curator out of the exchange margin rather than
+    /// on top of the buyer price or from the publisher share
+    #[serde(default)]
+    pub curation_fee: Option<u32>,
     /// Delivery state tracked by the exchange, written to Firestore periodically.
     #[serde(default)]
     pub delivery_state: DeliveryState,
 }
+
+impl Deal {
+    /// Buyer curated deals must be direct only and
+    /// usable by no buyer other than their owner
+    pub fn validate_owner(&self) -> Result<(), Error> {
+        let DealOwner::Buyer { id } = &self.owner else {
+            return Ok(());
+        };
+
+        match &self.policy {
+            DemandPolicy::Direct { buyer_ids } if buyer_ids.iter().all(|b| b == id) => Ok(()),
+            DemandPolicy::Direct { .. } => {
+                bail!(
+                    "buyer deal {} lists buyers other than its owner {}",
+                    self.id,
+                    id
+                )
+            }
+            DemandPolicy::Rtb { .. } => bail!("buyer deal {} has an RTB demand policy", self.id),
+        }
+    }
+
+    /// Whether the deal may package the publisher's inventory.
+    /// Buyer curated deals require the publisher to opt in
+    pub fn allows_publisher(&self, publisher: &Publisher) -> bool {
+        match self.owner {
+            DealOwner::Buyer { .. } => publisher.curation_opt_in,
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Deal {
+    /// An active platform deal for buyer co1 with inherited pricing and
+    /// no flight, goal or overrides, for tests to update from
+    pub fn stub(id: &str) -> Self {
+        Self {
+            status: Status::Active,
+            id: id.into(),
+            name: "test deal".into(),
+            policy: DemandPolicy::Direct {
+                buyer_ids: vec!["co1".into()],
+            },
+            owner: DealOwner::Platform,
+            pricing: DealPricing::Inherit,
+            targeting: DealTargeting {
+                common: CommonTargeting::default(),
+            },
+            start_date: None,
+            end_date: None,
+            delivery_goal: None,
+            pacing: None,
+            takes_priority: false,
+            guaranteed: false,
+            currency: None,
+            margin: None,
+            price_enforcement: Default::default(),
+            curation_fee: None,
+            delivery_state: Default::default(),
+        }
+    }
+}
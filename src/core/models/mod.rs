@@ -8,6 +8,8 @@ pub mod deal;
 pub mod placement;
 pub mod property;
 pub mod publisher;
+pub mod secret;
 pub mod shaping;
+pub mod supply_rule;
 pub mod sync;
 pub mod targeting;
@@ -1,3 +1,4 @@
+use crate::core::enrichment::device::DeviceType;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 
@@ -9,6 +10,53 @@ pub enum PublisherType {
     Both,
 }
 
+/// Impression media type, as used in floor rules and floor keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImpFormat {
+    Banner,
+    Video,
+    Audio,
+    Native,
+}
+
+/// Publisher set static floor. Empty or None criteria match
+/// anything, and when several rules match the most specific
+/// rule wins, ties going to the highest floor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FloorRule {
+    /// Placement id or imp tagid
+    #[serde(default)]
+    pub placement_id: Option<String>,
+    /// Country codes as sent in device.geo.country
+    #[serde(default)]
+    pub countries: Vec<String>,
+    #[serde(default)]
+    pub device_types: Vec<DeviceType>,
+    #[serde(default)]
+    pub formats: Vec<ImpFormat>,
+    /// CPM floor
+    pub floor: f64,
+    /// ISO-4217 currency of the floor.
+    /// None = the exchange accounting currency
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+/// First party ids captured by the ad tag which may be sent on
+/// to bidders. Ids not shared are dropped when building the auction
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct IdSharing {
+    /// Publisher provided id, sent as user.id and an EID
+    /// under the publisher domain
+    pub ppid: bool,
+    /// UID2 token, sent as an uidapi.com EID
+    pub uid2: bool,
+    /// SharedID first party cookie, sent as a pubcid.org EID
+    pub sharedid: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Builder)]
 pub struct Publisher {
     pub id: String,
@@ -21,4 +69,18 @@ pub struct Publisher {
     /// User sync return URL which should include
     /// the rx ID macro where our uid should go
     pub sync_url: Option<String>,
+    /// Static floors, applied to requests alongside
+    /// any learned floors and before margin markup
+    #[serde(default)]
+    #[builder(default)]
+    pub floor_rules: Vec<FloorRule>,
+    /// Opted in to buyer curated deals packaging
+    /// this publisher's inventory
+    #[serde(default)]
+    #[builder(default)]
+    pub curation_opt_in: bool,
+    /// Which ad tag first party ids may leave the exchange
+    #[serde(default)]
+    #[builder(default)]
+    pub id_sharing: IdSharing,
 }
@@ -0,0 +1,66 @@
+use anyhow::{Error, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// A sensitive value which is either inlined, or preferably
+/// referenced from the environment or a mounted secret file
+/// so it never lands in the config or database in plain text
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SecretRef {
+    /// Read from the named environment variable
+    Env { env: String },
+    /// Read from a file, e.g. a mounted k8s secret. Trailing
+    /// whitespace is trimmed so editor newlines don't leak in
+    File { file: String },
+    /// Inline value, only sensible for non sensitive values
+    Value { value: String },
+}
+
+impl SecretRef {
+    pub fn resolve(&self) -> Result<String, Error> {
+        match self {
+            SecretRef::Env { env } => {
+                std::env::var(env).map_err(|e| anyhow!("Secret env var {} unavailable: {}", env, e))
+            }
+            SecretRef::File { file } => std::fs::read_to_string(file)
+                .map(|s| s.trim_end().to_string())
+                .map_err(|e| anyhow!("Secret file {} unreadable: {}", file, e)),
+            SecretRef::Value { value } => Ok(value.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_by_field() {
+        let secrets: Vec<SecretRef> =
+            serde_json::from_str(r#"[{"env":"A"},{"file":"/b"},{"value":"c"}]"#).unwrap();
+
+        assert_eq!(
+            secrets,
+            vec![
+                SecretRef::Env {
+                    env: "A".to_string()
+                },
+                SecretRef::File {
+                    file: "/b".to_string()
+                },
+                SecretRef::Value {
+                    value: "c".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_value_resolves() {
+        let secret = SecretRef::Value {
+            value: "2.6".to_string(),
+        };
+
+        assert_eq!(secret.resolve().unwrap(), "2.6");
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens to requests a supply rule matches
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyRuleAction {
+    /// Request is rejected before the auction
+    #[default]
+    Block,
+    /// Request continues to auction, the match is only counted
+    Flag,
+}
+
+/// Pattern a request value is matched against, case insensitive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyPattern {
+    Exact(String),
+    Prefix(String),
+    Contains(String),
+    Regex(String),
+}
+
+/// Supply quality rule, e.g. blocking spoofed premium app bundles
+/// or flagging a publisher's traffic from a hosting range. A request
+/// matches when it matches every criteria the rule defines, and a
+/// criteria matches when any of its values do. Empty criteria match
+/// anything, but a rule must define at least one besides publishers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplyRule {
+    /// Unique rule id, tagged on the counters of requests it matched
+    pub id: String,
+    #[serde(default)]
+    pub action: SupplyRuleAction,
+    /// Publishers the rule applies to, all publishers when empty
+    #[serde(default)]
+    pub pub_ids: Vec<String>,
+    /// App bundles
+    #[serde(default)]
+    pub bundles: Vec<SupplyPattern>,
+    /// Site domains, and app domains
+    #[serde(default)]
+    pub domains: Vec<SupplyPattern>,
+    /// App store ids, e.g. 284882215 or com.example.app, matched
+    /// against the bundle and the id in the app store url
+    #[serde(default)]
+    pub store_ids: Vec<String>,
+    /// Device IP ranges in CIDR notation, or single addresses
+    #[serde(default)]
+    pub ip_ranges: Vec<String>,
+    /// Device user agents
+    #[serde(default)]
+    pub user_agents: Vec<SupplyPattern>,
+}
@@ -1,5 +1,6 @@
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use strum::{AsRefStr, Display, EnumString};
 
 /// Kind of deployment for a user sync URL, e.g. img or iframe
@@ -18,4 +19,22 @@ pub enum SyncKind {
 pub struct SyncConfig {
     pub url: String,
     pub kind: SyncKind,
+    /// Higher priority bidders are synced first
+    #[serde(default)]
+    #[builder(default)]
+    pub priority: u32,
+    /// Relative value of a match with this bidder for weighted sync
+    /// order, scaled by its recent revenue share. Non positive = synced last
+    #[serde(default = "default_sync_weight")]
+    #[builder(default = "1.0")]
+    pub weight: f64,
+    /// How long a buyeruid from this bidder is fresh for before we
+    /// sync again, overriding the exchange wide freshness
+    #[serde(default, with = "humantime_serde")]
+    #[builder(default)]
+    pub resync_interval: Option<Duration>,
+}
+
+fn default_sync_weight() -> f64 {
+    1.0
 }
@@ -1,6 +1,11 @@
 use crate::core::enrichment::device::{DeviceType, Os};
+use crate::core::geo::GeoRadiusIndex;
 use ahash::AHashSet;
-use compact_str::CompactString;
+use anyhow::{Error, bail};
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use compact_str::{CompactString, ToCompactString};
+use rtb::bid_request::Geo;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::hash::Hash;
 
@@ -39,6 +44,27 @@ impl<T: Eq + Hash> ListFilter<T> {
             Self::Deny(set) => val.map_or(true, |v| !set.contains(v)),
         }
     }
+
+    /// Rewrites each configured value, dropping any mapped to None
+    pub fn normalize(&mut self, f: impl Fn(&T) -> Option<T>) {
+        if let Self::Allow(set) | Self::Deny(set) = self {
+            *set = set.iter().filter_map(f).collect();
+        }
+    }
+
+    /// Multi valued form of [`Self::check`], e.g. for a request's categories
+    /// - `Allow(set)` → at least one value must be in the set
+    /// - `Deny(set)`  → none of the values may be in the set
+    pub fn check_any<'a>(&self, vals: impl IntoIterator<Item = &'a T>) -> bool
+    where
+        T: 'a,
+    {
+        match self {
+            Self::Any => true,
+            Self::Allow(set) => vals.into_iter().any(|v| set.contains(v)),
+            Self::Deny(set) => !vals.into_iter().any(|v| set.contains(v)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -50,6 +76,190 @@ pub enum AllowedPropertyTypes {
     Any,
 }
 
+/// Sub-country geo targeting against device.geo. Values are
+/// matched upper cased, except cities which are lower cased.
+/// Regions and cities aren't unique across countries, so
+/// should be paired with a country filter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeoTargeting {
+    /// Region codes, ISO-3166-2 subdivision without the country, e.g. CA
+    pub regions_filter: ListFilter<CompactString>,
+    /// Metro codes, e.g. Nielsen DMA 501
+    pub metros_filter: ListFilter<CompactString>,
+    pub cities_filter: ListFilter<CompactString>,
+    pub zips_filter: ListFilter<CompactString>,
+    /// Only locations within one of these circles pass,
+    /// requests without a lat/lon don't. Any location if unset
+    pub radius: Option<GeoRadiusIndex>,
+}
+
+impl GeoTargeting {
+    fn field(value: &str, lower: bool) -> Option<CompactString> {
+        let value = value.trim();
+
+        match (value.is_empty(), lower) {
+            (true, _) => None,
+            (false, true) => Some(value.to_lowercase().to_compact_string()),
+            (false, false) => Some(value.to_uppercase().to_compact_string()),
+        }
+    }
+
+    /// Normalizes configured values the same way as request values
+    pub fn normalize(&mut self) {
+        let upper = |v: &CompactString| Self::field(v, false);
+        let lower = |v: &CompactString| Self::field(v, true);
+
+        self.regions_filter.normalize(upper);
+        self.metros_filter.normalize(upper);
+        self.cities_filter.normalize(lower);
+        self.zips_filter.normalize(upper);
+    }
+
+    fn is_any(&self) -> bool {
+        matches!(self.regions_filter, ListFilter::Any)
+            && matches!(self.metros_filter, ListFilter::Any)
+            && matches!(self.cities_filter, ListFilter::Any)
+            && matches!(self.zips_filter, ListFilter::Any)
+            && self.radius.is_none()
+    }
+
+    pub fn check(&self, geo: Option<&Geo>) -> bool {
+        if self.is_any() {
+            return true;
+        }
+
+        let region = geo.and_then(|g| Self::field(&g.region, false));
+        let metro = geo.and_then(|g| Self::field(&g.metro, false));
+        let city = geo.and_then(|g| Self::field(&g.city, true));
+        let zip = geo.and_then(|g| Self::field(&g.zip, false));
+
+        if !self.regions_filter.check(region.as_ref())
+            || !self.metros_filter.check(metro.as_ref())
+            || !self.cities_filter.check(city.as_ref())
+            || !self.zips_filter.check(zip.as_ref())
+        {
+            return false;
+        }
+
+        match &self.radius {
+            None => true,
+            // 0,0 is the proto default rather than a real location
+            Some(index) => geo
+                .filter(|g| g.lat != 0.0 || g.lon != 0.0)
+                .is_some_and(|g| index.contains(g.lat, g.lon)),
+        }
+    }
+}
+
+/// Timezone schedule windows are evaluated in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ScheduleTimezone {
+    /// Fixed offset in minutes from UTC (e.g. -300 for EST, +330 for IST)
+    Fixed { offset_mins: i16 },
+    /// IANA zone following its DST rules, e.g. America/New_York
+    Zone { tz: Tz },
+    /// The user's local time from device.geo.utcoffset, or the
+    /// fallback offset in minutes when the request doesn't carry one.
+    /// A zero utcoffset is treated as absent, as it's indistinguishable
+    User { fallback_offset_mins: i16 },
+}
+
+/// Hour range on chosen days of the week, in local time. Windows
+/// ending at or before their start hour run past midnight into
+/// the following day, e.g. Fri 22-2 covers Fri 22:00 to Sat 02:00
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaypartWindow {
+    pub days: Vec<Weekday>,
+    /// First hour of the window, 0-23
+    pub start_hour: u8,
+    /// Hour the window ends before, 1-24
+    pub end_hour: u8,
+}
+
+impl DaypartWindow {
+    fn validate(&self) -> Result<(), Error> {
+        if self.start_hour > 23 {
+            bail!("start_hour {} must be 0-23", self.start_hour);
+        }
+
+        if self.end_hour == 0 || self.end_hour > 24 {
+            bail!("end_hour {} must be 1-24", self.end_hour);
+        }
+
+        if self.start_hour == self.end_hour {
+            bail!("start_hour and end_hour are both {}", self.start_hour);
+        }
+
+        Ok(())
+    }
+
+    fn contains(&self, day: Weekday, hour: u8) -> bool {
+        if self.start_hour < self.end_hour {
+            return self.days.contains(&day) && hour >= self.start_hour && hour < self.end_hour;
+        }
+
+        (self.days.contains(&day) && hour >= self.start_hour)
+            || (self.days.contains(&day.pred()) && hour < self.end_hour)
+    }
+}
+
+/// Restricts delivery to day of week and hour windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleTargeting {
+    pub timezone: ScheduleTimezone,
+    /// Passes when any window contains the local time, no windows denies all
+    pub windows: Vec<DaypartWindow>,
+}
+
+impl ScheduleTargeting {
+    /// Rejects window hours out of range and offsets of a day or more
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.timezone {
+            ScheduleTimezone::Fixed { offset_mins: mins }
+            | ScheduleTimezone::User {
+                fallback_offset_mins: mins,
+            } => {
+                if FixedOffset::east_opt(mins as i32 * 60).is_none() {
+                    bail!("timezone offset {} minutes is out of range", mins);
+                }
+            }
+            ScheduleTimezone::Zone { .. } => {}
+        }
+
+        self.windows.iter().try_for_each(DaypartWindow::validate)
+    }
+
+    /// Whether `at` falls within a window, where `user_utcoffset`
+    /// is the user's offset from UTC in minutes if known
+    pub fn check(&self, at: DateTime<Utc>, user_utcoffset: Option<i32>) -> bool {
+        let offset_mins = match self.timezone {
+            ScheduleTimezone::Fixed { offset_mins } => offset_mins as i32,
+            ScheduleTimezone::Zone { tz } => {
+                let local = at.with_timezone(&tz);
+                return self.contains(local.weekday(), local.hour() as u8);
+            }
+            ScheduleTimezone::User {
+                fallback_offset_mins,
+            } => user_utcoffset
+                .filter(|o| *o != 0)
+                .unwrap_or(fallback_offset_mins as i32),
+        };
+
+        let Some(offset) = FixedOffset::east_opt(offset_mins * 60) else {
+            return false;
+        };
+
+        let local = at.with_timezone(&offset);
+        self.contains(local.weekday(), local.hour() as u8)
+    }
+
+    fn contains(&self, day: Weekday, hour: u8) -> bool {
+        self.windows.iter().any(|w| w.contains(day, hour))
+    }
+}
+
 /// Common targeting parameters across
 /// models which may use them, e.g.
 /// campaigns, deals, or other
@@ -62,4 +272,46 @@ pub struct CommonTargeting {
     pub bundle_domain_filter: ListFilter<CompactString>,
     pub placement_id_filter: ListFilter<CompactString>,
     pub allowed_property_types: AllowedPropertyTypes,
+    /// Region, metro, city, zip and radius filters within the targeted countries
+    #[serde(default)]
+    pub geo: GeoTargeting,
+    /// IAB content categories of the site/app and its content, including
+    /// section and page categories. Filtering a parent category covers
+    /// its subcategories, e.g. IAB2 covers IAB2-3. IAB 1.0 only,
+    /// request categories in other taxonomies (cattax) never match
+    #[serde(default)]
+    pub cats_filter: ListFilter<CompactString>,
+    /// Site/app and content keywords, compared lower cased
+    #[serde(default)]
+    pub keywords_filter: ListFilter<CompactString>,
+    /// First party audience segments of the user
+    #[serde(default)]
+    pub segments_filter: ListFilter<CompactString>,
+    /// Day of week and hour delivery windows, any time if unset
+    #[serde(default)]
+    pub schedule: Option<ScheduleTargeting>,
+}
+
+impl CommonTargeting {
+    /// Normalizes configured values to match how request
+    /// values are compared, called once at load
+    pub fn normalize(&mut self) {
+        self.geo.normalize();
+        self.cats_filter.normalize(|v| {
+            let v = v.trim();
+            (!v.is_empty()).then(|| v.to_uppercase().to_compact_string())
+        });
+        self.keywords_filter.normalize(|v| {
+            let v = v.trim();
+            (!v.is_empty()).then(|| v.to_lowercase().to_compact_string())
+        });
+    }
+
+    /// Rejects targeting which can't be evaluated as configured
+    pub fn validate(&self) -> Result<(), Error> {
+        match &self.schedule {
+            Some(schedule) => schedule.validate(),
+            None => Ok(()),
+        }
+    }
 }
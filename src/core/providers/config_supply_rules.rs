@@ -0,0 +1,31 @@
+use crate::core::config_manager::ConfigManager;
+use crate::core::models::supply_rule::SupplyRule;
+use crate::core::providers::{Provider, ProviderEvent};
+use anyhow::Error;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub struct ConfigSupplyRuleProvider {
+    config_manager: Arc<ConfigManager>,
+}
+
+impl ConfigSupplyRuleProvider {
+    pub fn new(config_manager: Arc<ConfigManager>) -> Self {
+        Self { config_manager }
+    }
+}
+
+#[async_trait]
+impl Provider<SupplyRule> for ConfigSupplyRuleProvider {
+    async fn start(
+        &self,
+        _on_event: Box<dyn Fn(ProviderEvent<SupplyRule>) + Send + Sync>,
+    ) -> Result<Vec<SupplyRule>, Error> {
+        Ok(self
+            .config_manager
+            .get()
+            .supply_rules
+            .clone()
+            .unwrap_or_default())
+    }
+}
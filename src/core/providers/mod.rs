@@ -7,6 +7,7 @@ mod config_demand;
 mod config_placements;
 mod config_properties;
 mod config_publisher;
+mod config_supply_rules;
 mod firestore;
 mod provider;
 
@@ -19,5 +20,6 @@ pub use config_demand::ConfigDemandProvider;
 pub use config_placements::ConfigPlacementProvider;
 pub use config_properties::ConfigPropertyProvider;
 pub use config_publisher::ConfigPublisherProvider;
+pub use config_supply_rules::ConfigSupplyRuleProvider;
 pub use firestore::FirestoreProvider;
 pub use provider::*;
@@ -0,0 +1,132 @@
+use crate::core::segments::store::SegmentStore;
+use async_trait::async_trait;
+use moka::sync::{Cache, CacheBuilder};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Local in memory segment store. Memberships are lost on restart
+/// and only seen by the node which recorded them
+pub struct LocalSegmentStore {
+    /// Cache of local uid -> map<segment_id, membership expiry>
+    cache: Cache<String, HashMap<String, Instant>>,
+    /// Cap on segments per user, the soonest expiring are dropped
+    max_per_user: usize,
+}
+
+impl LocalSegmentStore {
+    /// Users are evicted entirely the longest membership ttl after their
+    /// last membership was added, by which point all have expired, or
+    /// earlier once over `max_users`
+    pub fn new(max_ttl: Duration, max_per_user: usize, max_users: u64) -> Self {
+        Self {
+            cache: CacheBuilder::new(max_users).time_to_live(max_ttl).build(),
+            max_per_user,
+        }
+    }
+}
+
+#[async_trait]
+impl SegmentStore for LocalSegmentStore {
+    async fn add(&self, local_id: &str, segment_id: &str, ttl: Duration) {
+        let now = Instant::now();
+        let max_per_user = self.max_per_user.max(1);
+
+        // upserted under the entry lock so concurrent adds for a user don't race
+        self.cache
+            .entry_by_ref(local_id)
+            .and_upsert_with(|existing| {
+                let mut memberships = existing.map(|e| e.into_value()).unwrap_or_default();
+
+                memberships.retain(|_, expiry| *expiry > now);
+                memberships.insert(segment_id.to_string(), now + ttl);
+
+                while memberships.len() > max_per_user {
+                    let soonest = memberships
+                        .iter()
+                        .min_by_key(|(_, expiry)| **expiry)
+                        .map(|(id, _)| id.clone());
+
+                    match soonest {
+                        Some(id) => memberships.remove(&id),
+                        None => break,
+                    };
+                }
+
+                memberships
+            });
+    }
+
+    async fn load(&self, local_id: &str) -> Vec<String> {
+        let now = Instant::now();
+
+        self.cache
+            .get(local_id)
+            .map(|memberships| {
+                memberships
+                    .into_iter()
+                    .filter(|(_, expiry)| *expiry > now)
+                    .map(|(id, _)| id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    async fn remove(&self, local_id: &str) -> usize {
+        self.cache
+            .remove(local_id)
+            .map_or(0, |memberships| memberships.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn memberships_expire_and_are_capped() {
+        let store = LocalSegmentStore::new(Duration::from_hours(1), 2, 100);
+
+        store.add("rx-1", "expired", Duration::ZERO).await;
+        store.add("rx-1", "short", Duration::from_secs(60)).await;
+        store.add("rx-1", "long", Duration::from_secs(600)).await;
+        store.add("rx-1", "longest", Duration::from_secs(900)).await;
+
+        let mut segments = store.load("rx-1").await;
+        segments.sort();
+
+        assert_eq!(segments, vec!["long", "longest"]);
+        assert!(store.load("rx-2").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn remove_drops_all_memberships() {
+        let store = LocalSegmentStore::new(Duration::from_hours(1), 10, 100);
+
+        store.add("rx-1", "a", Duration::from_secs(60)).await;
+        store.add("rx-1", "b", Duration::from_secs(60)).await;
+
+        assert_eq!(store.remove("rx-1").await, 2);
+        assert!(store.load("rx-1").await.is_empty());
+        assert_eq!(store.remove("rx-1").await, 0);
+    }
+
+    #[tokio::test]
+    async fn concurrent_adds_are_not_lost() {
+        let store = std::sync::Arc::new(LocalSegmentStore::new(Duration::from_hours(1), 100, 100));
+
+        let adds = (0..50).map(|i| {
+            let store = store.clone();
+            tokio::spawn(async move {
+                store
+                    .add("rx-1", &format!("seg-{i}"), Duration::from_secs(60))
+                    .await
+            })
+        });
+
+        for add in adds.collect::<Vec<_>>() {
+            add.await.unwrap();
+        }
+
+        assert_eq!(store.load("rx-1").await.len(), 50);
+    }
+}
@@ -0,0 +1,17 @@
+mod local_store;
+mod store;
+
+pub use local_store::LocalSegmentStore;
+pub use store::SegmentStore;
+
+/// Longest segment id accepted from segment pixels
+pub const MAX_SEGMENT_ID_LEN: usize = 64;
+
+/// Segment ids are short alphanumeric keys, with - _ and . allowed
+pub fn validate_segment_id(segment_id: &str) -> bool {
+    !segment_id.is_empty()
+        && segment_id.len() <= MAX_SEGMENT_ID_LEN
+        && segment_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
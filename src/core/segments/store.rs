@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// A first party audience segment backend store, holding
+/// the segments each local uid is a member of
+#[async_trait]
+pub trait SegmentStore: Send + Sync {
+    /// Adds the local uid to a segment, or refreshes its
+    /// membership, which then lasts for the ttl
+    async fn add(&self, local_id: &str, segment_id: &str, ttl: Duration);
+
+    /// Loads the segment ids the local uid is currently a member of
+    async fn load(&self, local_id: &str) -> Vec<String>;
+
+    /// Removes the local uid from all segments, e.g. on opt out,
+    /// returning how many memberships were removed
+    async fn remove(&self, local_id: &str) -> usize;
+}
@@ -1,3 +1,5 @@
+#[cfg(any(test, feature = "simulator"))]
+pub mod simulator;
 pub mod threshold;
 pub mod tree;
 mod utils;
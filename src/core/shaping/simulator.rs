@@ -0,0 +1,353 @@
+use crate::core::cluster::ClusterDiscovery;
+use crate::core::cluster::impls::FixedClusterDiscovery;
+use crate::core::models::shaping::TrafficShaping;
+use crate::core::shaping::tree::{
+    DEFAULT_MIN_DECISION_AUCTIONS, DEFAULT_SEGMENT_TTL, ShapingDecision, TreeShaper,
+};
+use anyhow::{Error, anyhow, bail};
+use governor::clock::FakeRelativeClock;
+use governor::{Quota, RateLimiter};
+use rtb::BidRequest;
+use rtb::bid_response::Bid;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A single recorded auction as replayed by the simulator, one
+/// json object per line in the auction log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionLogRecord {
+    /// The outbound request as it would have been handed to shaping,
+    /// from which the configured shaping features are extracted
+    pub req: BidRequest,
+    /// The bid received for this auction, if any
+    #[serde(default)]
+    pub bid: Option<Bid>,
+    /// Gross CPM billed for this auction, 0 if it never billed
+    #[serde(default)]
+    pub cpm_gross: f64,
+    /// Cost CPM paid to the publisher for this auction
+    #[serde(default)]
+    pub cpm_cost: f64,
+}
+
+/// A candidate endpoint shaping configuration to simulate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationScenario {
+    /// Display name for the report
+    pub name: String,
+    /// Cluster wide endpoint QPS limit, 0 for unlimited
+    #[serde(default)]
+    pub qps_limit: u32,
+    /// The shaping config under test, same shape as on ['Endpoint']
+    pub shaping: TrafficShaping,
+}
+
+/// Outcome totals of replaying an auction log through a scenario
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SimulationReport {
+    pub name: String,
+    /// Simulated wall time covered by the replay
+    pub duration: Duration,
+    /// Total recorded auctions replayed
+    pub requests: u64,
+    /// Auctions which would have been sent to the bidder
+    pub sent: u64,
+    pub passed_metric: u64,
+    pub passed_exploratory: u64,
+    pub passed_boost: u64,
+    pub blocked_shaping: u64,
+    /// Auctions which passed shaping but were dropped by the QPS limit
+    pub blocked_qps: u64,
+    /// Billed gross revenue in dollars across all recorded auctions
+    pub revenue_total: f64,
+    /// Billed gross revenue in dollars of auctions which would have been sent
+    pub revenue_retained: f64,
+}
+
+impl SimulationReport {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Share of recorded auctions which would have been sent, 0-1
+    pub fn pass_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+
+        self.sent as f64 / self.requests as f64
+    }
+
+    /// Share of recorded revenue which would have been kept, 0-1
+    pub fn revenue_retained_rate(&self) -> f64 {
+        if self.revenue_total <= 0.0 {
+            return 0.0;
+        }
+
+        self.revenue_retained / self.revenue_total
+    }
+
+    /// Average outbound QPS no longer sent to the bidder
+    pub fn qps_saved(&self) -> f64 {
+        let secs = self.duration.as_secs_f64();
+        if secs <= 0.0 {
+            return 0.0;
+        }
+
+        (self.requests - self.sent) as f64 / secs
+    }
+
+    /// Average outbound QPS which would have been sent to the bidder
+    pub fn qps_sent(&self) -> f64 {
+        let secs = self.duration.as_secs_f64();
+        if secs <= 0.0 {
+            return 0.0;
+        }
+
+        self.sent as f64 / secs
+    }
+}
+
+/// Mirrors the training performed by the live pipeline for a sent
+/// callout, with the billing event applied immediately after the bid
+fn train(shaper: &TreeShaper, record: &AuctionLogRecord) -> Result<(), Error> {
+    shaper.record_auction(&record.req)?;
+
+    let bid = match &record.bid {
+        Some(bid) => bid,
+        None => return Ok(()),
+    };
+
+    let shaping_key = shaper.record_bid(&record.req, bid)?;
+
+    if record.cpm_gross > 0.0 {
+        shaper.record_impression(&shaping_key, record.cpm_gross, record.cpm_cost)?;
+    }
+
+    Ok(())
+}
+
+fn build_shaper(scenario: &SimulationScenario) -> Result<Option<TreeShaper>, Error> {
+    match &scenario.shaping {
+        TrafficShaping::None => Ok(None),
+        TrafficShaping::Tree {
+            control_percent,
+            metric,
+            features,
+            min_target_metric,
+        } => {
+            if *control_percent == 0 || features.is_empty() {
+                bail!(
+                    "Scenario {} requires a non zero control percent and features",
+                    scenario.name
+                );
+            }
+
+            // the simulator is always a single node, qps_limit is taken as the node share
+            let cluster: Arc<dyn ClusterDiscovery> = Arc::new(FixedClusterDiscovery::new(1));
+
+            Ok(Some(TreeShaper::new(
+                features,
+                metric,
+                DEFAULT_MIN_DECISION_AUCTIONS,
+                &DEFAULT_SEGMENT_TTL,
+                *control_percent,
+                scenario.qps_limit,
+                *min_target_metric,
+                cluster,
+            )))
+        }
+    }
+}
+
+/// Replays the recorded auctions through the real ['TreeShaper'] and
+/// ['QpsHistogram'] of the scenario, at a fixed rate of `target_qps`
+/// against a simulated clock. The QPS limit is the same governor quota
+/// the live endpoint limiter uses, so it too allows a one second burst
+/// before replenishing evenly. Blocked auctions are never trained on,
+/// exactly as a skipped callout would not be in the live pipeline.
+///
+/// Must run on a current thread runtime with a paused clock, so the
+/// shaper threshold and prune tasks tick in simulated rather than
+/// wall time. Use a fresh runtime per scenario.
+pub async fn simulate(
+    records: &[AuctionLogRecord],
+    scenario: &SimulationScenario,
+    target_qps: u32,
+) -> Result<SimulationReport, Error> {
+    if target_qps == 0 {
+        bail!("Simulation target qps must be > 0");
+    }
+
+    let shaper = build_shaper(scenario)?;
+    let step = Duration::from_secs(1) / target_qps;
+    let start = Instant::now();
+
+    // governor keeps its own clock, so is driven by the simulated offset
+    let limiter_clock = FakeRelativeClock::default();
+    let limiter = NonZeroU32::new(scenario.qps_limit)
+        .map(|qps| RateLimiter::direct_with_clock(Quota::per_second(qps), limiter_clock.clone()));
+
+    let mut report = SimulationReport::new(&scenario.name);
+    let mut limiter_offset = Duration::ZERO;
+
+    for (idx, record) in records.iter().enumerate() {
+        let offset = step * u32::try_from(idx)?;
+        let sim_now = start + offset;
+
+        // only yield to the clock once it has fallen a full timer tick
+        // behind, letting the shaper threshold cycle run at 1s boundaries
+        if sim_now >= Instant::now() + Duration::from_millis(1) {
+            tokio::time::sleep_until(sim_now).await;
+        }
+
+        limiter_clock.advance(offset - limiter_offset);
+        limiter_offset = offset;
+
+        report.requests += 1;
+        report.revenue_total += record.cpm_gross / 1000.0;
+
+        if let Some(shaper) = &shaper {
+            let result = shaper
+                .passes_shaping(&record.req)
+                .map_err(|e| anyhow!("Shaping failed on record {}: {}", idx, e))?;
+
+            match result.decision {
+                ShapingDecision::PassedMetric => report.passed_metric += 1,
+                ShapingDecision::PassedExploratory => report.passed_exploratory += 1,
+                ShapingDecision::PassedBoost => report.passed_boost += 1,
+                ShapingDecision::Blocked => {
+                    report.blocked_shaping += 1;
+                    continue;
+                }
+            }
+        }
+
+        if limiter.as_ref().is_some_and(|l| l.check().is_err()) {
+            report.blocked_qps += 1;
+            continue;
+        }
+
+        report.sent += 1;
+        report.revenue_retained += record.cpm_gross / 1000.0;
+
+        if let Some(shaper) = &shaper {
+            train(shaper, record)?;
+        }
+    }
+
+    report.duration = step * u32::try_from(records.len())?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::shaping::{Metric, ShapingFeature};
+    use rtb::bid_request::{Device, Geo};
+
+    fn record(country: &str, cpm_gross: f64) -> AuctionLogRecord {
+        let mut req = BidRequest::default();
+        req.device = Some(Device {
+            geo: Some(Geo {
+                country: country.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        let bid = match cpm_gross > 0.0 {
+            true => Some(Bid {
+                price: cpm_gross,
+                ..Default::default()
+            }),
+            false => None,
+        };
+
+        AuctionLogRecord {
+            req,
+            bid,
+            cpm_gross,
+            cpm_cost: cpm_gross * 0.8,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn no_shaping_unlimited_sends_everything() {
+        let records: Vec<_> = (0..1_000).map(|_| record("USA", 1.0)).collect();
+        let scenario = SimulationScenario {
+            name: "baseline".into(),
+            qps_limit: 0,
+            shaping: TrafficShaping::None,
+        };
+
+        let report = simulate(&records, &scenario, 100).await.unwrap();
+
+        assert_eq!(report.sent, 1_000);
+        assert_eq!(report.pass_rate(), 1.0);
+        assert!((report.revenue_retained_rate() - 1.0).abs() < 1e-9);
+        assert_eq!(report.qps_saved(), 0.0);
+        assert_eq!(report.duration, Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn no_shaping_qps_limit_bursts_then_caps() {
+        let records: Vec<_> = (0..1_000).map(|_| record("USA", 0.0)).collect();
+        let scenario = SimulationScenario {
+            name: "limited".into(),
+            qps_limit: 40,
+            shaping: TrafficShaping::None,
+        };
+
+        let report = simulate(&records, &scenario, 100).await.unwrap();
+
+        // an initial burst of 40, then 40 per second over the remaining 9.99s
+        assert!((439..=440).contains(&report.sent), "sent {}", report.sent);
+        assert_eq!(report.sent + report.blocked_qps, 1_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn tree_shaping_prefers_valuable_segment() {
+        // half the traffic bills, half never bids
+        let records: Vec<_> = (0..40_000)
+            .map(|i| match i % 2 {
+                0 => record("USA", 2.0),
+                _ => record("CAN", 0.0),
+            })
+            .collect();
+
+        let scenario = SimulationScenario {
+            name: "tree".into(),
+            qps_limit: 500,
+            shaping: TrafficShaping::Tree {
+                control_percent: 5,
+                metric: Metric::Rpm,
+                features: vec![ShapingFeature::Geo],
+                min_target_metric: 0.0,
+            },
+        };
+
+        let report = simulate(&records, &scenario, 1_000).await.unwrap();
+
+        assert!(report.blocked_shaping > 0);
+        assert!(report.revenue_retained_rate() > report.pass_rate());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_target_qps_rejected() {
+        let scenario = SimulationScenario {
+            name: "bad".into(),
+            qps_limit: 0,
+            shaping: TrafficShaping::None,
+        };
+
+        assert!(simulate(&[], &scenario, 0).await.is_err());
+    }
+}
@@ -108,6 +108,13 @@ pub struct ShapingResult {
     pub raw_prediction: Option<RtbPredictionOutput>,
 }
 
+/// Min auctions a segment must observe before it is
+/// trusted to make its own predictions
+pub const DEFAULT_MIN_DECISION_AUCTIONS: u32 = 500;
+
+/// Inactivity window before an ad segment is pruned
+pub const DEFAULT_SEGMENT_TTL: Duration = Duration::from_secs(10 * 60);
+
 struct DynamicConfig {
     metric: Metric,
     explore_percent: u32,
@@ -3,5 +3,7 @@ mod logictree;
 mod serializers;
 mod utils;
 
+pub use logictree::DEFAULT_MIN_DECISION_AUCTIONS;
+pub use logictree::DEFAULT_SEGMENT_TTL;
 pub use logictree::ShapingDecision;
 pub use logictree::TreeShaper;
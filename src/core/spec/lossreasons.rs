@@ -0,0 +1,12 @@
+use rtb::spec_list;
+
+spec_list! {
+    /// Standard OpenRTB loss reason, used for deal bids below the deal
+    /// floor and for bids not matching a fixed price sent to the bidder
+    BID_BELOW_DEAL_FLOOR = 101 => "Bid was Below Deal Floor",
+}
+
+/// Whether the loss reason is a deal price enforcement rejection
+pub fn is_deal_price_mismatch(code: u32) -> bool {
+    code == BID_BELOW_DEAL_FLOOR
+}
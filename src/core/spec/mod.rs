@@ -1,4 +1,5 @@
 pub mod dimensions;
+pub mod lossreasons;
 pub mod nobidreasons;
 
 pub use dimensions::*;
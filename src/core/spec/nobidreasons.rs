@@ -13,4 +13,6 @@ spec_list! {
     MISSING_DOMAIN_OR_BUNDLE = 505 => "Missing Domain or Bundle",
     MISSING_DEVICE_DETAILS = 506 => "Missing Device Details (IP or UA)",
     NO_IMPRESSIONS_MATCH_AVAILABLE_VIEWPORT = 507 => "No Impressions Match Available Viewport",
+    /// Request matched a blocking supply quality rule
+    BLOCKED_BY_SUPPLY_RULE = 508 => "Blocked By Supply Rule",
 }
@@ -0,0 +1,167 @@
+use crate::app::config::UserSyncConfig;
+use crate::core::models::bidder::Bidder;
+use crate::core::usersync::model::SyncEntry;
+use crate::core::usersync::selection;
+use std::collections::HashMap;
+use std::sync::Arc;
+use url::form_urlencoded;
+
+/// Most bidders remembered per chain, bounding the cookie size
+const MAX_CHAIN_ATTEMPTS: usize = 32;
+
+/// State of a redirect sync chain, carried between hops in the
+/// ['constants::CONST_REX_SYNC_CHAIN_COOKIE'] cookie
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncChain {
+    /// Publisher the chain was started for
+    pub pubid: String,
+    /// Bidders already redirected through in this chain,
+    /// whether or not they returned a buyeruid
+    pub attempted: Vec<String>,
+}
+
+impl SyncChain {
+    pub fn new(pubid: String) -> Self {
+        Self {
+            pubid,
+            attempted: Vec::new(),
+        }
+    }
+
+    /// Whether a /sync/in return from the bidder is the hop this chain
+    /// is waiting on, i.e. the bidder last redirected to. Other returns,
+    /// such as iframe syncs while the chain cookie lives, don't continue it
+    pub fn awaits(&self, bidder_id: &str) -> bool {
+        self.attempted.last().is_some_and(|id| id == bidder_id)
+    }
+
+    /// Cookie safe encoding of the chain
+    pub fn encode(&self) -> String {
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+
+        serializer.append_pair("p", &self.pubid);
+
+        for bidder_id in &self.attempted {
+            serializer.append_pair("b", bidder_id);
+        }
+
+        serializer.finish()
+    }
+
+    /// Parses an encoded chain, None if it has no publisher
+    pub fn decode(value: &str) -> Option<Self> {
+        let mut chain = SyncChain::default();
+
+        for (key, value) in form_urlencoded::parse(value.as_bytes()) {
+            match key.as_ref() {
+                "p" => chain.pubid = value.into_owned(),
+                "b" if chain.attempted.len() < MAX_CHAIN_ATTEMPTS => {
+                    chain.attempted.push(value.into_owned())
+                }
+                _ => {}
+            }
+        }
+
+        (!chain.pubid.is_empty()).then_some(chain)
+    }
+}
+
+/// Picks the bidder a redirect chain should sync next, None once
+/// the chain has reached its limit or nobody needs syncing. See
+/// ['selection::select_sync_bidders'] for which bidders qualify
+pub fn next_chain_bidder(
+    bidders: &[Arc<Bidder>],
+    entries: &HashMap<String, SyncEntry>,
+    chain: &SyncChain,
+    revenue_shares: &HashMap<String, f64>,
+    config: &UserSyncConfig,
+    rotation: usize,
+    now_ms: u64,
+) -> Option<Arc<Bidder>> {
+    if chain.attempted.len() >= config.max_chain_syncs.min(MAX_CHAIN_ATTEMPTS) {
+        return None;
+    }
+
+    selection::select_sync_bidders(
+        bidders,
+        entries,
+        &chain.attempted,
+        revenue_shares,
+        config,
+        1,
+        rotation,
+        now_ms,
+    )
+    .pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::SyncOrder;
+    use crate::core::models::sync::{SyncConfig, SyncKind};
+
+    fn bidder(id: &str) -> Arc<Bidder> {
+        Arc::new(Bidder {
+            id: id.to_string(),
+            usersync: Some(SyncConfig {
+                url: format!("https://{id}.example/sync"),
+                kind: SyncKind::Image,
+                priority: 0,
+                weight: 1.0,
+                resync_interval: None,
+            }),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn round_trips_chain_cookie() {
+        let chain = SyncChain {
+            pubid: "pub 1".into(),
+            attempted: vec!["b&1".into(), "b=2".into()],
+        };
+
+        assert_eq!(SyncChain::decode(&chain.encode()), Some(chain));
+        assert_eq!(SyncChain::decode("b=1"), None);
+    }
+
+    #[test]
+    fn awaits_only_last_redirected_bidder() {
+        let mut chain = SyncChain::new("pub".into());
+        assert!(!chain.awaits("a"));
+
+        chain.attempted = vec!["a".into(), "b".into()];
+        assert!(chain.awaits("b"));
+        assert!(!chain.awaits("a"));
+        assert!(!chain.awaits("c"));
+    }
+
+    #[test]
+    fn skips_attempted_and_stops_at_chain_limit() {
+        let bidders = vec![bidder("a"), bidder("b")];
+        let entries = HashMap::new();
+        let revenue_shares = HashMap::new();
+
+        let config = UserSyncConfig {
+            max_chain_syncs: 2,
+            order: SyncOrder::Rotate,
+            ..Default::default()
+        };
+
+        let mut chain = SyncChain::new("pub".into());
+
+        let next = |chain: &SyncChain| {
+            next_chain_bidder(&bidders, &entries, chain, &revenue_shares, &config, 1, 0)
+                .map(|b| b.id.clone())
+        };
+
+        assert_eq!(next(&chain).as_deref(), Some("b"));
+
+        chain.attempted.push("b".into());
+        assert_eq!(next(&chain).as_deref(), Some("a"));
+
+        chain.attempted.push("a".into());
+        assert_eq!(next(&chain), None);
+    }
+}
@@ -11,3 +11,11 @@ pub const CONST_REX_COOKIE_ID_PARAM: &str = "rxid";
 /// represents the spot which we replace with the local exchange
 /// user ID
 pub const CONST_REX_LOCAL_ID_MACRO: &str = "{RXID}";
+
+/// Cookie marking a user who opted out of tracking, whom we don't
+/// issue an rxid, sync or pass buyeruids for
+pub const CONST_REX_OPTOUT_COOKIE: &str = "rxoptout";
+
+/// Cookie holding the state of an in progress redirect sync chain,
+/// so /sync/in knows to hand back to /sync/out for the next bidder
+pub const CONST_REX_SYNC_CHAIN_COOKIE: &str = "rxsc";
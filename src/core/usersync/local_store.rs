@@ -41,4 +41,8 @@ impl SyncStore for LocalStore {
     async fn load(&self, local_id: &String) -> Option<HashMap<String, SyncEntry>> {
         self.cache.get(local_id)
     }
+
+    async fn remove(&self, local_id: &String) -> Option<HashMap<String, SyncEntry>> {
+        self.cache.remove(local_id)
+    }
 }
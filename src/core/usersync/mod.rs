@@ -1,8 +1,12 @@
+pub mod chain;
 pub mod constants;
 mod local_store;
 pub mod model;
+mod revenue;
+pub mod selection;
 mod store;
 pub mod utils;
 
 pub use local_store::LocalStore;
+pub use revenue::SyncRevenue;
 pub use store::SyncStore;
@@ -0,0 +1,120 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Gross revenue per bidder from billed impressions, kept over
+/// the current and previous tumbling windows
+struct RevenueWindows {
+    started: Instant,
+    current: HashMap<String, f64>,
+    previous: HashMap<String, f64>,
+}
+
+/// Recent revenue earned per bidder on this node, so weighted sync
+/// order can favour the bidders whose matches are earning the most
+pub struct SyncRevenue {
+    window: Duration,
+    windows: Mutex<RevenueWindows>,
+}
+
+impl SyncRevenue {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            windows: Mutex::new(RevenueWindows {
+                started: Instant::now(),
+                current: HashMap::new(),
+                previous: HashMap::new(),
+            }),
+        }
+    }
+
+    fn rotate(&self, windows: &mut RevenueWindows, now: Instant) {
+        let elapsed = now.saturating_duration_since(windows.started);
+
+        if elapsed < self.window {
+            return;
+        }
+
+        // a whole window without events leaves nothing recent
+        windows.previous = if elapsed < self.window * 2 {
+            std::mem::take(&mut windows.current)
+        } else {
+            windows.current.clear();
+            HashMap::new()
+        };
+        windows.started = now;
+    }
+
+    fn record_at(&self, bidder_id: &str, revenue_cpm: f64, now: Instant) {
+        if revenue_cpm <= 0.0 {
+            return;
+        }
+
+        let mut windows = self.windows.lock();
+        self.rotate(&mut windows, now);
+
+        *windows.current.entry(bidder_id.to_string()).or_default() += revenue_cpm;
+    }
+
+    fn shares_at(&self, now: Instant) -> HashMap<String, f64> {
+        let mut windows = self.windows.lock();
+        self.rotate(&mut windows, now);
+
+        let mut totals = windows.previous.clone();
+        for (bidder_id, revenue) in &windows.current {
+            *totals.entry(bidder_id.clone()).or_default() += revenue;
+        }
+
+        let sum: f64 = totals.values().sum();
+        if sum <= 0.0 {
+            return HashMap::new();
+        }
+
+        totals.values_mut().for_each(|revenue| *revenue /= sum);
+        totals
+    }
+
+    /// Records the gross CPM of a billed impression won by the bidder
+    pub fn record(&self, bidder_id: &str, revenue_cpm: f64) {
+        self.record_at(bidder_id, revenue_cpm, Instant::now());
+    }
+
+    /// Each bidder's share of recent revenue, 0-1. Bidders without
+    /// recent revenue are absent, and the map is empty if none have
+    pub fn shares(&self) -> HashMap<String, f64> {
+        self.shares_at(Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_cover_current_and_previous_window() {
+        let revenue = SyncRevenue::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(revenue.shares_at(start).is_empty());
+
+        revenue.record_at("a", 3.0, start);
+        revenue.record_at("b", 1.0, start + Duration::from_secs(70));
+
+        let shares = revenue.shares_at(start + Duration::from_secs(70));
+        assert_eq!(shares.get("a"), Some(&0.75));
+        assert_eq!(shares.get("b"), Some(&0.25));
+
+        // a's window has now aged out
+        let shares = revenue.shares_at(start + Duration::from_secs(140));
+        assert_eq!(shares.get("a"), None);
+        assert_eq!(shares.get("b"), Some(&1.0));
+
+        // and everything, after two idle windows
+        assert!(
+            revenue
+                .shares_at(start + Duration::from_secs(300))
+                .is_empty()
+        );
+    }
+}
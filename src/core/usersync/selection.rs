@@ -0,0 +1,243 @@
+use crate::app::config::{SyncOrder, UserSyncConfig};
+use crate::core::models::bidder::Bidder;
+use crate::core::usersync::model::SyncEntry;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Whether the entry is still fresh for the bidder, per its
+/// resync interval or else the exchange wide freshness
+pub fn is_fresh(entry: &SyncEntry, bidder: &Bidder, config: &UserSyncConfig, now_ms: u64) -> bool {
+    let fresh_for = bidder
+        .usersync
+        .as_ref()
+        .and_then(|sync| sync.resync_interval)
+        .unwrap_or(config.fresh_for);
+
+    now_ms.saturating_sub(entry.ts) < fresh_for.as_millis() as u64
+}
+
+/// Weighted random sort key, larger keys first. Sorting by u^(1/w)
+/// samples without replacement proportionally to the weights
+fn weighted_key(weight: f64) -> f64 {
+    if weight > 0.0 {
+        fastrand::f64().powf(1.0 / weight)
+    } else {
+        0.0
+    }
+}
+
+/// Weighted order weight of a bidder, its configured weight scaled by
+/// its share of recent revenue plus the exploration share, so bidders
+/// without recent revenue are still synced. With no revenue recorded
+/// at all, bidders are picked by their configured weights alone
+fn sync_weight(
+    bidder: &Bidder,
+    revenue_shares: &HashMap<String, f64>,
+    config: &UserSyncConfig,
+) -> f64 {
+    let weight = bidder.usersync.as_ref().map_or(0.0, |sync| sync.weight);
+    if revenue_shares.is_empty() {
+        return weight;
+    }
+
+    let share = revenue_shares.get(&bidder.id).copied().unwrap_or(0.0);
+
+    weight * (config.revenue_explore.max(0.0) + share)
+}
+
+/// Picks the bidders a sync call should sync, up to the limit, out
+/// of those with a sync url which aren't excluded and we don't hold
+/// a fresh buyeruid for. Ordered by the configured sync order
+///
+/// # Arguments
+/// * 'exclude' - Bidder ids not to sync, e.g. earlier in a sync chain
+/// * 'revenue_shares' - Each bidder's share of recent revenue, see
+/// ['super::SyncRevenue::shares'], used by weighted order
+/// * 'rotation' - Position in the bidder list rotated order starts
+/// from, advanced by the caller on every call
+pub fn select_sync_bidders(
+    bidders: &[Arc<Bidder>],
+    entries: &HashMap<String, SyncEntry>,
+    exclude: &[String],
+    revenue_shares: &HashMap<String, f64>,
+    config: &UserSyncConfig,
+    limit: usize,
+    rotation: usize,
+    now_ms: u64,
+) -> Vec<Arc<Bidder>> {
+    let mut candidates: Vec<(usize, &Arc<Bidder>)> = bidders
+        .iter()
+        .enumerate()
+        .filter(|(_, bidder)| {
+            bidder
+                .usersync
+                .as_ref()
+                .is_some_and(|sync| !sync.url.trim().is_empty())
+                && !exclude.contains(&bidder.id)
+                && !entries
+                    .get(&bidder.id)
+                    .is_some_and(|entry| is_fresh(entry, bidder, config, now_ms))
+        })
+        .collect();
+
+    match config.order {
+        // stable sort, ties stay in config order
+        SyncOrder::Priority => candidates.sort_by_key(|(_, bidder)| {
+            Reverse(bidder.usersync.as_ref().map_or(0, |sync| sync.priority))
+        }),
+        SyncOrder::Rotate => {
+            let start = rotation % bidders.len().max(1);
+
+            candidates.sort_by_key(|(i, _)| (i + bidders.len() - start) % bidders.len())
+        }
+        SyncOrder::Weighted => {
+            let mut keyed: Vec<(f64, (usize, &Arc<Bidder>))> = candidates
+                .into_iter()
+                .map(|c| (weighted_key(sync_weight(c.1, revenue_shares, config)), c))
+                .collect();
+
+            keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+            candidates = keyed.into_iter().map(|(_, c)| c).collect();
+        }
+    }
+
+    candidates
+        .into_iter()
+        .take(limit)
+        .map(|(_, bidder)| bidder.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::sync::{SyncConfig, SyncKind};
+    use std::time::Duration;
+
+    const NOW_MS: u64 = 10_000_000_000;
+
+    fn bidder(id: &str, priority: u32, weight: f64) -> Arc<Bidder> {
+        Arc::new(Bidder {
+            id: id.to_string(),
+            usersync: Some(SyncConfig {
+                url: format!("https://{id}.example/sync"),
+                kind: SyncKind::Image,
+                priority,
+                weight,
+                resync_interval: None,
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn entry(age: Duration) -> SyncEntry {
+        SyncEntry {
+            ts: NOW_MS - age.as_millis() as u64,
+            rid: "buyer-uid".into(),
+        }
+    }
+
+    fn ids(selected: Vec<Arc<Bidder>>) -> Vec<String> {
+        selected.iter().map(|b| b.id.clone()).collect()
+    }
+
+    #[test]
+    fn skips_fresh_bidders_per_resync_interval() {
+        let mut short = (*bidder("short", 0, 1.0)).clone();
+        if let Some(sync) = short.usersync.as_mut() {
+            sync.resync_interval = Some(Duration::from_hours(1));
+        }
+
+        let bidders = vec![bidder("a", 0, 1.0), Arc::new(short), bidder("c", 0, 1.0)];
+        let config = UserSyncConfig::default();
+        let no_revenue = HashMap::new();
+
+        let entries = HashMap::from([
+            // within the default freshness
+            ("a".to_string(), entry(Duration::from_hours(2))),
+            // stale per its own interval
+            ("short".to_string(), entry(Duration::from_hours(2))),
+        ]);
+
+        let selected =
+            select_sync_bidders(&bidders, &entries, &[], &no_revenue, &config, 10, 0, NOW_MS);
+        assert_eq!(ids(selected), ["short", "c"]);
+
+        let selected =
+            select_sync_bidders(&bidders, &entries, &[], &no_revenue, &config, 1, 0, NOW_MS);
+        assert_eq!(ids(selected), ["short"]);
+    }
+
+    #[test]
+    fn orders_by_priority_rotation_and_weight() {
+        let bidders = vec![
+            bidder("a", 1, 1.0),
+            bidder("b", 5, 0.0),
+            bidder("c", 5, 1.0),
+        ];
+        let entries = HashMap::new();
+        let no_revenue = HashMap::new();
+        let mut config = UserSyncConfig::default();
+
+        let selected =
+            select_sync_bidders(&bidders, &entries, &[], &no_revenue, &config, 10, 0, NOW_MS);
+        assert_eq!(ids(selected), ["b", "c", "a"]);
+
+        config.order = SyncOrder::Rotate;
+        let selected =
+            select_sync_bidders(&bidders, &entries, &[], &no_revenue, &config, 10, 2, NOW_MS);
+        assert_eq!(ids(selected), ["c", "a", "b"]);
+
+        // zero weight always last
+        config.order = SyncOrder::Weighted;
+        let selected =
+            select_sync_bidders(&bidders, &entries, &[], &no_revenue, &config, 10, 0, NOW_MS);
+        assert_eq!(selected[2].id, "b");
+    }
+
+    #[test]
+    fn weighted_order_follows_revenue() {
+        let bidders = vec![
+            bidder("a", 0, 1.0),
+            bidder("b", 0, 1.0),
+            bidder("c", 0, 1.0),
+        ];
+        let entries = HashMap::new();
+        let config = UserSyncConfig {
+            order: SyncOrder::Weighted,
+            revenue_explore: 0.0,
+            ..Default::default()
+        };
+
+        // without exploration, bidders earning nothing go last
+        let shares = HashMap::from([("c".to_string(), 1.0)]);
+        let selected =
+            select_sync_bidders(&bidders, &entries, &[], &shares, &config, 10, 0, NOW_MS);
+        assert_eq!(selected[0].id, "c");
+
+        let bidder_b = &bidders[1];
+        assert_eq!(sync_weight(bidder_b, &shares, &config), 0.0);
+
+        let config = UserSyncConfig {
+            revenue_explore: 0.1,
+            ..config
+        };
+        assert_eq!(sync_weight(bidder_b, &shares, &config), 0.1);
+        assert_eq!(sync_weight(&bidders[2], &shares, &config), 1.1);
+    }
+
+    #[test]
+    fn weighted_order_uses_configured_weights_without_revenue() {
+        let bidders = vec![bidder("a", 0, 1.0), bidder("b", 0, 3.0)];
+        let config = UserSyncConfig {
+            order: SyncOrder::Weighted,
+            revenue_explore: 0.0,
+            ..Default::default()
+        };
+        let no_revenue = HashMap::new();
+
+        assert_eq!(sync_weight(&bidders[0], &no_revenue, &config), 1.0);
+        assert_eq!(sync_weight(&bidders[1], &no_revenue, &config), 3.0);
+    }
+}
@@ -19,4 +19,8 @@ pub trait SyncStore: Send + Sync {
     /// Loads sync entries indexed by partner id under local id
     /// E.g. buyer_id 123 -> sync entry
     async fn load(&self, local_id: &String) -> Option<HashMap<String, SyncEntry>>;
+
+    /// Purges every sync entry under local id, e.g. when the
+    /// user opts out. Returns the entries which were removed
+    async fn remove(&self, local_id: &String) -> Option<HashMap<String, SyncEntry>>;
 }
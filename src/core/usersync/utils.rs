@@ -1,6 +1,7 @@
 use crate::core::models::bidder::Bidder;
 use crate::core::models::sync::{SyncConfig, SyncKind};
 use crate::core::usersync::constants;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, warn};
 use uuid::Uuid;
@@ -19,6 +20,13 @@ pub fn validate_local_id(local_id: &str) -> bool {
         .starts_with(constants::CONST_REX_USER_ID_PREFIX)
 }
 
+/// Whether the user's cookies carry our opt out cookie
+pub fn is_opted_out(cookies: &HashMap<String, String>) -> bool {
+    cookies
+        .get(constants::CONST_REX_OPTOUT_COOKIE)
+        .is_some_and(|value| value == "1")
+}
+
 /// Generate a local buyer id value with our
 /// ['REX_USER_PREFIX'] platform prefix
 pub fn generate_local_id() -> String {
@@ -52,6 +60,14 @@ pub fn build_kind_pixel(sync: &SyncConfig, local_uid: &String, local_uid_macro:
     }
 }
 
+/// Build a partner sync url to redirect to, placing our local
+/// exchange id in the macro value if present in the partner url
+pub fn build_sync_redirect_url(sync: &SyncConfig, local_uid: &str) -> String {
+    sync.url
+        .trim()
+        .replace(constants::CONST_REX_LOCAL_ID_MACRO, local_uid)
+}
+
 /// Builds the iframe html content for the sync pixels
 /// of the provided bidders and optionally a return to
 /// the upstream supplier to complete our supply sync
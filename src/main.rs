@@ -13,14 +13,28 @@ use tracing::info;
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    #[cfg(feature = "simulator")]
+    if args.first().map(String::as_str) == Some("simulate-shaping") {
+        if let Err(e) = app::simulate::run(&args[1..]) {
+            eprintln!("Shaping simulation failed: {:?}", e);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    serve(args.into_iter().next().unwrap_or_else(|| "rex.yaml".into()));
+}
+
 #[actix_web::main]
-async fn main() {
+async fn serve(config_file: String) {
     rustls::crypto::aws_lc_rs::default_provider()
         .install_default()
         .expect("Failed to install rustls crypto provider");
 
-    let config_file = std::env::args().nth(1).unwrap_or_else(|| "rex.yaml".into());
-
     let startup_pipeline = build_start_pipeline(config_file.into());
     let startup_ctx = StartupContext {
         server: OnceLock::new(),